@@ -0,0 +1,181 @@
+use anyhow::bail;
+use indexmap::IndexMap;
+
+use crate::{Release, ReleaseSection, ReleaseSectionNote, ReleaseTitle};
+
+/// Build a [`Release`] without knowing its representation.
+///
+/// ```
+/// use changelog_document::{Release, ReleaseSectionNote};
+///
+/// let release = Release::builder("1.2.0")
+///     .date("2024-01-01")
+///     .note(
+///         "Fixed",
+///         ReleaseSectionNote::new("crash on resume")
+///             .scope("ui")
+///             .context("details"),
+///     )
+///     .note("Fixed", ReleaseSectionNote::new("leak"))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(release.version(), "1.2.0");
+/// assert_eq!(release.note_sections["Fixed"].notes.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReleaseBuilder {
+    title: ReleaseTitle,
+    header: Option<String>,
+    sections: Vec<ReleaseSection>,
+    footer: Option<String>,
+}
+
+impl Release {
+    pub fn builder(version: impl Into<String>) -> ReleaseBuilder {
+        ReleaseBuilder {
+            title: ReleaseTitle {
+                version: version.into(),
+                release_link: None,
+                title: None,
+                annotation: None,
+                yanked: false,
+            },
+            header: None,
+            sections: Vec::new(),
+            footer: None,
+        }
+    }
+}
+
+impl ReleaseBuilder {
+    /// Text after the version, usually the release date.
+    pub fn date(mut self, date: impl Into<String>) -> Self {
+        self.title.title = Some(date.into());
+        self
+    }
+
+    /// Parenthesized text after the date, like "beta".
+    pub fn annotation(mut self, annotation: impl Into<String>) -> Self {
+        self.title.annotation = Some(annotation.into());
+        self
+    }
+
+    pub fn link(mut self, link: impl Into<String>) -> Self {
+        self.title.release_link = Some(link.into());
+        self
+    }
+
+    pub fn yanked(mut self, yanked: bool) -> Self {
+        self.title.yanked = yanked;
+        self
+    }
+
+    pub fn header(mut self, header: impl Into<String>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    /// Add a section. Two sections can't have the same title.
+    pub fn section(mut self, section: ReleaseSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// Add a note to the section titled `section`, created if needed.
+    pub fn note(mut self, section: impl Into<String>, note: ReleaseSectionNote) -> Self {
+        let section = section.into();
+
+        match self.sections.iter_mut().find(|s| s.title == section) {
+            Some(section) => section.notes.push(note),
+            None => self.sections.push(ReleaseSection {
+                title: section,
+                description: None,
+                notes: vec![note],
+            }),
+        }
+
+        self
+    }
+
+    /// Fail if the version is empty, or if two sections have the same title.
+    pub fn build(self) -> anyhow::Result<Release> {
+        if self.title.version.trim().is_empty() {
+            bail!("The version of a release can't be empty");
+        }
+
+        let mut note_sections = IndexMap::new();
+
+        for section in self.sections {
+            if note_sections.contains_key(&section.title) {
+                bail!(
+                    "The section {} of release {} is added twice",
+                    section.title,
+                    self.title.version
+                );
+            }
+
+            note_sections.insert(section.title.clone(), section);
+        }
+
+        Ok(Release {
+            title: self.title,
+            header: self.header,
+            note_sections,
+            footer: self.footer,
+        })
+    }
+}
+
+impl ReleaseSectionNote {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            scope: None,
+            message: message.into(),
+            context: Vec::new(),
+            date: None,
+        }
+    }
+
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    /// Add a line of context.
+    pub fn context(mut self, line: impl Into<String>) -> Self {
+        self.context.push(line.into());
+        self
+    }
+
+    pub fn date(mut self, date: impl Into<String>) -> Self {
+        self.date = Some(date.into());
+        self
+    }
+}
+
+impl ReleaseSection {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            description: None,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Prose written before the first note.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn note(mut self, note: ReleaseSectionNote) -> Self {
+        self.notes.push(note);
+        self
+    }
+}
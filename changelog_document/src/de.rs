@@ -1,6 +1,6 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::utils::UNRELEASED;
+use crate::utils::{NO_SECTION, UNRELEASED, YANKED};
 
 use super::*;
 use pom::parser::*;
@@ -9,30 +9,153 @@ use utils::*;
 #[derive(Debug, Clone)]
 pub struct Options {
     pub unreleased: String,
+    /// Title of a section -> its heading, like in [`ser::OptionsRelease::section_headings`].
+    /// A section written with one of these headings gets its title back.
+    pub section_headings: HashMap<String, String>,
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             unreleased: String::from(UNRELEASED),
+            section_headings: HashMap::new(),
         }
     }
 }
 
-pub fn parse_changelog_with_options(input: &str, options: &Options) -> anyhow::Result<ChangeLog> {
+/// Something the parser kept in an unexpected way, or dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// Stable identifier of the problem, like "duplicate-section".
+    pub rule: String,
+    /// Starts at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// A section title appears twice in a release.
+pub const RULE_DUPLICATE_SECTION: &str = "duplicate-section";
+/// A note after other content in a section, which is not parsed as a note.
+pub const RULE_NOTE_AFTER_CONTENT: &str = "note-after-content";
+
+/// Why a document can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input doesn't follow the grammar. `line` starts at 1.
+    Syntax { line: usize, message: String },
+    /// The Unreleased section, named after [`Options::unreleased`], appears twice.
+    MultipleUnreleased { unreleased: String },
+    /// The Unreleased section is not the first release.
+    UnreleasedNotFirst { unreleased: String },
+    /// A release title which is not a semver version.
+    InvalidVersion { version: String, message: String },
+    /// Two releases with the same version.
+    DuplicateVersion { version: String },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            ParseError::MultipleUnreleased { unreleased } => {
+                write!(f, "more than one {unreleased} section")
+            }
+            ParseError::UnreleasedNotFirst { unreleased } => {
+                write!(f, "{unreleased} section not at index 0")
+            }
+            ParseError::InvalidVersion { version, message } => {
+                write!(f, "not valid semver {version}: {message}")
+            }
+            ParseError::DuplicateVersion { version } => {
+                write!(f, "Duplicate version found: {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    fn syntax(input: &[char], error: pom::Error) -> Self {
+        let (position, message) = match &error {
+            pom::Error::Incomplete => (input.len(), error.to_string()),
+            pom::Error::Mismatch { position, .. }
+            | pom::Error::Conversion { position, .. }
+            | pom::Error::Expect { position, .. }
+            | pom::Error::Custom { position, .. } => (*position, error.to_string()),
+        };
+
+        ParseError::Syntax {
+            line: line_of(input, position),
+            message,
+        }
+    }
+}
+
+/// A warning located by its char position in the input.
+type Diagnostic = (usize, &'static str, String);
+
+pub fn parse_changelog_with_warnings(
+    input: &str,
+    options: &Options,
+) -> Result<(ChangeLog, Vec<ParseWarning>), ParseError> {
+    parse_keep_a_changelog(input, options)
+}
+
+pub(crate) fn parse_keep_a_changelog(
+    input: &str,
+    options: &Options,
+) -> Result<(ChangeLog, Vec<ParseWarning>), ParseError> {
     let input = input.chars().collect::<Vec<_>>();
     let parser = changelog_parser(options);
-    let changelog = parser.parse(&input)?;
+    let (changelog, diagnostics) = parser
+        .parse(&input)
+        .map_err(|e| ParseError::syntax(&input, e))??;
+
+    let warnings = diagnostics
+        .into_iter()
+        .map(|(pos, rule, message)| ParseWarning {
+            rule: rule.to_owned(),
+            line: line_of(&input, pos),
+            message,
+        })
+        .collect();
+
+    Ok((changelog, warnings))
+}
 
-    Ok(changelog)
+/// Line of the char at `pos`. Starts at 1.
+fn line_of(input: &[char], pos: usize) -> usize {
+    input[..pos.min(input.len())]
+        .iter()
+        .filter(|c| **c == '\n')
+        .count()
+        + 1
 }
 
-pub fn parse_changelog(input: &str) -> anyhow::Result<ChangeLog> {
+pub fn parse_changelog_with_options(
+    input: &str,
+    options: &Options,
+) -> Result<ChangeLog, ParseError> {
+    parse_keep_a_changelog(input, options).map(|(changelog, _)| changelog)
+}
+
+pub fn parse_changelog(input: &str) -> Result<ChangeLog, ParseError> {
     parse_changelog_with_options(input, &Options::default())
 }
 
-pub(crate) fn changelog_parser(options: &Options) -> Parser<'_, char, ChangeLog> {
-    let header = (!call(release) * any()).repeat(0..).convert(|header| {
+/// The semantic checks, like the uniqueness of the versions, are reported in the output,
+/// to keep their [`ParseError`] variant.
+pub(crate) fn changelog_parser(
+    options: &Options,
+) -> Parser<'_, char, Result<(ChangeLog, Vec<Diagnostic>), ParseError>> {
+    let header = lines_until(call(release)).convert(|header| {
         let header = into_string(header);
 
         if header.is_empty() {
@@ -42,21 +165,39 @@ pub(crate) fn changelog_parser(options: &Options) -> Parser<'_, char, ChangeLog>
         }
     });
 
-    let parser = header + release().repeat(0..) + footer_links();
+    let parser = header + located_release().repeat(0..) + footer_links();
 
-    parser.convert(|((header, releases_vec), footer_links)| {
+    parser.map(|((header, releases_vec), footer_links)| {
         let mut releases = BTreeMap::new();
 
         let mut unreleased = None;
 
-        for (pos, release) in releases_vec.into_iter().enumerate() {
-            if release.title.version == options.unreleased {
+        let mut diagnostics = Vec::new();
+
+        for (pos, (mut release, release_diagnostics)) in releases_vec.into_iter().enumerate() {
+            diagnostics.extend(release_diagnostics);
+
+            if !options.section_headings.is_empty() {
+                title_sections(&mut release, &options.section_headings);
+            }
+
+            if release
+                .title
+                .version
+                .eq_ignore_ascii_case(&options.unreleased)
+            {
+                release.title.version.clone_from(&options.unreleased);
+
                 if unreleased.is_some() {
-                    return Err(format!("more than one {} section", options.unreleased));
+                    return Err(ParseError::MultipleUnreleased {
+                        unreleased: options.unreleased.clone(),
+                    });
                 }
 
                 if pos != 0 {
-                    return Err(format!("{} section not at index 0", options.unreleased));
+                    return Err(ParseError::UnreleasedNotFirst {
+                        unreleased: options.unreleased.clone(),
+                    });
                 }
 
                 unreleased = Some(release);
@@ -65,11 +206,18 @@ pub(crate) fn changelog_parser(options: &Options) -> Parser<'_, char, ChangeLog>
 
             let version = match Version::from_str(&release.title.version) {
                 Ok(v) => v,
-                Err(e) => return Err(format!("not valid semver {e}")),
+                Err(e) => {
+                    return Err(ParseError::InvalidVersion {
+                        version: release.title.version,
+                        message: e.to_string(),
+                    })
+                }
             };
 
-            if releases.insert(version, release).is_some() {
-                return Err("Duplicate version found".to_string());
+            if releases.insert(version.clone(), release).is_some() {
+                return Err(ParseError::DuplicateVersion {
+                    version: version.to_string(),
+                });
             }
         }
 
@@ -80,24 +228,57 @@ pub(crate) fn changelog_parser(options: &Options) -> Parser<'_, char, ChangeLog>
             footer_links,
         };
 
-        Ok::<ChangeLog, String>(res)
+        Ok((res, diagnostics))
     })
 }
 
+/// Replace the headings of `headings` by their section title.
+/// A section written both under its heading and under its title gets the notes of both.
+fn title_sections(release: &mut Release, headings: &HashMap<String, String>) {
+    for (heading, mut section) in std::mem::take(&mut release.note_sections) {
+        let title = match headings.iter().find(|(_, h)| h.trim() == heading.trim()) {
+            Some((title, _)) => title.clone(),
+            None => heading,
+        };
+
+        match release.note_sections.get_mut(&title) {
+            Some(existing) => {
+                existing.description = match (existing.description.take(), section.description) {
+                    (Some(first), Some(second)) => Some(format!("{first}\n\n{second}")),
+                    (first, second) => first.or(second),
+                };
+                existing.notes.append(&mut section.notes);
+            }
+            None => {
+                section.title.clone_from(&title);
+                release.note_sections.insert(title, section);
+            }
+        }
+    }
+}
+
 pub(crate) fn release_title<'a>() -> Parser<'a, char, ReleaseTitle> {
     let version = sym('#').repeat(2) * sym(' ') * sym('[') * none_of("\n]").repeat(1..) - sym(']');
 
     let release_link = sym('(') * none_of("\n)").repeat(1..) - sym(')');
 
-    let title = sym(' ') * sym('-') * sym(' ') * none_of("\n]").repeat(1..);
+    let yanked = || one_of(" \t").repeat(0..) * tag(YANKED);
+
+    // tolerate an empty title, like "## [Unreleased] - "
+    let title = sym(' ') * sym('-') * (!yanked() * none_of("\n]")).repeat(0..);
+
+    let parser =
+        version + release_link.opt() + title.opt() + yanked().opt() - one_of(" \t").repeat(0..);
 
-    let parser = version + release_link.opt() + title.opt();
+    parser.convert(|(((version, release_link), title), yanked)| {
+        let (title, annotation) = split_annotation(&title.map(into_string).unwrap_or_default());
 
-    parser.convert(|((version, release_link), title)| {
         let res = ReleaseTitle {
             version: into_string(version),
-            title: title.map(into_string),
+            title,
+            annotation,
             release_link: release_link.map(into_string),
+            yanked: yanked.is_some(),
         };
 
         Ok::<ReleaseTitle, ()>(res)
@@ -105,13 +286,16 @@ pub(crate) fn release_title<'a>() -> Parser<'a, char, ReleaseTitle> {
 }
 
 pub(crate) fn release_section_note<'a>() -> Parser<'a, char, ReleaseSectionNote> {
-    let scope = none_of(" \t\r`:\n").repeat(1..) - sym(':');
+    // the scope ends at the first ": ", and can't contain a code span or a link,
+    // so colons inside them, like in `a::b` or https://, stay in the message
+    let scope = none_of(" \t\r`:\n[]<>").repeat(1..) - sym(':') - one_of(" \t");
 
     let context_line = one_of(" \t") * none_of("\n").repeat(1..) - sym('\n');
 
     let context = context_line.repeat(0..);
 
-    let parser = spaceline() * sym('-') * sym(' ') * scope.opt() + none_of("\n").repeat(1..)
+    // "*" and "+" bullets are accepted, but serialized as "-"
+    let parser = spaceline() * one_of("-*+") * sym(' ') * scope.opt() + none_of("\n").repeat(1..)
         - sym('\n')
         + context;
 
@@ -120,20 +304,62 @@ pub(crate) fn release_section_note<'a>() -> Parser<'a, char, ReleaseSectionNote>
             scope: scope.map(into_string),
             message: into_string(note),
             context: context.into_iter().map(into_string).collect(),
+            date: None,
         };
 
         Ok::<ReleaseSectionNote, ()>(res)
     })
 }
 
+/// Split the trailing parenthesized annotation of a title, like "2024-01-01 (beta)".
+fn split_annotation(title: &str) -> (Option<String>, Option<String>) {
+    let non_empty = |s: &str| Some(s.trim().to_owned()).filter(|s| !s.is_empty());
+
+    if let Some(rest) = title.strip_suffix(')') {
+        if let Some(pos) = rest.rfind('(') {
+            let before = &rest[..pos];
+
+            if before.is_empty() || before.ends_with([' ', '\t']) {
+                if let Some(annotation) = non_empty(&rest[pos + 1..]) {
+                    return (non_empty(before), Some(annotation));
+                }
+            }
+        }
+    }
+
+    (non_empty(title), None)
+}
+
 pub(crate) fn release_section<'a>() -> Parser<'a, char, ReleaseSection> {
     let title = space() * sym('#').repeat(3) * sym(' ') * none_of("\n").repeat(1..) - sym('\n');
 
-    let parser = title - space() + release_section_note().repeat(0..);
+    let bullet = one_of(" \t").repeat(0..) * one_of("-*+") * sym(' ');
+
+    let description_line = (!bullet * !sym('#') * !call(footer_link) * none_of("\n").repeat(1..)
+        - (sym('\n').discard() | end()))
+    .map(|mut line| {
+        line.push('\n');
+        line
+    });
+
+    let blank_line = one_of(" \t\r").repeat(0..) * sym('\n').map(|c| vec![c]);
+
+    let description = (description_line | blank_line).repeat(0..).map(|lines| {
+        let description = into_string(lines.concat());
 
-    parser.convert(|(title, notes)| {
+        if description.is_empty() {
+            None
+        } else {
+            Some(description)
+        }
+    });
+
+    let parser = title - space() + description + release_section_note().repeat(0..);
+
+    parser.convert(|((title, description), notes)| {
         let res = ReleaseSection {
             title: into_string(title),
+            description,
             notes,
         };
 
@@ -142,48 +368,117 @@ pub(crate) fn release_section<'a>() -> Parser<'a, char, ReleaseSection> {
 }
 
 pub(crate) fn release<'a>() -> Parser<'a, char, Release> {
-    let header = ((!call(release_title) + !call(release_section) + !call(footer_links)) * any())
-        .repeat(0..)
-        .convert(|header| {
-            let header = into_string(header);
-
-            if header.is_empty() {
-                Ok::<_, ()>(None)
-            } else {
-                Ok(Some(header))
-            }
-        });
+    located_release().map(|(release, _)| release)
+}
 
-    let footer = ((!call(release_title) + !call(release_section) + !call(footer_links)) * any())
-        .repeat(0..)
-        .convert(|footer| {
-            let footer = into_string(footer);
+/// Parse a release, and report what could be lost along the way.
+fn located_release<'a>() -> Parser<'a, char, (Release, Vec<Diagnostic>)> {
+    // a note at the start of a line ends the header: the notes of a release without sections
+    let header = lines_until(
+        call(release_title).discard()
+            | call(release_section).discard()
+            | call(footer_links).discard()
+            | (one_of("-*+") * sym(' ')).discard(),
+    )
+    .convert(|header| {
+        let header = into_string(header);
+
+        if header.is_empty() {
+            Ok::<_, ()>(None)
+        } else {
+            Ok(Some(header))
+        }
+    });
 
-            if footer.is_empty() {
-                Ok::<_, ()>(None)
-            } else {
-                Ok(Some(footer))
+    let footer = lines_until(
+        call(release_title).discard()
+            | call(release_section).discard()
+            | call(footer_links).discard(),
+    );
+
+    let sectionless = release_section_note().repeat(1..).opt();
+
+    let parser = release_title()
+        + header
+        + sectionless
+        + (space() * empty().pos() + release_section()).repeat(0..)
+        + empty().pos()
+        + footer;
+
+    parser.convert(
+        |(((((title, header), sectionless), sections), footer_pos), footer)| {
+            let mut diagnostics = Vec::new();
+
+            let mut notes = IndexMap::new();
+
+            if let Some(sectionless) = sectionless {
+                notes.insert(
+                    NO_SECTION.to_owned(),
+                    ReleaseSection {
+                        title: NO_SECTION.to_owned(),
+                        description: None,
+                        notes: sectionless,
+                    },
+                );
             }
-        });
 
-    let parser = release_title() + header + release_section().repeat(0..) + footer;
+            for (pos, section) in sections.into_iter() {
+                if notes.contains_key(&section.title) {
+                    diagnostics.push((
+                        pos,
+                        RULE_DUPLICATE_SECTION,
+                        format!(
+                            "section \"{}\" of release {} appears twice, the first one was dropped",
+                            section.title, title.version
+                        ),
+                    ));
+                }
 
-    parser.convert(|(((title, header), sections), footer)| {
-        let mut notes = IndexMap::new();
+                notes.insert(section.title.clone(), section);
+            }
 
-        for section in sections.into_iter() {
-            notes.insert(section.title.clone(), section);
-        }
+            if let Some((section, _)) = notes.last() {
+                let mut offset = 0;
+
+                for line in footer.split(|c| *c == '\n') {
+                    let line_str = line.iter().collect::<String>();
+
+                    if ["- ", "* ", "+ "]
+                        .iter()
+                        .any(|bullet| line_str.trim_start().starts_with(bullet))
+                    {
+                        diagnostics.push((
+                            footer_pos + offset,
+                            RULE_NOTE_AFTER_CONTENT,
+                            format!(
+                                "note after other content in section \"{section}\" of release {}, \
+                                 kept as raw text",
+                                title.version
+                            ),
+                        ));
+                        break;
+                    }
+
+                    offset += line.len() + 1;
+                }
+            }
 
-        let res = Release {
-            title,
-            header,
-            note_sections: notes,
-            footer,
-        };
+            let footer = into_string(footer);
 
-        Ok::<Release, ()>(res)
-    })
+            let res = Release {
+                title,
+                header,
+                note_sections: notes,
+                footer: if footer.is_empty() {
+                    None
+                } else {
+                    Some(footer)
+                },
+            };
+
+            Ok::<_, ()>((res, diagnostics))
+        },
+    )
 }
 
 pub(crate) fn footer_link<'a>() -> Parser<'a, char, FooterLink> {
@@ -226,4 +521,36 @@ pub(crate) mod utils {
     pub fn spaceline<'a>() -> Parser<'a, char, ()> {
         one_of(" \n").repeat(0..).discard()
     }
+
+    /// Whole lines, up to the first line where `stop` matches.
+    /// `stop` is only tried at the start of a line, so it can't cut a line of prose,
+    /// nor a fenced code block.
+    pub fn lines_until<'a, O: 'a>(stop: Parser<'a, char, O>) -> Parser<'a, char, Vec<char>> {
+        (fenced_block() | (!stop * line()))
+            .repeat(0..)
+            .map(|lines| lines.concat())
+    }
+
+    /// One line, with its line break if any.
+    fn line<'a>() -> Parser<'a, char, Vec<char>> {
+        (none_of("\n").repeat(1..) + sym('\n').opt()).map(|(mut line, new_line)| {
+            line.extend(new_line);
+            line
+        }) | sym('\n').map(|c| vec![c])
+    }
+
+    /// A fenced code block, from its opening "```" or "~~~" line to its closing one.
+    /// Without closing fence, it is not a code block.
+    fn fenced_block<'a>() -> Parser<'a, char, Vec<char>> {
+        let fence = || one_of(" \t").repeat(0..) * (seq(&['`', '`', '`']) | seq(&['~', '~', '~']));
+
+        (-fence() * line() + (!fence() * line()).repeat(0..) + -fence() * line()).map(
+            |((open, lines), close)| {
+                let mut block = open;
+                block.extend(lines.concat());
+                block.extend(close);
+                block
+            },
+        )
+    }
 }
@@ -2,7 +2,7 @@ use std::mem;
 
 use indexmap::{IndexMap, IndexSet};
 
-use crate::{ChangeLog, Release, ReleaseSectionNote};
+use crate::{utils::NO_SECTION, ChangeLog, Release, ReleaseSectionNote};
 
 #[derive(Debug, Clone, Default)]
 pub struct Options {
@@ -17,10 +17,20 @@ impl ChangeLog {
             unreleased.sort_notes(&options.sort_options);
         }
 
+        // the versioned releases keep the section order they were written with
+        let release_options = SortOptions {
+            section_order: if options.sort_options.reorder_releases {
+                options.sort_options.section_order.clone()
+            } else {
+                Vec::new()
+            },
+            ..options.sort_options.clone()
+        };
+
         for release in self.releases.values_mut() {
             release.deduplicate();
             release.remove_empty();
-            release.sort_notes(&options.sort_options);
+            release.sort_notes(&release_options);
         }
 
         self.unreleased_or_default();
@@ -50,15 +60,38 @@ impl Release {
         self.note_sections.retain(|_, section| {
             section.notes.retain(|n| !n.message.is_empty());
 
-            !section.notes.is_empty()
+            !section.notes.is_empty() || section.description.is_some()
         });
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct SortOptions {
+    /// Applied to Unreleased, and to the versioned releases if `reorder_releases` is set.
     pub section_order: Vec<String>,
     pub sort_scope: bool,
+    /// Sort the sections of the versioned releases too.
+    pub reorder_releases: bool,
+}
+
+/// Sections of [Keep a Changelog](https://keepachangelog.com), in their canonical order.
+pub const KEEP_A_CHANGELOG_SECTIONS: [&str; 6] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+impl SortOptions {
+    /// Sort sections in the Keep a Changelog order.
+    pub fn keep_a_changelog() -> Self {
+        Self {
+            section_order: KEEP_A_CHANGELOG_SECTIONS.map(String::from).to_vec(),
+            ..Default::default()
+        }
+    }
 }
 
 impl Default for SortOptions {
@@ -66,6 +99,7 @@ impl Default for SortOptions {
         Self {
             section_order: Default::default(),
             sort_scope: true,
+            reorder_releases: false,
         }
     }
 }
@@ -78,6 +112,11 @@ impl Release {
 
             let mut section_cloned = self.note_sections.clone();
 
+            // the notes without section are written before the first heading
+            if let Some((key, section)) = section_cloned.shift_remove_entry(NO_SECTION) {
+                sorted.insert(key, section);
+            }
+
             for section in &options.section_order {
                 if let Some((key, section)) = section_cloned.shift_remove_entry(section) {
                     sorted.insert(key, section);
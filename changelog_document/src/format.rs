@@ -0,0 +1,35 @@
+use crate::{de, ser, ChangeLog};
+
+/// A changelog syntax. The [`ChangeLog`] is the common representation of all formats.
+pub trait ChangelogFormat {
+    fn parse(&self, input: &str) -> anyhow::Result<ChangeLog>;
+
+    fn serialize(&self, changelog: &ChangeLog) -> String;
+}
+
+/// The [Keep a Changelog](https://keepachangelog.com) format. The default one.
+#[derive(Debug, Clone, Default)]
+pub struct KeepAChangelog {
+    pub de_options: de::Options,
+    pub ser_options: ser::Options,
+}
+
+impl ChangelogFormat for KeepAChangelog {
+    fn parse(&self, input: &str) -> anyhow::Result<ChangeLog> {
+        Ok(de::parse_changelog_with_options(input, &self.de_options)?)
+    }
+
+    fn serialize(&self, changelog: &ChangeLog) -> String {
+        ser::serialize_keep_a_changelog(changelog, &self.ser_options)
+    }
+}
+
+impl ChangeLog {
+    pub fn parse_with(input: &str, format: &impl ChangelogFormat) -> anyhow::Result<Self> {
+        format.parse(input)
+    }
+
+    pub fn serialize_with(&self, format: &impl ChangelogFormat) -> String {
+        format.serialize(self)
+    }
+}
@@ -1,39 +1,76 @@
+#![doc = include_str!("../README.md")]
+
 use std::collections::BTreeMap;
 
 use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 
+mod builder;
 pub mod de;
 pub mod fmt;
+pub mod format;
+pub mod query;
 pub mod ser;
 pub mod utils;
 mod version;
+pub use builder::ReleaseBuilder;
 pub use semver;
 pub use version::Version;
 
+#[cfg(test)]
+mod roundtrip;
 #[cfg(test)]
 mod test;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReleaseTitle {
     pub version: String,
     pub release_link: Option<String>,
     pub title: Option<String>,
+    /// Trailing parenthesized text of the title, like "beta" in `## [1.2.0] - 2024-01-01 (beta)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+    /// Marked with "[YANKED]", after the title.
+    #[serde(default)]
+    pub yanked: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReleaseSection {
     pub title: String,
+    /// Prose written before the first note.
+    pub description: Option<String>,
     pub notes: Vec<ReleaseSectionNote>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReleaseSectionNote {
     pub scope: Option<String>,
     pub message: String,
     pub context: Vec<String>,
+    /// Date of the commit of the note, in ISO 8601, like "2024-01-02T09:00:00+00:00".
+    /// Only known while generating: it is not written, and not compared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+impl PartialEq for ReleaseSectionNote {
+    fn eq(&self, other: &Self) -> bool {
+        self.scope == other.scope && self.message == other.message && self.context == other.context
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl Eq for ReleaseSectionNote {}
+
+impl std::hash::Hash for ReleaseSectionNote {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.scope.hash(state);
+        self.message.hash(state);
+        self.context.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Release {
     pub title: ReleaseTitle,
     pub header: Option<String>,
@@ -41,18 +78,18 @@ pub struct Release {
     pub footer: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FooterLink {
     pub text: String,
     pub link: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FooterLinks {
     pub links: Vec<FooterLink>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ChangeLog {
     pub header: Option<String>,
     pub unreleased: Option<Release>,
@@ -71,31 +108,13 @@ impl ChangeLog {
     pub fn new() -> Self {
         let mut releases = BTreeMap::new();
 
-        let unreleased = Release {
-            title: ReleaseTitle {
-                version: String::from("Unreleased"),
-                title: None,
-                release_link: None,
-            },
-            header: None,
-            note_sections: IndexMap::new(),
-            footer: None,
-        };
+        let unreleased = Release::builder("Unreleased").build().unwrap();
 
         let version = Version::new(0, 1, 0);
 
         releases.insert(
             version.clone(),
-            Release {
-                title: ReleaseTitle {
-                    version: version.to_string(),
-                    title: None,
-                    release_link: None,
-                },
-                header: None,
-                note_sections: IndexMap::new(),
-                footer: None,
-            },
+            Release::builder(version.to_string()).build().unwrap(),
         );
 
         ChangeLog {
@@ -0,0 +1,75 @@
+use regex::Regex;
+
+use crate::{ChangeLog, ReleaseSectionNote, Version};
+
+/// A note of a changelog, with the release and section it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteRef<'a> {
+    /// `None` for the Unreleased section.
+    pub version: Option<&'a Version>,
+    pub section: &'a str,
+    pub note: &'a ReleaseSectionNote,
+}
+
+impl ChangeLog {
+    /// All the notes, in document order: Unreleased first, then the releases, the newest first.
+    ///
+    /// ```
+    /// use changelog_document::{de::parse_changelog, query::NoteFilter};
+    ///
+    /// let changelog = parse_changelog(
+    ///     "## [1.1.0]\n\n### Fixed\n\n- panic on resume\n\n## [1.0.0]\n\n### Fixed\n\n- panic on start\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let regex = regex::Regex::new("panic").unwrap();
+    /// let since = "1.0.0".parse().unwrap();
+    ///
+    /// let notes = changelog
+    ///     .notes()
+    ///     .since_version(&since)
+    ///     .matching(&regex)
+    ///     .map(|n| n.note.message.as_str())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(notes, vec!["panic on resume"]);
+    /// ```
+    pub fn notes(&self) -> impl Iterator<Item = NoteRef<'_>> {
+        let unreleased = self.unreleased.iter().map(|release| (None, release));
+        let releases = self
+            .releases_full()
+            .map(|(version, release)| (Some(version), release));
+
+        unreleased.chain(releases).flat_map(|(version, release)| {
+            release.note_sections.values().flat_map(move |section| {
+                section.notes.iter().map(move |note| NoteRef {
+                    version,
+                    section: &section.title,
+                    note,
+                })
+            })
+        })
+    }
+}
+
+/// Filters over [`ChangeLog::notes`]. They are lazy, and can be chained.
+pub trait NoteFilter<'a>: Iterator<Item = NoteRef<'a>> + Sized {
+    /// The notes of the releases newer than `version`, and of Unreleased.
+    fn since_version(self, version: &Version) -> impl Iterator<Item = NoteRef<'a>> {
+        self.filter(move |n| n.version.is_none_or(|v| v > version))
+    }
+
+    /// The notes of the section titled `section`, ignoring the case.
+    fn in_section(self, section: &str) -> impl Iterator<Item = NoteRef<'a>> {
+        self.filter(move |n| n.section.eq_ignore_ascii_case(section))
+    }
+
+    /// The notes whose message, or a line of context, matches `regex`.
+    fn matching(self, regex: &Regex) -> impl Iterator<Item = NoteRef<'a>> {
+        self.filter(move |n| {
+            regex.is_match(&n.note.message) || n.note.context.iter().any(|c| regex.is_match(c))
+        })
+    }
+}
+
+impl<'a, I: Iterator<Item = NoteRef<'a>>> NoteFilter<'a> for I {}
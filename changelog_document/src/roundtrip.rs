@@ -0,0 +1,195 @@
+//! Property tests of the round trip between the model and the markdown.
+//! Shrunk counterexamples are saved under `proptest-regressions/`, and replayed first.
+
+use std::{collections::BTreeMap, fs::read_dir};
+
+use indexmap::IndexMap;
+use pretty_assertions::assert_eq;
+use proptest::{collection::vec, option, prelude::*, sample::subsequence};
+
+use crate::*;
+use de::parse_changelog;
+use ser::{serialize_changelog, Options};
+
+const SECTIONS: [&str; 8] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+    "Documentation",
+    "Performance",
+];
+
+fn words() -> impl Strategy<Value = String> {
+    vec("[a-z0-9][a-z0-9.,()`'#/-]{0,8}", 1..6).prop_map(|words| words.join(" "))
+}
+
+fn note() -> impl Strategy<Value = ReleaseSectionNote> {
+    (
+        option::of("[a-z][a-z0-9-]{0,7}"),
+        words(),
+        vec(words(), 0..3),
+    )
+        .prop_map(|(scope, message, context)| ReleaseSectionNote {
+            scope,
+            message,
+            context,
+            date: None,
+        })
+}
+
+fn sections() -> impl Strategy<Value = IndexMap<String, ReleaseSection>> {
+    subsequence(SECTIONS.to_vec(), 0..4)
+        .prop_flat_map(|titles| {
+            titles
+                .into_iter()
+                .map(|title| {
+                    (option::of(words()), vec(note(), 1..4)).prop_map(
+                        move |(description, notes)| ReleaseSection {
+                            title: title.to_owned(),
+                            description,
+                            notes,
+                        },
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .prop_map(|sections| {
+            sections
+                .into_iter()
+                .map(|section| (section.title.clone(), section))
+                .collect()
+        })
+}
+
+/// Like [`sections`], with sometimes notes without section, at any position.
+fn sections_and_sectionless() -> impl Strategy<Value = IndexMap<String, ReleaseSection>> {
+    (
+        sections(),
+        option::of(vec(note(), 1..3)),
+        any::<prop::sample::Index>(),
+    )
+        .prop_map(|(sections, sectionless, index)| {
+            let Some(notes) = sectionless else {
+                return sections;
+            };
+
+            let mut sections = sections.into_iter().collect::<Vec<_>>();
+
+            // a description would be read as the header of the release
+            let section = ReleaseSection {
+                title: utils::NO_SECTION.to_owned(),
+                description: None,
+                notes,
+            };
+
+            sections.insert(
+                index.index(sections.len() + 1),
+                (section.title.clone(), section),
+            );
+
+            sections.into_iter().collect()
+        })
+}
+
+fn release(version: String) -> impl Strategy<Value = Release> {
+    (
+        option::of("20[0-9]{2}-[01][0-9]-[0-3][0-9]"),
+        option::of("[a-z]{1,5}"),
+        option::of("https://example\\.com/[a-z]{1,5}"),
+        any::<bool>(),
+        option::of(words()),
+        sections_and_sectionless(),
+        option::of(words()),
+    )
+        .prop_map(
+            move |(date, annotation, release_link, yanked, header, note_sections, footer)| {
+                Release {
+                    title: ReleaseTitle {
+                        version: version.clone(),
+                        release_link,
+                        title: date,
+                        annotation,
+                        yanked,
+                    },
+                    header,
+                    // without sections, the footer can't be told apart from the header
+                    footer: footer.filter(|_| !note_sections.is_empty()),
+                    note_sections,
+                }
+            },
+        )
+}
+
+fn changelog() -> impl Strategy<Value = ChangeLog> {
+    let versions = vec((0..3u64, 0..10u64, 0..10u64), 0..5);
+
+    (
+        option::of(Just("# Changelog".to_owned())),
+        option::of(sections_and_sectionless()),
+        versions,
+    )
+        .prop_flat_map(|(header, unreleased, versions)| {
+            let versions = versions
+                .into_iter()
+                .map(|(major, minor, patch)| Version::new(major, minor, patch))
+                .collect::<std::collections::BTreeSet<_>>();
+
+            let releases = versions
+                .iter()
+                .map(|version| {
+                    let version = version.clone();
+                    release(version.to_string()).prop_map(move |release| (version.clone(), release))
+                })
+                .collect::<Vec<_>>();
+
+            let len = versions.len();
+            let links = subsequence(versions.into_iter().collect::<Vec<_>>(), 0..=len);
+
+            (Just(header), Just(unreleased), releases, links)
+        })
+        .prop_map(|(header, unreleased, releases, links)| ChangeLog {
+            header,
+            unreleased: unreleased.map(|note_sections| Release {
+                note_sections,
+                ..utils::DEFAULT_UNRELEASED.clone()
+            }),
+            releases: releases.into_iter().collect::<BTreeMap<_, _>>(),
+            footer_links: FooterLinks {
+                links: links
+                    .into_iter()
+                    .rev()
+                    .map(|version| FooterLink {
+                        text: version.to_string(),
+                        link: format!("https://example.com/compare/{version}"),
+                    })
+                    .collect(),
+            },
+        })
+}
+
+proptest! {
+    #[test]
+    fn model_roundtrip(changelog in changelog()) {
+        let output = serialize_changelog(&changelog, &Options::default());
+
+        let parsed = parse_changelog(&output).unwrap();
+
+        prop_assert_eq!(parsed, changelog, "serialized as:\n{}", output);
+    }
+}
+
+#[test]
+fn real_documents_idempotent() {
+    for entry in read_dir("../tests/changelogs").unwrap() {
+        let path = entry.unwrap().path();
+        let input = std::fs::read_to_string(&path).unwrap();
+
+        let first = serialize_changelog(&parse_changelog(&input).unwrap(), &Options::default());
+        let second = serialize_changelog(&parse_changelog(&first).unwrap(), &Options::default());
+
+        assert_eq!(first, second, "{}", path.display());
+    }
+}
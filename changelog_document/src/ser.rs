@@ -1,6 +1,13 @@
-use crate::*;
-
-// todo: use io::Write
+use crate::{
+    format::{ChangelogFormat, KeepAChangelog},
+    utils::{NO_SECTION, YANKED},
+    *,
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{self, Write},
+};
 
 #[derive(Debug, Clone, Default)]
 pub struct Options {
@@ -10,121 +17,300 @@ pub struct Options {
 #[derive(Debug, Clone)]
 pub struct OptionsRelease {
     pub serialize_title: bool,
+    /// Written before each note. Context lines are indented by its width.
+    pub bullet_prefix: String,
+    /// Skip the sections without notes nor description.
+    pub omit_empty_sections: bool,
+    /// Uppercase the first letter of the messages. A message starting with a code span is kept.
+    pub capitalize_first: bool,
+    /// Remove the period ending the messages, before their PR link and acknowledgement.
+    /// An ellipsis is kept.
+    pub strip_trailing_period: bool,
+    /// Write the notes of all the sections as one list under the release title,
+    /// without the section headings and descriptions.
+    pub flatten_sections: bool,
+    /// Heading written for a section, like "✨ Features" for "Added". A section not in the map
+    /// is written with its title. Parse with the same map in [`de::Options`] to get the titles back.
+    pub section_headings: HashMap<String, String>,
+    /// Write the notes of each section by date, the most recent first.
+    /// The notes without date keep their order, after the other ones.
+    pub sort_notes_by_date: bool,
 }
 
 impl Default for OptionsRelease {
     fn default() -> Self {
         Self {
             serialize_title: true,
+            bullet_prefix: String::from("- "),
+            omit_empty_sections: true,
+            capitalize_first: false,
+            strip_trailing_period: false,
+            flatten_sections: false,
+            section_headings: HashMap::new(),
+            sort_notes_by_date: false,
         }
     }
 }
 
 pub fn serialize_changelog(changelog: &ChangeLog, options: &Options) -> String {
-    let mut s = String::new();
+    KeepAChangelog {
+        ser_options: options.clone(),
+        ..Default::default()
+    }
+    .serialize(changelog)
+}
+
+pub(crate) fn serialize_keep_a_changelog(changelog: &ChangeLog, options: &Options) -> String {
+    let mut buf = Vec::new();
+
+    write_changelog(&mut buf, changelog, options).expect("writing to a Vec can't fail");
+
+    String::from_utf8(buf).expect("only strings were written")
+}
 
+/// Like [`serialize_changelog`], but stream the changelog to `w`.
+pub fn write_changelog<W: Write>(
+    w: &mut W,
+    changelog: &ChangeLog,
+    options: &Options,
+) -> io::Result<()> {
     let mut should_new_line = false;
 
-    if let Some(header) = &changelog.header {
-        s.push_str(header);
-        s.push('\n');
+    // exactly one blank line between the header and the first release,
+    // whatever the whitespace around the header
+    if let Some(header) = changelog
+        .header
+        .as_deref()
+        .map(str::trim)
+        .filter(|header| !header.is_empty())
+    {
+        writeln!(w, "{header}")?;
 
         should_new_line = true;
     }
 
     if let Some(unreleased) = &changelog.unreleased {
         if should_new_line {
-            s.push('\n');
+            writeln!(w)?;
         }
         should_new_line = true;
 
-        serialize_release(&mut s, unreleased, &options.release_option);
+        write_release(w, unreleased, &options.release_option)?;
     }
 
     for release in changelog.releases() {
         if should_new_line {
-            s.push('\n');
+            writeln!(w)?;
         }
         should_new_line = true;
-        serialize_release(&mut s, release, &options.release_option);
+        write_release(w, release, &options.release_option)?;
     }
 
-    if !changelog.footer_links.links.is_empty() {
-        s.push('\n');
+    if should_new_line && !changelog.footer_links.links.is_empty() {
+        writeln!(w)?;
     }
 
     for footer_link in &changelog.footer_links.links {
-        s.push_str(&format!("[{}]: {}\n", footer_link.text, footer_link.link));
+        writeln!(w, "[{}]: {}", footer_link.text, footer_link.link)?;
     }
 
-    s
+    Ok(())
 }
 
 // todo: handle footer links
 pub fn serialize_release(s: &mut String, release: &Release, options: &OptionsRelease) {
+    let mut buf = Vec::new();
+
+    write_release(&mut buf, release, options).expect("writing to a Vec can't fail");
+
+    s.push_str(std::str::from_utf8(&buf).expect("only strings were written"));
+}
+
+/// Like [`serialize_release`], but stream the release to `w`.
+pub fn write_release<W: Write>(
+    w: &mut W,
+    release: &Release,
+    options: &OptionsRelease,
+) -> io::Result<()> {
     let mut should_new_line = false;
 
     if options.serialize_title {
-        let mut full_title = format!("## [{}]", release.title.version);
+        write!(w, "## [{}]", release.title.version)?;
 
         if let Some(release_link) = &release.title.release_link {
-            full_title.push_str(&format!("({})", release_link));
+            write!(w, "({release_link})")?;
         }
 
-        if let Some(title) = &release.title.title {
-            full_title.push_str(&format!(" - {}", title));
+        match (&release.title.title, &release.title.annotation) {
+            (Some(title), Some(annotation)) => write!(w, " - {title} ({annotation})")?,
+            (Some(title), None) => write!(w, " - {title}")?,
+            (None, Some(annotation)) => write!(w, " - ({annotation})")?,
+            (None, None) => {}
         }
 
-        full_title.push('\n');
+        if release.title.yanked {
+            write!(w, " {YANKED}")?;
+        }
 
-        s.push_str(&full_title);
+        writeln!(w)?;
 
         should_new_line = true;
     }
 
     if let Some(header) = &release.header {
         if should_new_line {
-            s.push('\n');
+            writeln!(w)?;
         }
-        s.push_str(&format!("{}\n", header));
+        writeln!(w, "{header}")?;
         should_new_line = true;
     }
 
-    for (_, section) in &release.note_sections {
-        if !section.notes.is_empty() {
-            if should_new_line {
-                s.push('\n');
-            }
-            should_new_line = true;
+    if options.flatten_sections {
+        let mut notes = release
+            .note_sections
+            .values()
+            .flat_map(|section| &section.notes)
+            .peekable();
 
-            s.push_str(&format!("### {}\n\n", section.title));
+        if should_new_line && notes.peek().is_some() {
+            writeln!(w)?;
+        }
 
-            for note in &section.notes {
-                serialize_release_section_note(s, note);
+        for note in notes {
+            write_release_section_note(w, note, options)?;
+            should_new_line = true;
+        }
+    } else {
+        // the notes without section first, or they would be read back in the previous section
+        let sections = release.note_sections.get(NO_SECTION).into_iter().chain(
+            release
+                .note_sections
+                .iter()
+                .filter(|(key, _)| *key != NO_SECTION)
+                .map(|(_, section)| section),
+        );
+
+        for section in sections {
+            let is_empty = section.notes.is_empty() && section.description.is_none();
+
+            if !is_empty || !options.omit_empty_sections {
+                if should_new_line {
+                    writeln!(w)?;
+                }
+                should_new_line = true;
+
+                if section.title != NO_SECTION {
+                    let heading = options
+                        .section_headings
+                        .get(&section.title)
+                        .unwrap_or(&section.title);
+
+                    writeln!(w, "### {heading}")?;
+
+                    if !is_empty {
+                        writeln!(w)?;
+                    }
+                }
+
+                if let Some(description) = &section.description {
+                    writeln!(w, "{description}")?;
+
+                    if !section.notes.is_empty() {
+                        writeln!(w)?;
+                    }
+                }
+
+                for note in sorted_notes(&section.notes, options) {
+                    write_release_section_note(w, note, options)?;
+                }
             }
         }
     }
 
     if let Some(footer) = &release.footer {
         if should_new_line {
-            s.push('\n');
+            writeln!(w)?;
         }
-        s.push_str(&format!("{}\n", footer));
+        writeln!(w, "{footer}")?;
     }
+
+    Ok(())
 }
 
-pub fn serialize_release_section_note(s: &mut String, note: &ReleaseSectionNote) {
-    let note_title = if let Some(scope) = &note.scope {
-        format!("- {}: {}\n", scope, note.message)
-    } else {
-        format!("- {}\n", note.message)
-    };
+fn sorted_notes<'a>(
+    notes: &'a [ReleaseSectionNote],
+    options: &OptionsRelease,
+) -> Vec<&'a ReleaseSectionNote> {
+    let mut notes = notes.iter().collect::<Vec<_>>();
+
+    if options.sort_notes_by_date {
+        // stable, and None is the smallest
+        notes.sort_by(|a, b| b.date.cmp(&a.date));
+    }
+
+    notes
+}
+
+pub fn serialize_release_section_note(
+    s: &mut String,
+    note: &ReleaseSectionNote,
+    options: &OptionsRelease,
+) {
+    let mut buf = Vec::new();
 
-    s.push_str(&note_title);
+    write_release_section_note(&mut buf, note, options).expect("writing to a Vec can't fail");
+
+    s.push_str(std::str::from_utf8(&buf).expect("only strings were written"));
+}
+
+/// Like [`serialize_release_section_note`], but stream the note to `w`.
+pub fn write_release_section_note<W: Write>(
+    w: &mut W,
+    note: &ReleaseSectionNote,
+    options: &OptionsRelease,
+) -> io::Result<()> {
+    let prefix = &options.bullet_prefix;
+    let message = normalize_message(&note.message, options);
+
+    match &note.scope {
+        Some(scope) => writeln!(w, "{prefix}{scope}: {message}")?,
+        None => writeln!(w, "{prefix}{message}")?,
+    }
+
+    let indent = " ".repeat(prefix.chars().count());
 
     for context in &note.context {
-        s.push_str(&format!("  {}\n", context));
+        writeln!(w, "{indent}{context}")?;
     }
+
+    Ok(())
+}
+
+fn normalize_message<'a>(message: &'a str, options: &OptionsRelease) -> Cow<'a, str> {
+    if !options.capitalize_first && !options.strip_trailing_period {
+        return Cow::Borrowed(message);
+    }
+
+    // the suffixes added by the generation are kept as is
+    let core = utils::parse_note_attribution(message).message;
+    let (mut core, suffix) = match message.strip_prefix(core.as_str()) {
+        Some(suffix) => (core, suffix),
+        None => (message.to_owned(), ""),
+    };
+
+    if options.strip_trailing_period && core.ends_with('.') && !core.ends_with("..") {
+        core.pop();
+    }
+
+    if options.capitalize_first {
+        let mut chars = core.chars();
+
+        if let Some(first) = chars.next().filter(char::is_ascii_lowercase) {
+            core = first.to_ascii_uppercase().to_string() + chars.as_str();
+        }
+    }
+
+    Cow::Owned(core + suffix)
 }
 
 #[cfg(test)]
@@ -141,18 +327,220 @@ mod test {
         println!("{}", output);
     }
 
+    #[test]
+    fn write() {
+        let mut buf = Vec::new();
+
+        write_changelog(&mut buf, &CHANGELOG1, &Options::default()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            serialize_changelog(&CHANGELOG1, &Options::default())
+        );
+    }
+
+    fn changelog(header: Option<&str>) -> ChangeLog {
+        let mut changelog = ChangeLog::new();
+        changelog.header = header.map(String::from);
+        changelog
+    }
+
+    #[test]
+    fn without_header() {
+        let output = serialize_changelog(&changelog(None), &Options::default());
+        assert!(output.starts_with("## [Unreleased]\n"), "{output:?}");
+
+        let output = serialize_changelog(&changelog(Some(" \n")), &Options::default());
+        assert!(output.starts_with("## [Unreleased]\n"), "{output:?}");
+    }
+
+    #[test]
+    fn with_header() {
+        for header in ["# Changelog", "# Changelog\n", "\n# Changelog\n\n\n"] {
+            let output = serialize_changelog(&changelog(Some(header)), &Options::default());
+            assert!(
+                output.starts_with("# Changelog\n\n## [Unreleased]\n"),
+                "{output:?}"
+            );
+        }
+    }
+
     #[test]
     fn test2() {
         let release_note = ReleaseSectionNote {
             scope: Some("data".into()),
             message: "the program".into(),
             context: vec!["- fix la base".into(), "49-3 hihi".into()],
+            date: None,
         };
 
         let mut output = String::new();
 
-        serialize_release_section_note(&mut output, &release_note);
+        serialize_release_section_note(&mut output, &release_note, &OptionsRelease::default());
 
         println!("{:?}", output);
     }
+
+    #[test]
+    fn bullet_prefix() {
+        let release_note = ReleaseSectionNote {
+            scope: None,
+            message: "the program".into(),
+            context: vec!["more".into()],
+            date: None,
+        };
+
+        let options = OptionsRelease {
+            bullet_prefix: "• ".into(),
+            ..Default::default()
+        };
+
+        let mut output = String::new();
+        serialize_release_section_note(&mut output, &release_note, &options);
+        assert_eq!(output, "• the program\n  more\n");
+
+        let options = OptionsRelease {
+            bullet_prefix: "".into(),
+            ..Default::default()
+        };
+
+        let mut output = String::new();
+        serialize_release_section_note(&mut output, &release_note, &options);
+        assert_eq!(output, "the program\nmore\n");
+    }
+
+    #[test]
+    fn normalize_messages() {
+        let options = OptionsRelease {
+            capitalize_first: true,
+            strip_trailing_period: true,
+            ..Default::default()
+        };
+
+        let serialized = |scope: Option<&str>, message: &str| {
+            let note = ReleaseSectionNote {
+                scope: scope.map(Into::into),
+                message: message.into(),
+                context: vec!["keep this.".into()],
+                date: None,
+            };
+
+            let mut output = String::new();
+            serialize_release_section_note(&mut output, &note, &options);
+            output
+        };
+
+        assert_eq!(
+            serialized(Some("ui"), "fix the crash."),
+            "- ui: Fix the crash\n  keep this.\n"
+        );
+        assert_eq!(
+            serialized(
+                None,
+                "fix the crash. in [#1](https://github.com/a/b/pull/1) by [@a](https://github.com/a)"
+            ),
+            "- Fix the crash in [#1](https://github.com/a/b/pull/1) by [@a](https://github.com/a)\n  keep this.\n"
+        );
+        assert_eq!(
+            serialized(None, "`cargo test` runs the doc tests."),
+            "- `cargo test` runs the doc tests\n  keep this.\n"
+        );
+        assert_eq!(serialized(None, "`foo.bar`"), "- `foo.bar`\n  keep this.\n");
+        assert_eq!(
+            serialized(None, "and more..."),
+            "- And more...\n  keep this.\n"
+        );
+
+        let mut output = String::new();
+        serialize_release_section_note(
+            &mut output,
+            &ReleaseSectionNote {
+                scope: None,
+                message: "fix the crash.".into(),
+                context: vec![],
+                date: None,
+            },
+            &OptionsRelease::default(),
+        );
+        assert_eq!(output, "- fix the crash.\n");
+    }
+
+    #[test]
+    fn empty_sections() {
+        let mut release = crate::utils::DEFAULT_UNRELEASED.clone();
+
+        for title in ["Added", "Fixed"] {
+            release.note_sections.insert(
+                title.into(),
+                ReleaseSection {
+                    title: title.into(),
+                    description: None,
+                    notes: vec![],
+                },
+            );
+        }
+
+        release.note_sections["Fixed"]
+            .notes
+            .push(ReleaseSectionNote {
+                scope: None,
+                message: "crash".into(),
+                context: vec![],
+                date: None,
+            });
+
+        let mut output = String::new();
+        serialize_release(&mut output, &release, &OptionsRelease::default());
+        assert_eq!(output, "## [Unreleased]\n\n### Fixed\n\n- crash\n");
+
+        let options = OptionsRelease {
+            omit_empty_sections: false,
+            ..Default::default()
+        };
+
+        let mut output = String::new();
+        serialize_release(&mut output, &release, &options);
+        assert_eq!(
+            output,
+            "## [Unreleased]\n\n### Added\n\n### Fixed\n\n- crash\n"
+        );
+    }
+
+    #[test]
+    fn sort_notes_by_date() {
+        let mut release = crate::utils::DEFAULT_UNRELEASED.clone();
+
+        release.note_sections.insert(
+            "Fixed".into(),
+            ReleaseSection {
+                title: "Fixed".into(),
+                description: None,
+                notes: vec![
+                    ReleaseSectionNote::new("undated a"),
+                    ReleaseSectionNote::new("old").date("2024-01-01T10:00:00+00:00"),
+                    ReleaseSectionNote::new("undated b"),
+                    ReleaseSectionNote::new("new").date("2024-03-01T08:00:00+00:00"),
+                ],
+            },
+        );
+
+        let mut output = String::new();
+        serialize_release(&mut output, &release, &OptionsRelease::default());
+        assert_eq!(
+            output,
+            "## [Unreleased]\n\n### Fixed\n\n- undated a\n- old\n- undated b\n- new\n"
+        );
+
+        let options = OptionsRelease {
+            sort_notes_by_date: true,
+            ..Default::default()
+        };
+
+        let mut output = String::new();
+        serialize_release(&mut output, &release, &options);
+        assert_eq!(
+            output,
+            "## [Unreleased]\n\n### Fixed\n\n- new\n- old\n- undated a\n- undated b\n"
+        );
+    }
 }
@@ -39,75 +39,32 @@ pub static CHANGELOG1: LazyLock<ChangeLog> = LazyLock::new(|| ChangeLog {
         let version = Version::new(0, 1, 0);
         releases.insert(
             version.clone(),
-            Release {
-                title: ReleaseTitle {
-                    version: version.to_string(),
-                    title: Some("i'm am the title of the night".into()),
-                    release_link: None,
-                },
-                header: Some("header".into()),
-                note_sections: {
-                    let mut notes = IndexMap::new();
-
-                    let section = String::from("Fixed");
-                    notes.insert(
-                        section.clone(),
-                        ReleaseSection {
-                            title: section,
-                            notes: vec![
-                                ReleaseSectionNote {
-                                    scope: Some("data".into()),
-                                    message: "the program".into(),
-                                    context: vec![
-                                        "- fix la base".into(),
-                                        "49-3 hihi".into(),
-                                        "lol".into(),
-                                    ],
-                                },
-                                ReleaseSectionNote {
-                                    scope: Some("ui".into()),
-                                    message: "the widget".into(),
-                                    context: vec![],
-                                },
-                                ReleaseSectionNote {
-                                    scope: None,
-                                    message: "lol".into(),
-                                    context: vec![],
-                                },
-                                ReleaseSectionNote {
-                                    scope: Some("ui".into()),
-                                    message: "the widget".into(),
-                                    context: vec![],
-                                },
-                                ReleaseSectionNote {
-                                    scope: Some("data".into()),
-                                    message: "the widget".into(),
-                                    context: vec![],
-                                },
-                            ],
-                        },
-                    );
-                    notes
-                },
-                footer: None,
-            },
+            Release::builder(version.to_string())
+                .date("i'm am the title of the night")
+                .header("header")
+                .note(
+                    "Fixed",
+                    ReleaseSectionNote::new("the program")
+                        .scope("data")
+                        .context("- fix la base")
+                        .context("49-3 hihi")
+                        .context("lol"),
+                )
+                .note("Fixed", ReleaseSectionNote::new("the widget").scope("ui"))
+                .note("Fixed", ReleaseSectionNote::new("lol"))
+                .note("Fixed", ReleaseSectionNote::new("the widget").scope("ui"))
+                .note("Fixed", ReleaseSectionNote::new("the widget").scope("data"))
+                .build()
+                .unwrap(),
         );
 
         let version = Version::new(0, 1, 1);
         releases.insert(
             version.clone(),
-            Release {
-                title: ReleaseTitle {
-                    version: version.to_string(),
-                    title: None,
-                    release_link: Some(
-                        "https://github.com/wiiznokes/fan-control/releases/tag/v2024.7.30".into(),
-                    ),
-                },
-                header: None,
-                note_sections: IndexMap::new(),
-                footer: None,
-            },
+            Release::builder(version.to_string())
+                .link("https://github.com/wiiznokes/fan-control/releases/tag/v2024.7.30")
+                .build()
+                .unwrap(),
         );
         releases
     },
@@ -143,11 +100,102 @@ fn release_title() {
     assert_eq!(input, s);
 }
 
+#[test]
+fn release_title_annotation() {
+    let title = |input: &str| {
+        let f_input = input.chars().collect::<Vec<_>>();
+        let release = de::release().parse(&f_input).unwrap();
+
+        let mut s = String::new();
+        ser::serialize_release(&mut s, &release, &OptionsRelease::default());
+        assert_eq!(input, s);
+
+        (release.title.title, release.title.annotation)
+    };
+
+    let some = |s: &str| Some(s.to_owned());
+
+    assert_eq!(
+        title("## [1.2.0] - 2024-01-01 (beta)\n"),
+        (some("2024-01-01"), some("beta"))
+    );
+    assert_eq!(
+        title("## [1.2.0] - 2024-01-01 (beta) [YANKED]\n"),
+        (some("2024-01-01"), some("beta"))
+    );
+    assert_eq!(title("## [1.2.0] - (rc 1)\n"), (None, some("rc 1")));
+    assert_eq!(
+        title("## [1.2.0] - 2024-01-01\n"),
+        (some("2024-01-01"), None)
+    );
+    assert_eq!(
+        title("## [1.2.0] - fn(x) support\n"),
+        (some("fn(x) support"), None)
+    );
+    assert_eq!(title("## [1.2.0] - f(x)\n"), (some("f(x)"), None));
+    assert_eq!(title("## [1.2.0]\n"), (None, None));
+}
+
 #[test]
 fn last_version() {
     assert_eq!(CHANGELOG1.last_version().unwrap(), Version::new(0, 1, 1));
 }
 
+#[test]
+fn calver_next_to_semver() {
+    let changelog = parse_changelog("## [24.3.9]\n\n## [24.04]\n\n## [24.4.1]\n").unwrap();
+
+    // 24.04 is compared as 24.4.0
+    assert_eq!(
+        changelog
+            .versions_sorted()
+            .iter()
+            .map(|r| r.version())
+            .collect::<Vec<_>>(),
+        vec!["24.4.1", "24.04", "24.3.9"]
+    );
+}
+
+#[test]
+fn version_lookup() {
+    // document order is not the version order
+    let changelog = parse_changelog(
+        "## [Unreleased]
+
+## [24.04]
+
+## [1.2.0] - 2024-02-01 [YANKED]
+
+## [24.10]
+
+## [1.10.0]
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+        changelog
+            .versions_sorted()
+            .iter()
+            .map(|r| r.version())
+            .collect::<Vec<_>>(),
+        vec!["24.10", "24.04", "1.10.0", "1.2.0"]
+    );
+
+    assert_eq!(changelog.latest_version(false).unwrap().version(), "24.10");
+
+    assert_eq!(changelog.get("v1.2.0").unwrap().version(), "1.2.0");
+    assert_eq!(changelog.get("24.04").unwrap().version(), "24.04");
+    assert_eq!(changelog.get("unreleased").unwrap().version(), "Unreleased");
+    assert!(changelog.contains_version("1.10.0"));
+    assert!(!changelog.contains_version("1.3.0"));
+    assert!(!changelog.contains_version("not a version"));
+
+    let changelog = parse_changelog("## [1.2.0] [YANKED]\n\n## [1.1.0]\n").unwrap();
+    assert_eq!(changelog.latest_version(true).unwrap().version(), "1.1.0");
+    assert_eq!(changelog.latest_version(false).unwrap().version(), "1.2.0");
+}
+
 fn default_sort_order() -> Vec<String> {
     vec![
         "Security".into(),
@@ -201,6 +249,7 @@ fn end_to_end_init(path: &Path) {
         sort_options: SortOptions {
             section_order: default_sort_order(),
             sort_scope: !filename.contains("nosort"),
+            reorder_releases: true,
         },
     });
 
@@ -231,3 +280,802 @@ fn end_to_end_err(path: &Path) {
 
     parse_changelog(&content).unwrap_err();
 }
+
+#[test]
+fn strip_links() {
+    assert_eq!(
+        utils::strip_links("fix [#1](https://a.b/1) by [@me](https://a.b/me) [not a link]"),
+        "fix #1 by @me [not a link]"
+    );
+}
+
+#[test]
+fn expand_links() {
+    assert_eq!(
+        utils::expand_links("fix [#1](https://a.b/1) in [https://a.b](https://a.b) [not a link]"),
+        "fix #1 (https://a.b/1) in https://a.b [not a link]"
+    );
+}
+
+#[test]
+fn plain_message() {
+    let note = ReleaseSectionNote::new(
+        "fixed a bug in [#123](https://github.com/a/b/pull/123) by [@x](https://github.com/x)",
+    )
+    .scope("ui");
+
+    assert_eq!(note.plain_message(), "fixed a bug in #123 by @x");
+}
+
+#[test]
+fn note_attribution() {
+    use utils::{parse_note_attribution, Contributor, PrLink};
+
+    let attribution = parse_note_attribution(
+        "fix the [docs](https://docs.rs) in [#2](https://github.com/a/b/pull/2) by [@alice](https://github.com/alice)",
+    );
+    assert_eq!(attribution.message, "fix the [docs](https://docs.rs)");
+    assert_eq!(
+        attribution.pr,
+        Some(PrLink {
+            id: "#2".into(),
+            url: "https://github.com/a/b/pull/2".into()
+        })
+    );
+    assert_eq!(
+        attribution.author,
+        Some(Contributor {
+            name: "alice".into(),
+            link: "https://github.com/alice".into()
+        })
+    );
+
+    let attribution = parse_note_attribution("fix again by [@bob](https://github.com/bob)");
+    assert_eq!(attribution.message, "fix again");
+    assert_eq!(attribution.pr, None);
+    assert_eq!(attribution.author.unwrap().name, "bob");
+
+    let attribution =
+        parse_note_attribution("typo in [a1b2c3d](https://github.com/a/b/commit/a1b2c3d)");
+    assert_eq!(attribution.message, "typo");
+    assert_eq!(attribution.author, None);
+    assert_eq!(attribution.pr.unwrap().id, "a1b2c3d");
+
+    let attribution = parse_note_attribution("plain message");
+    assert_eq!(attribution.message, "plain message");
+    assert_eq!((attribution.pr, attribution.author), (None, None));
+}
+
+#[test]
+fn contributors() {
+    let changelog = parse_changelog(
+        "## [Unreleased]
+
+### Added
+
+- new in [#3](https://github.com/a/b/pull/3) by [@bob](https://github.com/bob)
+
+## [1.0.0]
+
+### Fixed
+
+- fix in [#2](https://github.com/a/b/pull/2) by [@alice](https://github.com/alice)
+- fix again by [@bob](https://github.com/bob)
+- [@ not a mention](https://github.com)
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+        changelog.contributors(),
+        vec![
+            utils::Contributor {
+                name: "bob".into(),
+                link: "https://github.com/bob".into()
+            },
+            utils::Contributor {
+                name: "alice".into(),
+                link: "https://github.com/alice".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn lifecycle_links() {
+    let mut changelog = parse_changelog(
+        "## [Unreleased]
+
+### Removed
+
+- the `--legacy` flag in [#9](https://github.com/a/b/pull/9)
+- unrelated thing
+
+## [1.1.0]
+
+### Removed
+
+- old config format
+
+## [1.0.0]
+
+### Deprecated
+
+- deprecate the `--legacy` flag
+- old config format
+",
+    )
+    .unwrap();
+
+    let links = changelog.lifecycle_links();
+
+    assert_eq!(
+        links
+            .iter()
+            .map(|link| (
+                link.deprecated_in.as_str(),
+                link.removed_in.as_str(),
+                link.note.message.as_str()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("1.0.0", "1.1.0", "old config format"),
+            (
+                "1.0.0",
+                "Unreleased",
+                "the `--legacy` flag in [#9](https://github.com/a/b/pull/9)"
+            ),
+        ]
+    );
+
+    changelog.inject_lifecycle_links();
+    changelog.inject_lifecycle_links();
+
+    let output = ser::serialize_changelog(&changelog, &ser::Options::default());
+
+    assert!(output.starts_with(
+        "## [Unreleased]
+
+### Removed
+
+- the `--legacy` flag in [#9](https://github.com/a/b/pull/9)
+  (see Deprecated in 1.0.0)
+- unrelated thing
+
+## [1.1.0]
+
+### Removed
+
+- old config format
+  (see Deprecated in 1.0.0)
+"
+    ));
+}
+
+#[test]
+fn parse_warnings() {
+    let input = "## [Unreleased]
+
+### Added
+
+- a
+
+### Fixed
+
+- b
+
+some prose
+
+- c
+
+## [1.0.0]
+
+### Added
+
+- d
+
+### Added
+
+- e
+";
+
+    let (changelog, warnings) =
+        de::parse_changelog_with_warnings(input, &de::Options::default()).unwrap();
+
+    assert_eq!(
+        warnings
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec![
+            "line 13: note after other content in section \"Fixed\" of release Unreleased, kept as raw text",
+            "line 21: section \"Added\" of release 1.0.0 appears twice, the first one was dropped",
+        ]
+    );
+
+    assert_eq!(
+        changelog.unreleased.unwrap().footer.as_deref(),
+        Some("some prose\n\n- c")
+    );
+
+    let (_, warnings) = de::parse_changelog_with_warnings(
+        include_str!("../tests/scope.init"),
+        &de::Options::default(),
+    )
+    .unwrap();
+    assert_eq!(warnings, vec![]);
+}
+
+#[test]
+fn custom_format() {
+    use format::{ChangelogFormat, KeepAChangelog};
+
+    /// One line per note, without sections.
+    struct Flat;
+
+    impl ChangelogFormat for Flat {
+        fn parse(&self, input: &str) -> anyhow::Result<ChangeLog> {
+            KeepAChangelog::default().parse(input)
+        }
+
+        fn serialize(&self, changelog: &ChangeLog) -> String {
+            let mut s = String::new();
+
+            for release in changelog.releases() {
+                for section in release.note_sections.values() {
+                    for note in &section.notes {
+                        s.push_str(&format!("{}: {}\n", release.version(), note.message));
+                    }
+                }
+            }
+
+            s
+        }
+    }
+
+    let input = "## [1.0.0]\n\n### Added\n\n- a\n- b\n";
+
+    let changelog = ChangeLog::parse_with(input, &Flat).unwrap();
+
+    assert_eq!(changelog.serialize_with(&Flat), "1.0.0: a\n1.0.0: b\n");
+    assert_eq!(changelog.serialize_with(&KeepAChangelog::default()), input);
+}
+
+#[test]
+fn insert_release() {
+    use utils::InsertPolicy;
+
+    let input = include_str!("../tests/insert_release.init");
+
+    let release = |version: &str| {
+        let mut release = utils::DEFAULT_UNRELEASED.clone();
+        release.title.version = version.into();
+        release
+    };
+
+    let inserted = |version: &str, policy: InsertPolicy| {
+        let mut changelog = parse_changelog(input).unwrap();
+        changelog.insert_release(release(version), policy).unwrap();
+        changelog
+    };
+
+    let order = |changelog: &ChangeLog| {
+        changelog
+            .releases()
+            .map(|r| r.version().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let links = |changelog: &ChangeLog| {
+        changelog
+            .footer_links
+            .links
+            .iter()
+            .map(|l| l.text.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let link = |text: &str| InsertPolicy {
+        footer_link: Some(format!("https://github.com/a/b/releases/tag/{text}")),
+        ..Default::default()
+    };
+
+    // newest
+    let changelog = inserted("3.0.0", link("3.0.0"));
+    assert_eq!(order(&changelog), vec!["3.0.0", "2.0.0", "1.0.0", "0.1.0"]);
+    assert_eq!(
+        links(&changelog),
+        vec!["Unreleased", "3.0.0", "2.0.0", "0.1.0"]
+    );
+    assert!(
+        ser::serialize_changelog(&changelog, &ser::Options::default())
+            .starts_with("## [Unreleased]\n\n## [3.0.0]\n\n## [2.0.0]")
+    );
+
+    // oldest
+    let changelog = inserted("0.0.1", link("0.0.1"));
+    assert_eq!(order(&changelog), vec!["2.0.0", "1.0.0", "0.1.0", "0.0.1"]);
+    assert_eq!(
+        links(&changelog),
+        vec!["Unreleased", "2.0.0", "0.1.0", "0.0.1"]
+    );
+
+    // middle, the version without footer link
+    let changelog = inserted("1.5.0", link("1.5.0"));
+    assert_eq!(order(&changelog), vec!["2.0.0", "1.5.0", "1.0.0", "0.1.0"]);
+    assert_eq!(
+        links(&changelog),
+        vec!["Unreleased", "2.0.0", "1.5.0", "0.1.0"]
+    );
+
+    let changelog = inserted("1.5.0", InsertPolicy::default());
+    assert_eq!(changelog.footer_links.links.len(), 3);
+
+    // existing
+    let mut changelog = parse_changelog(input).unwrap();
+    let err = changelog
+        .insert_release(release("1.0.0"), InsertPolicy::default())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "The release 1.0.0 already exists");
+
+    let replaced = changelog
+        .insert_release(
+            release("1.0.0"),
+            InsertPolicy {
+                replace: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(replaced.note_sections["Added"].notes[0].message, "b");
+    assert!(changelog.get("1.0.0").unwrap().note_sections.is_empty());
+}
+
+#[test]
+fn note_scope() {
+    let notes = |input: &str| {
+        let changelog = parse_changelog(&format!("## [1.0.0]\n\n### Fixed\n\n{input}")).unwrap();
+
+        changelog.releases().next().unwrap().note_sections["Fixed"]
+            .notes
+            .iter()
+            .map(|note| (note.scope.clone(), note.message.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    let scoped = |scope: &str, message: &str| (Some(scope.to_owned()), message.to_owned());
+    let unscoped = |message: &str| (None, message.to_owned());
+
+    assert_eq!(
+        notes(
+            "- fix(api): handle `:` in headers
+- api: rename `a::b` to `c: d`
+- `std::io`: faster writes
+- see [the docs](https://docs.rs/changelog): for more
+- https://example.com is up again
+- ui:no space after the colon
+- ui: link to <https://example.com>
+"
+        ),
+        vec![
+            scoped("fix(api)", "handle `:` in headers"),
+            scoped("api", "rename `a::b` to `c: d`"),
+            unscoped("`std::io`: faster writes"),
+            unscoped("see [the docs](https://docs.rs/changelog): for more"),
+            unscoped("https://example.com is up again"),
+            unscoped("ui:no space after the colon"),
+            scoped("ui", "link to <https://example.com>"),
+        ]
+    );
+}
+
+#[test]
+fn release_builder() {
+    let release = Release::builder("1.2.0")
+        .date("2024-01-01")
+        .section(ReleaseSection::new("Added").description("New things."))
+        .note("Added", ReleaseSectionNote::new("export"))
+        .note("Fixed", ReleaseSectionNote::new("crash").scope("ui"))
+        .build()
+        .unwrap();
+
+    let mut output = String::new();
+    ser::serialize_release(&mut output, &release, &OptionsRelease::default());
+
+    assert_eq!(
+        output,
+        "## [1.2.0] - 2024-01-01\n\n### Added\n\nNew things.\n\n- export\n\n### Fixed\n\n- ui: crash\n"
+    );
+
+    let err = Release::builder(" ").build().unwrap_err();
+    assert_eq!(err.to_string(), "The version of a release can't be empty");
+
+    let err = Release::builder("1.2.0")
+        .note("Fixed", ReleaseSectionNote::new("crash"))
+        .section(ReleaseSection::new("Fixed"))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The section Fixed of release 1.2.0 is added twice"
+    );
+}
+
+#[test]
+fn parse_errors() {
+    use de::ParseError;
+
+    assert_eq!(
+        parse_changelog("## [Unreleased]\n\n## [1.0.0]\n\n## [unreleased]\n").unwrap_err(),
+        ParseError::MultipleUnreleased {
+            unreleased: "Unreleased".into()
+        }
+    );
+
+    assert_eq!(
+        parse_changelog("## [1.0.0]\n\n## [Unreleased]\n").unwrap_err(),
+        ParseError::UnreleasedNotFirst {
+            unreleased: "Unreleased".into()
+        }
+    );
+
+    assert!(matches!(
+        parse_changelog("## [1.0.0]\n\n## [one]\n").unwrap_err(),
+        ParseError::InvalidVersion { version, .. } if version == "one"
+    ));
+
+    assert_eq!(
+        parse_changelog("## [1.0.0]\n\n## [1.0.0]\n").unwrap_err(),
+        ParseError::DuplicateVersion {
+            version: "1.0.0".into()
+        }
+    );
+}
+
+#[test]
+fn query_notes() {
+    use query::NoteFilter;
+    use regex::Regex;
+
+    let changelog = parse_changelog(include_str!("../tests/query.md")).unwrap();
+
+    let messages = |notes: Vec<query::NoteRef>| {
+        notes
+            .iter()
+            .map(|n| n.note.message.clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        messages(changelog.notes().collect()),
+        vec![
+            "panic when the config is empty",
+            "dark theme",
+            "crash on resume",
+            "panic on start",
+            "export"
+        ]
+    );
+
+    let first = changelog.notes().nth(1).unwrap();
+    assert_eq!(first.version.map(|v| v.to_string()), Some("1.3.0".into()));
+    assert_eq!(first.section, "Added");
+    assert_eq!(first.note.scope.as_deref(), Some("ui"));
+
+    let since: Version = "1.2.0".parse().unwrap();
+    assert_eq!(
+        messages(changelog.notes().since_version(&since).collect()),
+        vec![
+            "panic when the config is empty",
+            "dark theme",
+            "crash on resume"
+        ]
+    );
+
+    assert_eq!(
+        messages(changelog.notes().in_section("added").collect()),
+        vec!["dark theme", "export"]
+    );
+
+    let panic = Regex::new("panic").unwrap();
+    assert_eq!(
+        messages(changelog.notes().matching(&panic).collect()),
+        vec![
+            "panic when the config is empty",
+            "crash on resume",
+            "panic on start"
+        ]
+    );
+
+    assert_eq!(
+        messages(
+            changelog
+                .notes()
+                .since_version(&since)
+                .in_section("Fixed")
+                .matching(&panic)
+                .filter(|n| n.version.is_some())
+                .collect()
+        ),
+        vec!["crash on resume"]
+    );
+}
+
+#[test]
+fn move_note() {
+    let mut changelog = parse_changelog(
+        "## [Unreleased]\n\n### Fixed\n\n- a\n- b\n- c\n\n## [1.0.0]\n\n### Added\n\n- init\n",
+    )
+    .unwrap();
+
+    let messages = |changelog: &ChangeLog| {
+        changelog.unreleased.as_ref().unwrap().note_sections["Fixed"]
+            .notes
+            .iter()
+            .map(|n| n.message.clone())
+            .collect::<Vec<_>>()
+    };
+
+    changelog.move_note("unreleased", "fixed", 0, 2).unwrap();
+    assert_eq!(messages(&changelog), vec!["b", "c", "a"]);
+
+    changelog.move_note("Unreleased", "Fixed", 2, 0).unwrap();
+    assert_eq!(messages(&changelog), vec!["a", "b", "c"]);
+
+    changelog.move_note("v1.0.0", "Added", 0, 0).unwrap();
+
+    let err = changelog
+        .move_note("Unreleased", "Fixed", 1, 3)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The section Fixed of release Unreleased has 3 notes, there is no note at index 3"
+    );
+    assert_eq!(messages(&changelog), vec!["a", "b", "c"]);
+
+    changelog.move_note("2.0.0", "Fixed", 0, 1).unwrap_err();
+    changelog.move_note("1.0.0", "Fixed", 0, 1).unwrap_err();
+}
+
+#[test]
+fn flatten_sections() {
+    let input = "## [1.0.0] - 2024-01-01
+
+### Added
+
+Some description.
+
+- ui: dark theme
+
+### Fixed
+
+- crash
+  On resume.
+";
+
+    let changelog = parse_changelog(input).unwrap();
+    let release = changelog.releases().next().unwrap();
+
+    let options = OptionsRelease {
+        flatten_sections: true,
+        ..Default::default()
+    };
+
+    let mut output = String::new();
+    ser::serialize_release(&mut output, release, &options);
+    assert_eq!(
+        output,
+        "## [1.0.0] - 2024-01-01\n\n- ui: dark theme\n- crash\n  On resume.\n"
+    );
+
+    // the flat list is parsed back as notes, not as the header
+    let flat = parse_changelog(&output).unwrap();
+    let release = flat.releases().next().unwrap();
+    assert_eq!(release.header, None);
+    assert_eq!(
+        release.note_sections[utils::NO_SECTION]
+            .notes
+            .iter()
+            .map(|note| note.message.as_str())
+            .collect::<Vec<_>>(),
+        vec!["dark theme", "crash"]
+    );
+
+    let mut round_trip = String::new();
+    ser::serialize_release(&mut round_trip, release, &OptionsRelease::default());
+    assert_eq!(round_trip, output);
+}
+
+#[test]
+fn sectionless_release() {
+    let input = "## [1.0.0] - 2024-01-01
+
+Maintenance release.
+
+* deps: update
+- fix the parser
+  With more tests.
+
+## [0.9.0]
+";
+
+    let changelog = parse_changelog(input).unwrap();
+    let release = changelog.releases().next().unwrap();
+
+    assert_eq!(release.header.as_deref(), Some("Maintenance release."));
+    assert_eq!(
+        release.note_sections.keys().collect::<Vec<_>>(),
+        vec![utils::NO_SECTION]
+    );
+
+    let notes = &release.note_sections[utils::NO_SECTION].notes;
+    assert_eq!(notes[0], ReleaseSectionNote::new("update").scope("deps"));
+    assert_eq!(
+        notes[1],
+        ReleaseSectionNote::new("fix the parser").context("With more tests.")
+    );
+
+    let output = ser::serialize_changelog(&changelog, &ser::Options::default());
+    assert_eq!(output, input.replace("* deps", "- deps"));
+}
+
+#[test]
+fn section_headings() {
+    let headings = std::collections::HashMap::from([
+        ("Added".to_owned(), "✨ Features".to_owned()),
+        ("Fixed".to_owned(), "🐛 Fixes".to_owned()),
+    ]);
+
+    let input = "## [1.0.0]\n\n### Added\n\n- dark theme\n\n### Fixed\n\n- crash\n\n### Security\n\n- escape\n";
+    let changelog = parse_changelog(input).unwrap();
+
+    let options = ser::Options {
+        release_option: OptionsRelease {
+            section_headings: headings.clone(),
+            ..Default::default()
+        },
+    };
+
+    let output = ser::serialize_changelog(&changelog, &options);
+    assert_eq!(
+        output,
+        "## [1.0.0]\n\n### ✨ Features\n\n- dark theme\n\n### 🐛 Fixes\n\n- crash\n\n### Security\n\n- escape\n"
+    );
+
+    // the titles are parsed back with the same map
+    let parsed = de::parse_changelog_with_options(
+        &output,
+        &de::Options {
+            section_headings: headings,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(parsed, changelog);
+
+    // without it, the headings are the titles
+    let parsed = parse_changelog(&output).unwrap();
+    assert!(parsed
+        .releases()
+        .next()
+        .unwrap()
+        .note_sections
+        .contains_key("✨ Features"));
+}
+
+#[test]
+fn section_headings_merge() {
+    let headings =
+        std::collections::HashMap::from([("Added".to_owned(), "✨ Features".to_owned())]);
+
+    // written by hand under the title, and generated under the heading
+    let input = "## [1.0.0]\n\n### Added\n\n- dark theme\n\n### Fixed\n\n- crash\n\n### ✨ Features\n\n- light theme\n";
+
+    let changelog = de::parse_changelog_with_options(
+        input,
+        &de::Options {
+            section_headings: headings,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let release = changelog.releases().next().unwrap();
+    assert_eq!(
+        release.note_sections.keys().collect::<Vec<_>>(),
+        vec!["Added", "Fixed"]
+    );
+    assert_eq!(
+        release.note_sections["Added"].notes,
+        vec![
+            ReleaseSectionNote::new("dark theme"),
+            ReleaseSectionNote::new("light theme")
+        ]
+    );
+}
+
+#[test]
+fn sort_keeps_sectionless_notes_first() {
+    let input =
+        "## [Unreleased]\n\n- loose note\n\n### Fixed\n\n- crash\n\n### Added\n\n- feature\n";
+
+    let fmt_options = fmt::Options {
+        sort_options: SortOptions::keep_a_changelog(),
+    };
+
+    let mut changelog = parse_changelog(input).unwrap();
+    changelog.sanitize(&fmt_options);
+    let output = ser::serialize_changelog(&changelog, &Default::default());
+
+    assert_eq!(
+        output,
+        "## [Unreleased]\n\n- loose note\n\n### Added\n\n- feature\n\n### Fixed\n\n- crash\n"
+    );
+
+    // a second pass reads the loose note back without section
+    let mut parsed = parse_changelog(&output).unwrap();
+    assert_eq!(
+        parsed.unreleased.as_ref().unwrap().note_sections[utils::NO_SECTION].notes[0].message,
+        "loose note"
+    );
+    parsed.sanitize(&fmt_options);
+    assert_eq!(
+        ser::serialize_changelog(&parsed, &Default::default()),
+        output
+    );
+
+    // whatever the order of the model
+    let mut release = changelog.unreleased.clone().unwrap();
+    release.note_sections.move_index(0, 2);
+    let mut output = String::new();
+    ser::serialize_release(&mut output, &release, &Default::default());
+    assert!(output.starts_with("## [Unreleased]\n\n- loose note\n"));
+}
+
+#[test]
+fn fenced_list_in_release_header() {
+    let input = "## [1.0.0] - 2024-01-02
+
+Install with:
+
+```sh
+- not a note
+### nor a section
+
+cargo install changen
+```
+
+- a note
+
+### Fixed
+
+- crash
+";
+
+    let changelog = parse_changelog(input).unwrap();
+    let release = changelog.releases().next().unwrap();
+
+    assert_eq!(
+        release.header.as_deref(),
+        Some(
+            "Install with:\n\n```sh\n- not a note\n### nor a section\n\ncargo install changen\n```"
+        )
+    );
+    assert_eq!(
+        release.note_sections[utils::NO_SECTION].notes,
+        vec![ReleaseSectionNote::new("a note")]
+    );
+    assert_eq!(release.note_sections["Fixed"].notes.len(), 1);
+
+    assert_eq!(
+        ser::serialize_changelog(&changelog, &Default::default()),
+        input
+    );
+
+    // an unclosed fence is prose
+    let changelog = parse_changelog("## [1.0.0]\n\n```sh\n- a note\n").unwrap();
+    let release = changelog.releases().next().unwrap();
+    assert_eq!(release.header.as_deref(), Some("```sh"));
+    assert_eq!(release.note_sections[utils::NO_SECTION].notes.len(), 1);
+}
@@ -1,21 +1,22 @@
-use std::{borrow::Cow, collections::btree_map, iter::Rev, sync::LazyLock};
+use std::{
+    borrow::Cow,
+    collections::{btree_map, HashSet},
+    iter::Rev,
+    sync::LazyLock,
+};
 
 use anyhow::bail;
 
-use crate::{ChangeLog, Release, ReleaseSection, ReleaseTitle, Version};
+use crate::{ChangeLog, FooterLink, Release, ReleaseSection, ReleaseSectionNote, Version};
 
 pub const UNRELEASED: &str = "Unreleased";
+/// Marker of a release pulled after its publication, written after its title.
+pub const YANKED: &str = "[YANKED]";
+/// Title of the section holding the notes written right under a release title, without a section heading.
+pub const NO_SECTION: &str = "";
 
-pub static DEFAULT_UNRELEASED: LazyLock<Release> = LazyLock::new(|| Release {
-    title: ReleaseTitle {
-        version: UNRELEASED.into(),
-        release_link: None,
-        title: None,
-    },
-    header: Default::default(),
-    note_sections: Default::default(),
-    footer: Default::default(),
-});
+pub static DEFAULT_UNRELEASED: LazyLock<Release> =
+    LazyLock::new(|| Release::builder(UNRELEASED).build().unwrap());
 
 impl ChangeLog {
     pub fn last_version(&self) -> Option<Version> {
@@ -30,19 +31,431 @@ impl ChangeLog {
         self.unreleased.as_mut().unwrap()
     }
 
-    pub fn releases(&self) -> Rev<btree_map::Values<Version, Release>> {
+    pub fn releases(&self) -> Rev<btree_map::Values<'_, Version, Release>> {
         self.releases.values().rev()
     }
 
-    pub fn releases_keys(&self) -> Rev<btree_map::Keys<Version, Release>> {
+    pub fn releases_keys(&self) -> Rev<btree_map::Keys<'_, Version, Release>> {
         self.releases.keys().rev()
     }
 
-    pub fn releases_full(&self) -> Rev<btree_map::Iter<Version, Release>> {
+    pub fn releases_full(&self) -> Rev<btree_map::Iter<'_, Version, Release>> {
         self.releases.iter().rev()
     }
 }
 
+impl ChangeLog {
+    /// The newest versioned release. Yanked releases are skipped if `skip_yanked` is set.
+    pub fn latest_version(&self, skip_yanked: bool) -> Option<&Release> {
+        self.releases()
+            .find(|release| !(skip_yanked && release.title.yanked))
+    }
+
+    /// The release of `version`, with or without a `v` prefix. "Unreleased" is accepted.
+    pub fn get(&self, version: &str) -> Option<&Release> {
+        let version = version.trim();
+
+        if version.eq_ignore_ascii_case(UNRELEASED) {
+            return self.unreleased.as_ref();
+        }
+
+        let version = version
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(version)
+            .parse::<Version>()
+            .ok()?;
+
+        self.releases.get(&version)
+    }
+
+    /// Like [`Self::get`], mutably.
+    pub fn get_mut(&mut self, version: &str) -> Option<&mut Release> {
+        let version = version.trim();
+
+        if version.eq_ignore_ascii_case(UNRELEASED) {
+            return self.unreleased.as_mut();
+        }
+
+        let version = version
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(version)
+            .parse::<Version>()
+            .ok()?;
+
+        self.releases.get_mut(&version)
+    }
+
+    /// Like [`Self::get`].
+    pub fn contains_version(&self, version: &str) -> bool {
+        self.get(version).is_some()
+    }
+
+    /// The versioned releases, the newest first, whatever their order in the document.
+    /// Versions which are not semver, like `24.04`, are compared as their major and minor numbers,
+    /// like `24.4.0`. The releases are keyed by version, so there is no document order to fall back to.
+    pub fn versions_sorted(&self) -> Vec<&Release> {
+        self.releases().collect()
+    }
+}
+
+/// How [`ChangeLog::insert_release`] inserts a release.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InsertPolicy {
+    /// Replace the release of the same version instead of failing.
+    pub replace: bool,
+    /// Footer link of the release, written in the order of the releases.
+    pub footer_link: Option<String>,
+}
+
+impl ChangeLog {
+    /// Insert a versioned release at its position: after Unreleased for the newest version, or
+    /// between its neighbours when back-filling an old one.
+    /// Return the release replaced, when [`InsertPolicy::replace`] is set.
+    pub fn insert_release(
+        &mut self,
+        release: Release,
+        policy: InsertPolicy,
+    ) -> anyhow::Result<Option<Release>> {
+        let text = release.version().to_owned();
+
+        let version = match text
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(&text)
+            .parse::<Version>()
+        {
+            Ok(version) => version,
+            Err(e) => bail!("Can't insert the release {text}: {e}"),
+        };
+
+        if self.releases.contains_key(&version) && !policy.replace {
+            bail!("The release {version} already exists");
+        }
+
+        if let Some(link) = policy.footer_link {
+            let links = &mut self.footer_links.links;
+
+            match links
+                .iter_mut()
+                .find(|l| l.text.eq_ignore_ascii_case(&text))
+            {
+                Some(footer_link) => footer_link.link = link,
+                None => {
+                    // after Unreleased and the links of the newer releases
+                    let pos = links
+                        .iter()
+                        .rposition(|l| {
+                            l.text.eq_ignore_ascii_case(UNRELEASED)
+                                || l.text
+                                    .strip_prefix(['v', 'V'])
+                                    .unwrap_or(&l.text)
+                                    .parse::<Version>()
+                                    .is_ok_and(|v| v > version)
+                        })
+                        .map_or(0, |pos| pos + 1);
+
+                    links.insert(pos, FooterLink { text, link });
+                }
+            }
+        }
+
+        Ok(self.releases.insert(version, release))
+    }
+}
+
+impl ChangeLog {
+    /// Move the note at `from` to `to` in `section` of the release `version`, the other notes
+    /// keeping their order. `to` is the index of the note after the move.
+    /// The version is found like with [`Self::get`], and the section title ignoring the case.
+    pub fn move_note(
+        &mut self,
+        version: &str,
+        section: &str,
+        from: usize,
+        to: usize,
+    ) -> anyhow::Result<()> {
+        let Some(release) = self.get_mut(version) else {
+            bail!("Release {version} not found");
+        };
+
+        let Some(section) = release
+            .note_sections
+            .values_mut()
+            .find(|s| s.title.eq_ignore_ascii_case(section))
+        else {
+            bail!("The release {version} has no section {section}");
+        };
+
+        let len = section.notes.len();
+
+        for index in [from, to] {
+            if index >= len {
+                bail!(
+                    "The section {} of release {version} has {len} notes, there is no note at index {index}",
+                    section.title
+                );
+            }
+        }
+
+        let note = section.notes.remove(from);
+        section.notes.insert(to, note);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Contributor {
+    /// Handle, without the `@`
+    pub name: String,
+    pub link: String,
+}
+
+impl ChangeLog {
+    /// All contributors mentioned (`[@name](link)`) in the notes, in document order.
+    pub fn contributors(&self) -> Vec<Contributor> {
+        let mut contributors: Vec<Contributor> = Vec::new();
+
+        for release in self.unreleased.iter().chain(self.releases()) {
+            for section in release.note_sections.values() {
+                for note in &section.notes {
+                    for contributor in mentions(&note.message) {
+                        if contributors.iter().all(|c| c.name != contributor.name) {
+                            contributors.push(contributor);
+                        }
+                    }
+                }
+            }
+        }
+
+        contributors
+    }
+}
+
+fn mentions(message: &str) -> Vec<Contributor> {
+    let mut res = Vec::new();
+    let mut rest = message;
+
+    while let Some(start) = rest.find("[@") {
+        rest = &rest[start + 2..];
+
+        let Some(name_end) = rest.find("](") else {
+            break;
+        };
+        let name = &rest[..name_end];
+
+        let Some(link_end) = rest[name_end..].find(')') else {
+            break;
+        };
+        let link = &rest[name_end + 2..name_end + link_end];
+
+        if !name.is_empty() && !name.contains(['[', ']', ' ']) {
+            res.push(Contributor {
+                name: name.into(),
+                link: link.into(),
+            });
+        }
+
+        rest = &rest[name_end + link_end..];
+    }
+
+    res
+}
+
+/// Link to the pull request of a note, like `[#12](https://github.com/a/b/pull/12)`,
+/// or to its commit when there is no pull request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrLink {
+    /// Text of the link, like `#12`, or a short sha
+    pub id: String,
+    pub url: String,
+}
+
+/// A note message split from the suffixes added by the generation:
+/// ` in [#12](url)` and ` by [@login](link)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteAttribution {
+    /// The message without the suffixes.
+    pub message: String,
+    pub pr: Option<PrLink>,
+    pub author: Option<Contributor>,
+}
+
+/// Split the pull request link and the author acknowledgement from the end of a note message.
+/// Links in the middle of the message are left untouched.
+pub fn parse_note_attribution(message: &str) -> NoteAttribution {
+    let mut rest = message.trim_end();
+
+    let author = trailing_link(rest, " by [").and_then(|(pos, text, link)| {
+        let name = text.strip_prefix('@')?;
+        (!name.is_empty() && !name.contains(' ')).then(|| {
+            rest = &rest[..pos];
+            Contributor {
+                name: name.into(),
+                link: link.into(),
+            }
+        })
+    });
+
+    let pr = trailing_link(rest, " in [").map(|(pos, text, link)| {
+        rest = &rest[..pos];
+        PrLink {
+            id: text.into(),
+            url: link.into(),
+        }
+    });
+
+    NoteAttribution {
+        message: rest.into(),
+        pr,
+        author,
+    }
+}
+
+/// `(position of the prefix, text, link)` of a `{prefix}text](link)` ending `input`.
+fn trailing_link<'a>(input: &'a str, prefix: &str) -> Option<(usize, &'a str, &'a str)> {
+    let pos = input.rfind(prefix)?;
+    let link = input[pos + prefix.len()..].strip_suffix(')')?;
+    let (text, link) = link.split_once("](")?;
+
+    (!text.contains([']', '[']) && !link.contains(['(', ')', ' '])).then_some((pos, text, link))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleLink {
+    /// Version of the release deprecating the feature
+    pub deprecated_in: String,
+    /// Version of the release removing it
+    pub removed_in: String,
+    /// The note of the Removed section
+    pub note: ReleaseSectionNote,
+}
+
+const DEPRECATED: &str = "Deprecated";
+const REMOVED: &str = "Removed";
+
+impl ChangeLog {
+    /// Match the notes of Removed sections to notes of Deprecated sections
+    /// from older releases, when their messages are similar enough.
+    pub fn lifecycle_links(&self) -> Vec<LifecycleLink> {
+        // oldest first
+        let releases = self
+            .releases
+            .values()
+            .chain(self.unreleased.iter())
+            .collect::<Vec<_>>();
+
+        let mut links = Vec::new();
+
+        for (pos, release) in releases.iter().enumerate() {
+            let Some(removed) = release.note_sections.get(REMOVED) else {
+                continue;
+            };
+
+            for note in &removed.notes {
+                let mut best: Option<(f32, &Release)> = None;
+
+                for older in &releases[..pos] {
+                    let Some(deprecated) = older.note_sections.get(DEPRECATED) else {
+                        continue;
+                    };
+
+                    for deprecated_note in &deprecated.notes {
+                        let score = similarity(deprecated_note, note);
+
+                        if score >= 0.5 && best.is_none_or(|(best, _)| score >= best) {
+                            best = Some((score, older));
+                        }
+                    }
+                }
+
+                if let Some((_, older)) = best {
+                    links.push(LifecycleLink {
+                        deprecated_in: older.version().into(),
+                        removed_in: release.version().into(),
+                        note: note.clone(),
+                    });
+                }
+            }
+        }
+
+        links
+    }
+
+    /// Add a `(see Deprecated in X)` context line to the Removed notes found by [`ChangeLog::lifecycle_links`].
+    pub fn inject_lifecycle_links(&mut self) -> Vec<LifecycleLink> {
+        let links = self.lifecycle_links();
+
+        for link in &links {
+            let release = if link.removed_in == UNRELEASED {
+                self.unreleased.as_mut()
+            } else {
+                self.releases
+                    .values_mut()
+                    .find(|release| release.version() == link.removed_in)
+            };
+
+            let Some(section) = release.and_then(|release| release.note_sections.get_mut(REMOVED))
+            else {
+                continue;
+            };
+
+            let line = format!("(see Deprecated in {})", link.deprecated_in);
+
+            for note in &mut section.notes {
+                if *note == link.note && !note.context.contains(&line) {
+                    note.context.push(line.clone());
+                }
+            }
+        }
+
+        links
+    }
+}
+
+/// Proportion of words shared by both notes, ignoring links and lifecycle verbs.
+fn similarity(a: &ReleaseSectionNote, b: &ReleaseSectionNote) -> f32 {
+    fn words(note: &ReleaseSectionNote) -> HashSet<String> {
+        let message = note.plain_message();
+
+        note.scope
+            .iter()
+            .map(String::as_str)
+            .chain(message.split_whitespace())
+            .filter(|word| !word.starts_with(['#', '@']))
+            .map(|word| {
+                word.trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|word| {
+                !word.is_empty()
+                    && ![
+                        "in",
+                        "by",
+                        "the",
+                        "a",
+                        "an",
+                        "deprecate",
+                        "deprecated",
+                        "remove",
+                        "removed",
+                    ]
+                    .contains(&word.as_str())
+            })
+            .collect()
+    }
+
+    let a = words(a);
+    let b = words(b);
+
+    let union = a.union(&b).count();
+
+    if union == 0 {
+        return 0.0;
+    }
+
+    a.intersection(&b).count() as f32 / union as f32
+}
+
 pub enum NthRelease<'a> {
     Unreleased(Cow<'a, Release>),
     Released(Cow<'a, Version>, Cow<'a, Release>),
@@ -110,3 +523,65 @@ impl Release {
         }
     }
 }
+
+impl ReleaseSectionNote {
+    /// The message with its markdown links replaced by their text, like "fixed a bug in #123 by @x".
+    /// For plain text outputs: emails, terminals.
+    pub fn plain_message(&self) -> String {
+        strip_links(&self.message)
+    }
+}
+
+/// Replace markdown links (`[text](url)`) by their text.
+pub fn strip_links(input: &str) -> String {
+    map_links(input, |text, _| text.to_owned())
+}
+
+/// Replace markdown links (`[text](url)`) by their text followed by their url, like "text (url)".
+/// A link whose text is its url is replaced by the url.
+pub fn expand_links(input: &str) -> String {
+    map_links(input, |text, url| {
+        if text == url {
+            url.to_owned()
+        } else {
+            format!("{text} ({url})")
+        }
+    })
+}
+
+fn map_links(input: &str, f: impl Fn(&str, &str) -> String) -> String {
+    let mut res = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('[') {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let link = rest.find(']').and_then(|text_end| {
+            let text = &rest[1..text_end];
+            let after = &rest[text_end + 1..];
+
+            if text.contains(['[', '\n']) || !after.starts_with('(') {
+                return None;
+            }
+
+            after
+                .find(')')
+                .map(|url_end| (text, &after[1..url_end], text_end + 1 + url_end + 1))
+        });
+
+        match link {
+            Some((text, url, len)) => {
+                res.push_str(&f(text, url));
+                rest = &rest[len..];
+            }
+            None => {
+                res.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    res.push_str(rest);
+    res
+}
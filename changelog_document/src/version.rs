@@ -1,5 +1,7 @@
 use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 #[derive(Debug, Clone)]
 pub enum Version {
     Semver(semver::Version),
@@ -82,6 +84,19 @@ impl Version {
     }
 }
 
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Version::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -0,0 +1,203 @@
+use anyhow::{bail, Result};
+use changelog::{ChangeLog, ReleaseSection};
+
+use crate::{config::Amend, report::GeneratedNote};
+
+/// Edit a note of the Unreleased section: `target` when given, or else the last note.
+/// Return the section of the amended note.
+pub fn amend(
+    changelog: &mut ChangeLog,
+    options: &Amend,
+    target: Option<&GeneratedNote>,
+) -> Result<String> {
+    let Some(unreleased) = changelog.unreleased.as_mut().filter(|unreleased| {
+        unreleased
+            .note_sections
+            .values()
+            .any(|s| !s.notes.is_empty())
+    }) else {
+        bail!("The Unreleased section is empty, there is no note to amend");
+    };
+
+    let (section_title, pos) = match target {
+        Some(target) => {
+            let pos = unreleased
+                .note_sections
+                .get(&target.section)
+                .and_then(|section| section.notes.iter().position(|n| *n == target.note));
+
+            match pos {
+                Some(pos) => (target.section.clone(), pos),
+                None => bail!(
+                    "The note \"{}\" of the report is not in the {} section of Unreleased",
+                    target.note.message,
+                    target.section
+                ),
+            }
+        }
+        None => {
+            let section = unreleased
+                .note_sections
+                .values()
+                .rev()
+                .find(|section| !section.notes.is_empty())
+                .unwrap();
+
+            (section.title.clone(), section.notes.len() - 1)
+        }
+    };
+
+    let mut note = unreleased.note_sections[&section_title].notes.remove(pos);
+
+    if let Some(message) = &options.message {
+        note.message = message.clone();
+    }
+
+    if let Some(scope) = &options.scope {
+        note.scope = Some(scope.clone()).filter(|scope| !scope.is_empty());
+    }
+
+    note.context.extend(options.append_context.iter().cloned());
+
+    let new_section_title = match &options.section {
+        Some(section) => unreleased
+            .note_sections
+            .keys()
+            .find(|title| title.eq_ignore_ascii_case(section))
+            .cloned()
+            .unwrap_or(section.clone()),
+        None => section_title.clone(),
+    };
+
+    if new_section_title == section_title {
+        unreleased.note_sections[&section_title]
+            .notes
+            .insert(pos, note);
+    } else {
+        unreleased
+            .note_sections
+            .entry(new_section_title.clone())
+            .or_insert_with(|| ReleaseSection {
+                title: new_section_title.clone(),
+                description: None,
+                notes: Vec::new(),
+            })
+            .notes
+            .push(note);
+    }
+
+    Ok(new_section_title)
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::{
+        de::parse_changelog,
+        ser::{serialize_changelog, Options},
+        ReleaseSectionNote,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INPUT: &str = "## [Unreleased]
+
+### Added
+
+- ui: new button
+
+### Fixed
+
+- crash
+- leak in [#2](https://github.com/a/b/pull/2)
+";
+
+    fn options() -> Amend {
+        Amend {
+            file: None,
+            message: None,
+            scope: None,
+            section: None,
+            append_context: vec![],
+            report: None,
+            stdout: false,
+        }
+    }
+
+    fn amended(options: &Amend, target: Option<&GeneratedNote>) -> String {
+        let mut changelog = parse_changelog(INPUT).unwrap();
+        amend(&mut changelog, options, target).unwrap();
+        changelog.sanitize(&changelog::fmt::Options::default());
+        serialize_changelog(&changelog, &Options::default())
+    }
+
+    #[test]
+    fn message() {
+        let mut options = options();
+        options.message = Some("memory leak in [#2](https://github.com/a/b/pull/2)".into());
+        options.append_context = vec!["Only on Windows.".into()];
+
+        assert_eq!(
+            amended(&options, None),
+            "## [Unreleased]
+
+### Added
+
+- ui: new button
+
+### Fixed
+
+- crash
+- memory leak in [#2](https://github.com/a/b/pull/2)
+  Only on Windows.
+"
+        );
+    }
+
+    #[test]
+    fn move_section() {
+        let mut options = options();
+        options.section = Some("added".into());
+        options.scope = Some("ui".into());
+
+        let target = GeneratedNote {
+            section: "Fixed".into(),
+            note: ReleaseSectionNote {
+                scope: None,
+                message: "crash".into(),
+                context: vec![],
+                date: None,
+            },
+        };
+
+        assert_eq!(
+            amended(&options, Some(&target)),
+            "## [Unreleased]
+
+### Added
+
+- ui: new button
+- ui: crash
+
+### Fixed
+
+- leak in [#2](https://github.com/a/b/pull/2)
+"
+        );
+    }
+
+    #[test]
+    fn empty_unreleased() {
+        let mut changelog =
+            parse_changelog("## [Unreleased]\n\n## [1.0.0]\n\n### Added\n\n- init\n").unwrap();
+
+        let mut options = options();
+        options.message = Some("a".into());
+
+        let err = amend(&mut changelog, &options, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The Unreleased section is empty, there is no note to amend"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt::Write;
+
+use anyhow::bail;
+use changelog::{utils::strip_links, ChangeLog, Release, ReleaseSectionNote};
+
+use crate::utils::{escape_xml, parse_iso_date};
+
+/// Render the releases of `changelog` as the `<releases>` element of an AppStream metainfo file,
+/// the newest first. Unreleased is not rendered.
+///
+/// The description only uses the `<p>`, `<ul>` and `<li>` elements: a section becomes a paragraph
+/// with its title, followed by the list of its notes. Links are replaced by their text,
+/// and the context of a note is joined to its message, because lists can't be nested.
+/// The date of each release must be a `YYYY-MM-DD` date.
+pub fn render_releases(changelog: &ChangeLog) -> anyhow::Result<String> {
+    let mut output = String::from("<releases>\n");
+
+    for release in changelog.releases() {
+        render_release(&mut output, release)?;
+    }
+
+    output.push_str("</releases>\n");
+
+    Ok(output)
+}
+
+fn render_release(output: &mut String, release: &Release) -> anyhow::Result<()> {
+    let version = release.version();
+
+    let Some(date) = release.title.title.as_deref().map(str::trim) else {
+        bail!("The release {version} has no date, which AppStream requires");
+    };
+
+    if parse_iso_date(date).is_none() {
+        bail!("The date \"{date}\" of the release {version} is not a YYYY-MM-DD date");
+    }
+
+    let sections = release
+        .note_sections
+        .values()
+        .filter(|section| !section.notes.is_empty())
+        .collect::<Vec<_>>();
+
+    if sections.is_empty() {
+        writeln!(
+            output,
+            "  <release version=\"{}\" date=\"{date}\"/>",
+            escape_xml(version)
+        )
+        .unwrap();
+        return Ok(());
+    }
+
+    writeln!(
+        output,
+        "  <release version=\"{}\" date=\"{date}\">",
+        escape_xml(version)
+    )
+    .unwrap();
+    writeln!(output, "    <description>").unwrap();
+
+    for section in sections {
+        writeln!(output, "      <p>{}</p>", escape_xml(&section.title)).unwrap();
+
+        if let Some(description) = &section.description {
+            writeln!(output, "      <p>{}</p>", inline_text(description)).unwrap();
+        }
+
+        writeln!(output, "      <ul>").unwrap();
+
+        for note in &section.notes {
+            writeln!(output, "        <li>{}</li>", note_text(note)).unwrap();
+        }
+
+        writeln!(output, "      </ul>").unwrap();
+    }
+
+    writeln!(output, "    </description>").unwrap();
+    writeln!(output, "  </release>").unwrap();
+
+    Ok(())
+}
+
+fn note_text(note: &ReleaseSectionNote) -> String {
+    let mut text = match &note.scope {
+        Some(scope) => format!("{scope}: {}", note.message),
+        None => note.message.clone(),
+    };
+
+    for line in &note.context {
+        text.push(' ');
+        text.push_str(line.trim());
+    }
+
+    inline_text(&text)
+}
+
+/// One line of escaped text, without links, code spans and emphasis markers.
+fn inline_text(markdown: &str) -> String {
+    let text = strip_links(markdown)
+        .replace("**", "")
+        .replace("__", "")
+        .replace('`', "");
+
+    escape_xml(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Replace the `<releases>` element of the metainfo file `xml` by `releases`, indented like it.
+/// The rest of the file is kept as is.
+pub fn splice_releases(xml: &str, releases: &str) -> anyhow::Result<String> {
+    let (start, end) = match (xml.find("<releases>"), xml.find("</releases>")) {
+        (Some(start), Some(end)) if start < end => (start, end + "</releases>".len()),
+        _ => match xml.find("<releases/>") {
+            Some(start) => (start, start + "<releases/>".len()),
+            None => bail!("The metainfo file has no <releases> element"),
+        },
+    };
+
+    let line_start = xml[..start].rfind('\n').map_or(0, |pos| pos + 1);
+    let indent = &xml[line_start..start];
+
+    if !indent.chars().all(char::is_whitespace) {
+        bail!("The <releases> element of the metainfo file must start its line");
+    }
+
+    let releases = releases
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 || line.is_empty() {
+                line.to_owned()
+            } else {
+                format!("{indent}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(format!("{}{releases}{}", &xml[..start], &xml[end..]))
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CHANGELOG: &str = include_str!("../tests/appstream/CHANGELOG.md");
+
+    #[test]
+    fn render() {
+        let changelog = parse_changelog(CHANGELOG).unwrap();
+
+        assert_eq!(
+            render_releases(&changelog).unwrap(),
+            include_str!("../tests/appstream/releases.xml")
+        );
+    }
+
+    #[test]
+    fn splice() {
+        let changelog = parse_changelog(CHANGELOG).unwrap();
+        let releases = render_releases(&changelog).unwrap();
+
+        let spliced =
+            splice_releases(include_str!("../tests/appstream/metainfo.xml"), &releases).unwrap();
+
+        assert_eq!(
+            spliced,
+            include_str!("../tests/appstream/metainfo.expect.xml")
+        );
+
+        // idempotent
+        assert_eq!(splice_releases(&spliced, &releases).unwrap(), spliced);
+
+        splice_releases("<component></component>", &releases).unwrap_err();
+    }
+
+    #[test]
+    fn invalid_dates() {
+        let err = render_releases(&parse_changelog("## [1.0.0]\n\n### Added\n\n- a\n").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The release 1.0.0 has no date, which AppStream requires"
+        );
+
+        let err = render_releases(
+            &parse_changelog("## [1.0.0] - 01/02/2024\n\n### Added\n\n- a\n").unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The date \"01/02/2024\" of the release 1.0.0 is not a YYYY-MM-DD date"
+        );
+    }
+}
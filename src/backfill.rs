@@ -0,0 +1,249 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use changelog::{
+    de::parse_changelog, ser::serialize_changelog, utils::UNRELEASED, ChangeLog, FooterLink,
+    Release, Version,
+};
+use regex::Regex;
+
+use crate::{
+    config::{Backfill, Generate, MapMessageToSection},
+    generate::gen_release_notes_between,
+    git_provider::{DiffTags, GitProvider},
+    repository::{tag_name, Period, Repository},
+};
+
+/// Tags to include in a backfill. Both bounds are inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct TagRange {
+    pub from: Option<Version>,
+    pub to: Option<Version>,
+    /// Tags not matching it are ignored, and don't delimit releases.
+    pub filter: Option<Regex>,
+    /// Prefix of the tags, stripped to get the versions.
+    pub tag_prefix: String,
+}
+
+impl TagRange {
+    fn contains(&self, tag: &Version) -> bool {
+        self.from.as_ref().is_none_or(|from| tag >= from)
+            && self.to.as_ref().is_none_or(|to| tag <= to)
+    }
+}
+
+pub fn backfill_changelog<R: Repository>(r: &R, options: &Backfill) -> Result<String> {
+    let map = MapMessageToSection::try_new(options.map.as_ref())?;
+
+    let generate = Generate {
+        map: options.map.clone(),
+        exclude_unidentified: options.exclude_unidentified,
+        provider: options.provider.clone(),
+        repo: options.repo.clone(),
+        ..Default::default()
+    };
+
+    let range = TagRange {
+        from: options.from.clone(),
+        to: options.to.clone(),
+        filter: options.tag_filter.clone(),
+        tag_prefix: options.tag_prefix.clone(),
+    };
+
+    let mut changelog = parse_changelog(include_str!("../res/CHANGELOG_DEFAULT.md"))?;
+
+    changelog.unreleased_or_default();
+
+    backfill(r, &mut changelog, &map, &generate, &range, true)?;
+
+    changelog.sanitize(&map.to_fmt_options_all_releases());
+
+    if let Some(repo) = &options.repo {
+        changelog.footer_links.links =
+            footer_links(&changelog, &options.provider, repo, &options.tag_prefix)?;
+    }
+
+    let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
+
+    Ok(output)
+}
+
+/// Add a release for each tag of the repo, titled with the tag creation date.
+/// If `with_notes` is set, the release notes of each tag are generated since the previous one.
+/// When the generation fails midway, the releases generated so far are kept.
+pub fn backfill<R: Repository>(
+    r: &R,
+    changelog: &mut ChangeLog,
+    map: &MapMessageToSection,
+    options: &Generate,
+    range: &TagRange,
+    with_notes: bool,
+) -> Result<()> {
+    let tags = r.tags_list(range.filter.as_ref(), &range.tag_prefix)?;
+
+    let total = tags.iter().filter(|tag| range.contains(tag)).count();
+    let mut done = 0;
+
+    let mut prev: Option<&Version> = None;
+
+    for tag in &tags {
+        if range.contains(tag) {
+            let mut builder = Release::builder(tag.to_string());
+            if let Some(date) = r.tag_date(&tag_name(&range.tag_prefix, tag)) {
+                builder = builder.date(date);
+            }
+            let mut release = builder.build()?;
+
+            if with_notes {
+                info!("[{}/{}] generating release {}", done + 1, total, tag);
+
+                let period = Period {
+                    since: prev.map(|prev| tag_name(&range.tag_prefix, prev)),
+                    until: Some(tag_name(&range.tag_prefix, tag)),
+                };
+
+                if let Err(e) = gen_release_notes_between(r, &mut release, map, options, &period) {
+                    error!(
+                        "Error while generating release {tag}: {e}. Keeping the {done} releases generated so far."
+                    );
+                    break;
+                }
+            }
+
+            changelog.releases.insert(tag.clone(), release);
+            done += 1;
+        }
+
+        prev = Some(tag);
+    }
+
+    Ok(())
+}
+
+/// Compare links for Unreleased and each release of the changelog.
+pub fn footer_links(
+    changelog: &ChangeLog,
+    provider: &GitProvider,
+    repo: &str,
+    tag_prefix: &str,
+) -> Result<Vec<FooterLink>> {
+    let mut links = Vec::new();
+
+    let Some(last) = changelog.last_version() else {
+        return Ok(links);
+    };
+
+    match provider.unreleased_link(repo, &last, tag_prefix) {
+        Ok(link) => links.push(FooterLink {
+            text: UNRELEASED.into(),
+            link,
+        }),
+        Err(e) => {
+            warn!("{e}");
+            return Ok(links);
+        }
+    }
+
+    let versions = changelog.releases_keys().collect::<Vec<_>>();
+
+    for (pos, version) in versions.iter().enumerate() {
+        let diff_tags = DiffTags::new((*version).clone(), versions.get(pos + 1).cloned().cloned())?
+            .with_tag_prefix(tag_prefix);
+
+        links.push(FooterLink {
+            text: version.to_string(),
+            link: provider.diff_link(repo, &diff_tags)?,
+        });
+    }
+
+    Ok(links)
+}
+
+/// Rewrite the footer links of Unreleased and each release to the compare links of `provider`.
+/// The first release links to its tag. Links that don't correspond to a release are left untouched.
+pub fn normalize_footer_links(
+    changelog: &mut ChangeLog,
+    provider: &GitProvider,
+    repo: &str,
+    tag_prefix: &str,
+) -> Result<()> {
+    let versions = changelog.releases_keys().cloned().collect::<Vec<_>>();
+
+    for footer_link in &mut changelog.footer_links.links {
+        if footer_link.text.eq_ignore_ascii_case(UNRELEASED) {
+            if let Some(last) = versions.first() {
+                footer_link.link = provider.unreleased_link(repo, last, tag_prefix)?;
+                continue;
+            }
+        }
+
+        let pos = Version::from_str(&footer_link.text)
+            .ok()
+            .and_then(|version| versions.iter().position(|v| *v == version));
+
+        match pos {
+            Some(pos) => {
+                let diff_tags =
+                    DiffTags::new(versions[pos].clone(), versions.get(pos + 1).cloned())?
+                        .with_tag_prefix(tag_prefix);
+                footer_link.link = provider.diff_link(repo, &diff_tags)?;
+            }
+            None => warn!(
+                "The footer link [{}] doesn't correspond to a release, left untouched",
+                footer_link.text
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn normalize_links() {
+        let mut changelog = parse_changelog(
+            "## [Unreleased]
+
+## [1.1.0]
+
+## [1.0.0]
+
+[Unreleased]: https://github.com/a/b/compare/1.0.0...master
+[1.1.0]: ../../releases/tag/1.1.0
+[1.0.0]: https://github.com/a/b/compare/0.1.0...1.0.0
+[docs]: https://docs.rs
+",
+        )
+        .unwrap();
+
+        normalize_footer_links(&mut changelog, &GitProvider::Github, "a/b", "").unwrap();
+
+        assert_eq!(
+            changelog.footer_links.links,
+            vec![
+                FooterLink {
+                    text: "Unreleased".into(),
+                    link: "https://github.com/a/b/compare/1.1.0...HEAD".into()
+                },
+                FooterLink {
+                    text: "1.1.0".into(),
+                    link: "https://github.com/a/b/compare/1.0.0...1.1.0".into()
+                },
+                FooterLink {
+                    text: "1.0.0".into(),
+                    link: "https://github.com/a/b/tree/1.0.0".into()
+                },
+                FooterLink {
+                    text: "docs".into(),
+                    link: "https://docs.rs".into()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::bail;
+use changelog::{ChangeLog, Release, Version};
+
+use crate::{config::Bump, utils};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Patch,
+    Minor,
+    Major,
+}
+
+/// Suggest the version following the last release, based on the Unreleased notes.
+/// `today` is the current (year, month), used by the calver scheme.
+pub fn bump(changelog: &ChangeLog, options: &Bump, today: (u64, u64)) -> anyhow::Result<Version> {
+    let Some(unreleased) = &changelog.unreleased else {
+        bail!("No unreleased section");
+    };
+
+    if unreleased
+        .note_sections
+        .values()
+        .all(|s| s.notes.is_empty())
+    {
+        bail!("Nothing to release: the unreleased section is empty");
+    }
+
+    let Some(current) = options.current.clone().or_else(|| changelog.last_version()) else {
+        bail!("No previous release found. Use the --current option.");
+    };
+
+    let current = current.version();
+
+    let next = if options.calver {
+        let (year, month) = today;
+
+        if current.major == year && current.minor == month {
+            Version::new(year, month, current.patch + 1)
+        } else {
+            Version::new(year, month, 0)
+        }
+    } else {
+        match level(unreleased, options) {
+            Level::Major => Version::new(current.major + 1, 0, 0),
+            Level::Minor => Version::new(current.major, current.minor + 1, 0),
+            Level::Patch => Version::new(current.major, current.minor, current.patch + 1),
+        }
+    };
+
+    Ok(next)
+}
+
+pub fn level(release: &Release, options: &Bump) -> Level {
+    let is_in = |sections: &[String], title: &str| {
+        sections
+            .iter()
+            .any(|section| section.eq_ignore_ascii_case(title))
+    };
+
+    let mut level = Level::Patch;
+
+    for (title, section) in &release.note_sections {
+        if section.notes.is_empty() {
+            continue;
+        }
+
+        let breaking = section.notes.iter().any(|note| {
+            note.scope
+                .as_deref()
+                .unwrap_or(&note.message)
+                .to_uppercase()
+                .starts_with("BREAKING")
+        });
+
+        if breaking || is_in(&options.major_sections, title) {
+            return Level::Major;
+        }
+
+        if is_in(&options.minor_sections, title) {
+            level = Level::Minor;
+        }
+    }
+
+    level
+}
+
+/// Current (year, month), in UTC.
+pub fn today() -> (u64, u64) {
+    civil_from_days(utils::today())
+}
+
+fn civil_from_days(days: i64) -> (u64, u64) {
+    let (year, month, _) = utils::civil_from_days(days);
+
+    (year, month)
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+
+    use crate::config::cli_defaults;
+
+    use super::*;
+
+    fn default_options() -> Bump {
+        cli_defaults()
+    }
+
+    fn suggest(unreleased: &str, options: &Bump) -> anyhow::Result<String> {
+        let changelog = parse_changelog(&format!(
+            "## [Unreleased]\n\n{unreleased}\n## [1.2.3]\n\n### Fixed\n\n- a\n"
+        ))?;
+
+        bump(&changelog, options, (2024, 5)).map(|v| v.to_string())
+    }
+
+    #[test]
+    fn levels() {
+        let options = default_options();
+
+        assert_eq!(suggest("### Fixed\n\n- b\n", &options).unwrap(), "1.2.4");
+        assert_eq!(
+            suggest("### Fixed\n\n- b\n\n### Added\n\n- c\n", &options).unwrap(),
+            "1.3.0"
+        );
+        assert_eq!(
+            suggest(
+                "### Added\n\n- c\n\n### Breaking Changes\n\n- d\n",
+                &options
+            )
+            .unwrap(),
+            "2.0.0"
+        );
+        assert_eq!(
+            suggest("### Changed\n\n- BREAKING: d\n", &options).unwrap(),
+            "2.0.0"
+        );
+
+        suggest("", &options).unwrap_err();
+    }
+
+    #[test]
+    fn configured_sections() {
+        let mut options = default_options();
+        options.minor_sections = vec!["Changed".into()];
+        options.current = Some(Version::new(0, 1, 0));
+
+        assert_eq!(suggest("### Added\n\n- c\n", &options).unwrap(), "0.1.1");
+        assert_eq!(suggest("### Changed\n\n- c\n", &options).unwrap(), "0.2.0");
+    }
+
+    #[test]
+    fn calver() {
+        let mut options = default_options();
+        options.calver = true;
+
+        assert_eq!(suggest("### Added\n\n- c\n", &options).unwrap(), "2024.5.0");
+
+        options.current = Some(Version::new(2024, 5, 0));
+        assert_eq!(suggest("### Added\n\n- c\n", &options).unwrap(), "2024.5.1");
+
+        assert_eq!(civil_from_days(0), (1970, 1));
+        assert_eq!(civil_from_days(19_844), (2024, 5));
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::bail;
+use changelog::ChangeLog;
+
+use crate::{
+    config::Check,
+    generate::{commit_should_be_ignored, Response},
+    repository::{RawCommit, Repository},
+};
+
+/// Fail with the unmet requirement.
+pub fn check<R: Repository>(r: &R, changelog: &ChangeLog, options: &Check) -> anyhow::Result<()> {
+    let notes = changelog
+        .unreleased
+        .iter()
+        .flat_map(|release| release.note_sections.values())
+        .flat_map(|section| &section.notes)
+        .collect::<Vec<_>>();
+
+    if options.require_unreleased_entry && notes.is_empty() {
+        bail!("The Unreleased section has no entry.");
+    }
+
+    if let Some(rev) = &options.require_entry_for {
+        let sha = if rev == "HEAD" {
+            r.last_commit_sha()
+        } else {
+            rev.clone()
+        };
+
+        let raw_commit = RawCommit::from_sha(r, &sha);
+
+        if let Response::Yes { reason, .. } =
+            commit_should_be_ignored(&raw_commit, &options.skip_trailer)
+        {
+            info!("No entry required for the ignored commit {rev}: {reason}");
+            return Ok(());
+        }
+
+        let id = match options.pr.clone().or_else(|| pr_number(&raw_commit.title)) {
+            Some(pr) => format!("#{}", pr.trim_start_matches('#')),
+            None => raw_commit.short_commit().to_owned(),
+        };
+
+        if !notes.iter().any(|note| references(&note.message, &id)) {
+            bail!("No entry of the Unreleased section references {id}, for the commit {rev}.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of the PR mentioned in a commit title, like "fix: crash (#12)".
+fn pr_number(title: &str) -> Option<String> {
+    let (_, rest) = title.rsplit_once('#')?;
+
+    let number = rest
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>();
+
+    if number.is_empty() {
+        None
+    } else {
+        Some(number)
+    }
+}
+
+/// `message` contains `id`, not followed by another digit.
+fn references(message: &str, id: &str) -> bool {
+    message.match_indices(id).any(|(pos, _)| {
+        !message[pos + id.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit())
+    })
+}
@@ -3,9 +3,14 @@ use utils::{into_string, space};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FormattedCommit {
+    /// Conventional commit type, like "feat". Empty when the commit is not conventional.
+    pub kind: String,
+    /// Section of the note. The commit type until it is mapped.
     pub section: String,
     pub scope: Option<String>,
     pub message: String,
+    /// Marked with "!", like "feat(api)!: ...".
+    pub breaking: bool,
 }
 
 pub fn parse_commit(input: &str) -> anyhow::Result<FormattedCommit> {
@@ -19,14 +24,19 @@ pub fn parse_commit(input: &str) -> anyhow::Result<FormattedCommit> {
 fn commit_parser<'a>() -> Parser<'a, char, FormattedCommit> {
     let scope = space() * sym('(') * none_of("()").repeat(1..) - sym(')');
 
-    let parser = none_of(" :()").repeat(1..) + scope.opt() - space() * sym(':') * space()
+    let parser = none_of(" :()!").repeat(1..) + scope.opt() + sym('!').opt()
+        - space() * sym(':') * space()
         + any().repeat(1..);
 
-    parser.convert(|((section, scope), message)| {
+    parser.convert(|(((section, scope), breaking), message)| {
+        let kind = into_string(section);
+
         let res = FormattedCommit {
-            section: into_string(section),
+            section: kind.clone(),
+            kind,
             scope: scope.map(into_string),
             message: into_string(message),
+            breaking: breaking.is_some(),
         };
 
         Ok::<FormattedCommit, ()>(res)
@@ -62,9 +72,11 @@ mod test {
         assert_eq!(
             commit_parser().parse(&m),
             Ok(FormattedCommit {
+                kind: String::from("fix"),
                 section: String::from("fix"),
                 scope: Some(String::from("hello")),
-                message: String::from("hihi")
+                message: String::from("hihi"),
+                breaking: false,
             })
         );
 
@@ -78,10 +90,30 @@ mod test {
         assert_eq!(
             commit_parser().parse(&m),
             Ok(FormattedCommit {
+                kind: String::from("improve"),
                 section: String::from("improve"),
                 scope: Some(String::from("ignore")),
-                message: String::from("hihi")
+                message: String::from("hihi"),
+                breaking: false,
+            })
+        );
+    }
+
+    #[test]
+    fn breaking() {
+        let m = map("feat(api)!: new signatures");
+        assert_eq!(
+            commit_parser().parse(&m),
+            Ok(FormattedCommit {
+                kind: String::from("feat"),
+                section: String::from("feat"),
+                scope: Some(String::from("api")),
+                message: String::from("new signatures"),
+                breaking: true,
             })
         );
+
+        let m = map("refactor!: rework");
+        assert!(commit_parser().parse(&m).unwrap().breaking);
     }
 }
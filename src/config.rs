@@ -1,20 +1,29 @@
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::{collections::HashSet, fmt::Display};
 
-use changelog::fmt::SortOptions;
-use clap::{arg, Args, Parser, Subcommand, ValueHint};
+use changelog::fmt::{SortOptions, KEEP_A_CHANGELOG_SECTIONS};
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand, ValueHint};
 
 use changelog::ser::{Options, OptionsRelease};
-use changelog::Version;
+use changelog::{utils::UNRELEASED, Version};
 use clap::ValueEnum;
 use indexmap::IndexMap;
+use log::LevelFilter;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::git_provider::GitProvider;
 
+pub const SECTION_TRAILER: &str = "Changelog-Section";
+pub const SKIP_TRAILER: &str = "Changelog";
+/// The same key as [`SKIP_TRAILER`]: a value other than "skip" or "ignore" is the message.
+pub const MESSAGE_TRAILER: &str = "Changelog";
+/// Value of `--map` selecting [`MapMessageToSection::keep_a_changelog_default`].
+pub const KEEP_A_CHANGELOG_MAP: &str = "keep-a-changelog";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MapMessageToSection(pub IndexMap<String, HashSet<String>>);
 
@@ -25,7 +34,50 @@ impl Default for MapMessageToSection {
     }
 }
 
+/// Section found by [`MapMessageToSection::try_find_section_keyword`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundSection {
+    pub section: String,
+    pub keyword: String,
+    /// The keyword was found in the description, not in the message.
+    pub in_description: bool,
+}
+
 impl MapMessageToSection {
+    /// The Keep a Changelog sections, in their canonical order, mapped from the conventional commit types.
+    pub fn keep_a_changelog_default() -> Self {
+        let types: [&[&str]; 6] = [
+            &["feat", "feature", "add", "new"],
+            &[
+                "change", "impr", "improve", "perf", "refactor", "build", "chore",
+            ],
+            &["deprecate", "obsolete"],
+            &["remove", "rm", "delete"],
+            &["fix", "bug", "issue", "hotfix"],
+            &["sec", "security", "vulnerability"],
+        ];
+
+        let map = KEEP_A_CHANGELOG_SECTIONS
+            .iter()
+            .zip(types)
+            .map(|(section, types)| {
+                (
+                    section.to_string(),
+                    types.iter().map(ToString::to_string).collect(),
+                )
+            })
+            .collect();
+
+        Self(map)
+    }
+
+    /// Like [`Self::to_fmt_options`], but the sections of the versioned releases are sorted too.
+    pub fn to_fmt_options_all_releases(self) -> changelog::fmt::Options {
+        let mut options = self.to_fmt_options();
+        options.sort_options.reorder_releases = true;
+        options
+    }
+
     pub fn to_fmt_options(self) -> changelog::fmt::Options {
         changelog::fmt::Options {
             sort_options: SortOptions {
@@ -43,6 +95,12 @@ impl MapMessageToSection {
     }
 
     pub fn map_section(&self, section: &str) -> Option<String> {
+        self.map_section_keyword(section)
+            .map(|(section, _)| section)
+    }
+
+    /// Like [`Self::map_section`], with the keyword that matched.
+    pub fn map_section_keyword(&self, section: &str) -> Option<(String, String)> {
         let section_normalized = section.to_lowercase();
 
         for (section, needles) in &self.0 {
@@ -50,7 +108,7 @@ impl MapMessageToSection {
                 let needle_normalized = needle.to_lowercase();
 
                 if section_normalized == needle_normalized {
-                    return Some(section.to_owned());
+                    return Some((section.to_owned(), needle.to_owned()));
                 }
             }
         }
@@ -59,7 +117,13 @@ impl MapMessageToSection {
     }
 
     /// Best effort recognition
-    pub fn try_find_section(&self, (message, desc): (&str, &str)) -> Option<String> {
+    pub fn try_find_section(&self, message_desc: (&str, &str)) -> Option<String> {
+        self.try_find_section_keyword(message_desc)
+            .map(|found| found.section)
+    }
+
+    /// Like [`Self::try_find_section`], with the keyword that matched and where.
+    pub fn try_find_section_keyword(&self, (message, desc): (&str, &str)) -> Option<FoundSection> {
         let message_normalized = message.to_lowercase();
         let desc_normalized = desc.to_lowercase();
 
@@ -67,20 +131,32 @@ impl MapMessageToSection {
             for needle in needles {
                 let needle_normalized = needle.to_lowercase();
 
-                if message_normalized.contains(&needle_normalized) {
-                    return Some(section.to_owned());
-                }
-                if desc_normalized.contains(&needle_normalized) {
-                    return Some(section.to_owned());
-                }
+                let in_description = if message_normalized.contains(&needle_normalized) {
+                    false
+                } else if desc_normalized.contains(&needle_normalized) {
+                    true
+                } else {
+                    continue;
+                };
+
+                return Some(FoundSection {
+                    section: section.to_owned(),
+                    keyword: needle.to_owned(),
+                    in_description,
+                });
             }
         }
 
         None
     }
 
+    /// The map of the file at `path`, or [`Self::keep_a_changelog_default`] if `path` is
+    /// [`KEEP_A_CHANGELOG_MAP`].
     pub fn try_new<P: AsRef<Path>>(path: Option<P>) -> anyhow::Result<MapMessageToSection> {
         match path {
+            Some(path) if path.as_ref() == Path::new(KEEP_A_CHANGELOG_MAP) => {
+                Ok(MapMessageToSection::keep_a_changelog_default())
+            }
             Some(path) => {
                 let mut file = File::open(&path)?;
 
@@ -114,6 +190,48 @@ impl Display for CommitMessageParsing {
     }
 }
 
+#[derive(ValueEnum, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BodyMode {
+    /// Don't use the commit body
+    #[default]
+    Ignore,
+    /// Add the first paragraph of the commit body as context
+    FirstParagraph,
+    /// Add each paragraph of the commit body as a context line
+    AllParagraphs,
+}
+
+impl Display for BodyMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyMode::Ignore => write!(f, "ignore"),
+            BodyMode::FirstParagraph => write!(f, "first-paragraph"),
+            BodyMode::AllParagraphs => write!(f, "all-paragraphs"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, PartialEq, Eq)]
+pub enum ScopePolicy {
+    /// Keep the scope, but print a warning
+    #[default]
+    Warn,
+    /// Remove the scope from the release note
+    Drop,
+    /// Exclude the commit
+    Error,
+}
+
+impl Display for ScopePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopePolicy::Warn => write!(f, "warn"),
+            ScopePolicy::Drop => write!(f, "drop"),
+            ScopePolicy::Error => write!(f, "error"),
+        }
+    }
+}
+
 #[derive(ValueEnum, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum MergeDevVersions {
     /// Yes if the version is stable, no otherwise
@@ -138,60 +256,223 @@ impl Display for MergeDevVersions {
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Write the resulting changelog to this file instead of modifying the input. "-" means the standard output.
+    #[arg(short, long, global = true, value_hint = ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+    /// Parse the resulting changelog back before writing it, and fail if it doesn't match.
+    #[arg(long, global = true)]
+    pub verify_roundtrip: bool,
+    /// Only print the errors. Must be placed before the command.
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// Print the debug logs, or the trace logs with -vv. Must be placed before the command.
+    #[arg(short, long, action = ArgAction::Count)]
+    pub verbose: u8,
+}
+
+impl Cli {
+    pub fn log_level(&self) -> LevelFilter {
+        if self.quiet {
+            return LevelFilter::Error;
+        }
+
+        match self.verbose {
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum Commands {
+    #[command(alias = "init")]
     New(New),
     Validate(Validate),
     #[command(alias = "gen")]
-    Generate(Generate),
+    Generate(Box<Generate>),
     Release(Release),
     Show(Show),
     #[command(aliases = ["delete", "rm"])]
     Remove(Remove),
+    Merge(Merge),
+    Backfill(Backfill),
+    Bump(Bump),
+    Fmt(Fmt),
+    Check(Check),
+    /// Mark a release as yanked, with "[YANKED]" after its title.
+    Yank(Yank),
+    /// Remove the "[YANKED]" marker of a release.
+    Unyank(Yank),
+    NormalizeLinks(NormalizeLinks),
+    Export(Export),
+    ExportAppstream(ExportAppstream),
+    ExportDebian(ExportDebian),
+    ExportRpm(ExportRpm),
+    ExportFeed(ExportFeed),
+    Publish(Publish),
+    Diff(Diff),
+    Amend(Amend),
+    TagMessage(TagMessage),
+    Stats(Stats),
+    Search(Search),
+    Summarize(Summarize),
+    #[command(alias = "collect")]
+    ImportFragments(ImportFragments),
+    ImportReleases(ImportReleases),
+    Convert(Convert),
 }
 
 /// Generate release notes. By default, generate from the last release in the changelog to HEAD.
 #[derive(Debug, Clone, Args)]
 pub struct Generate {
-    /// Path to the changelog file.
+    /// Path to the changelog file. "-" means the standard input.
     #[arg(
         short,
         long,
         default_value = "CHANGELOG.md",
         value_hint = ValueHint::FilePath,
-        short_alias = 'o',
-        alias = "output",
     )]
     pub file: Option<PathBuf>,
-    /// Path to the commit type to changelog section map.
+    /// Path to the commit type to changelog section map,
+    /// or "keep-a-changelog" for the Keep a Changelog sections.
     #[arg(long, value_hint = ValueHint::FilePath)]
     pub map: Option<PathBuf>,
     /// Parsing of the commit message.
     #[arg(long, default_value_t)]
     pub parsing: CommitMessageParsing,
+    /// How the commit body is mapped to the context of the release note.
+    #[arg(long, default_value_t)]
+    pub multiline_body: BodyMode,
     /// Don't include unidentified commits.
     #[arg(long)]
     pub exclude_unidentified: bool,
+    /// Use the title of the pull request of a commit in place of its subject, when there is one.
+    /// PR titles are often better curated than the subjects of squash merges.
+    #[arg(long)]
+    pub prefer_pr_title: bool,
     /// Don't include commits which are not attached to a pull request.
     #[arg(long)]
     pub exclude_not_pr: bool,
+    /// Fail, listing the commits which are not attached to a pull request, instead of including them.
+    #[arg(long, conflicts_with = "exclude_not_pr")]
+    pub require_pr: bool,
+    /// Skip the commits whose PR or message is already in a versioned release,
+    /// like when generating after tagging, but before the release.
+    #[arg(long)]
+    pub skip_released: bool,
+    /// Use the git note attached to a commit, when present, in place of its message.
+    /// The first line of the note is parsed like a commit title.
+    #[arg(long)]
+    pub git_notes: bool,
+    /// Only include merge commits. Useful when every change lands with a merge commit.
+    #[arg(long)]
+    pub merges_only: bool,
+    /// Only include the commits touching files under this path. Useful for a package of a monorepo.
+    #[arg(long = "path", value_hint = ValueHint::DirPath)]
+    pub path_filter: Option<String>,
+    /// Files matching this glob don't count as touched. Example: '*.md' to ignore docs-only changes.
+    /// A glob without "/" is matched against the file name. Can be repeated.
+    #[arg(long = "exclude-glob")]
+    pub exclude_globs: Vec<String>,
+    /// Key of the commit trailer overriding the section of the note, before any mapping.
+    /// Example: "Changelog-Section: Security".
+    #[arg(long, default_value = SECTION_TRAILER)]
+    pub section_trailer: String,
+    /// Key of the commit trailer skipping the commit, with the value "skip" or "ignore".
+    /// Example: "Changelog: skip".
+    #[arg(long, default_value = SKIP_TRAILER)]
+    pub skip_trailer: String,
+    /// Key of the commit trailer replacing the message of the note, unless its value is "skip"
+    /// or "ignore". Example: "Changelog: Faster startup on large repositories".
+    #[arg(long, default_value = MESSAGE_TRAILER)]
+    pub message_trailer: String,
+    /// Keys of the commit footers added as context lines of the note, like "Refs: #10".
+    #[arg(long = "context-footer", value_delimiter = ',')]
+    pub context_footers: Vec<String>,
+    /// Generate one note per PR, titled by the PR, with its commits as context.
+    /// Commits without PR get their own note. Needs the repo.
+    #[arg(long)]
+    pub group_by_pr: bool,
+    /// Number of PRs requested at the same time, for the commits not found in the last merged PRs.
+    /// Only used with --group-by-pr.
+    #[arg(long, default_value_t = 8)]
+    pub pr_concurrency: usize,
+    /// Commits with one of these words in their title, or a "DEPRECATED:" trailer, go to the Deprecated section.
+    #[arg(long, value_delimiter = ',', default_value = "deprecate,deprecated")]
+    pub deprecation_keywords: Vec<String>,
+    /// Scopes allowed in commit messages. All scopes are allowed if omitted.
+    #[arg(long, value_delimiter = ',')]
+    pub allowed_scopes: Option<Vec<String>>,
+    /// What to do with a scope which is not allowed.
+    #[arg(long, default_value_t, requires = "allowed_scopes")]
+    pub scope_policy: ScopePolicy,
+    /// Route a note to the section named by its scope, and drop the scope.
+    /// Example: "fix(security): ..." goes in the Security section.
+    #[arg(long)]
+    pub promote_scope_to_section: bool,
+    /// Fail instead of creating a section which is not in the release, in the map,
+    /// or in the allowed sections. Catches the typos in the sections of the commits.
+    #[arg(long)]
+    pub strict_sections: bool,
+    /// Other sections which can be created with --strict-sections.
+    /// Example: "Unidentified", to keep the unidentified commits.
+    #[arg(long, value_delimiter = ',', requires = "strict_sections")]
+    pub allowed_sections: Vec<String>,
+    /// Heading written for a section, as SECTION=HEADING, like "Added=✨ Features". Can be repeated.
+    /// The sections written under these headings are read back as their section.
+    #[arg(
+        long = "section-heading",
+        value_name = "SECTION=HEADING",
+        value_parser = parse_section_heading
+    )]
+    pub section_headings: Vec<(String, String)>,
+    /// Write the notes of each section by commit date, the most recent first.
+    /// The notes already in the changelog have no date, and stay after the new ones.
+    #[arg(long)]
+    pub sort_notes_by_date: bool,
     /// We use the Github api to map commit sha to PRs.
     #[arg(long, default_value_t)]
     pub provider: GitProvider,
     /// Needed for fetching PRs. Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
     #[arg(long)]
     pub repo: Option<String>,
+    /// Branch PRs are merged into, used when the default branch can't be detected.
+    #[arg(long, default_value = "main")]
+    pub default_branch: String,
     /// Omit the PR link from the output.
     #[arg(long)]
     pub omit_pr_link: bool,
+    /// Format of the PR link. {id} and {url} are replaced by the PR id and url.
+    #[arg(long, default_value = "in [{id}]({url})")]
+    pub pr_link_format: String,
     /// Omit contributors' acknowledgements/mention.
     #[arg(long)]
     pub omit_thanks: bool,
+    /// Only thank the external contributors: the authors listed in --internal-author
+    /// keep the PR link, without acknowledgement. Bots can be listed there too.
+    #[arg(long, requires = "internal_authors")]
+    pub thanks_only_external: bool,
+    /// Logins of the maintainers, not thanked with --thanks-only-external.
+    #[arg(long = "internal-author", value_delimiter = ',')]
+    pub internal_authors: Vec<String>,
+    /// Link of an author in the thanks, when the provider doesn't give a full one.
+    /// {author} is replaced by the login. Example: 'https://git.example.com/{author}'.
+    #[arg(long)]
+    pub author_link_template: Option<String>,
     /// Print the result on the standard output.
     #[arg(long)]
     pub stdout: bool,
+    /// Don't write the changelog, only print a summary of the generated notes.
+    /// It is colored on a terminal, unless NO_COLOR is set.
+    #[arg(long, conflicts_with = "stdout")]
+    pub dry_run: bool,
+    /// Print the summary of the generated notes as a JSON report. Unless with --dry-run, the changelog must then be written to a file.
+    #[arg(long, default_value_t, conflicts_with = "stdout")]
+    pub output_format: OutputFormat,
+    /// Also append the Unreleased notes to the Github Actions step summary ($GITHUB_STEP_SUMMARY).
+    #[arg(long)]
+    pub step_summary: bool,
     /// Generate only this commit, or tag.
     #[arg(
         long,
@@ -204,12 +485,44 @@ pub struct Generate {
         conflicts_with_all = ["since", "until"],
     )]
     pub milestone: Option<String>,
+    /// Other repos sharing the milestone. Their PRs are added too, with links like 'owner/name#12'.
+    /// Only used with --milestone.
+    #[arg(long = "milestone-repo", requires = "milestone")]
+    pub milestone_repos: Vec<String>,
+    /// Use the content of the changelog section of a PR body, when present, in place of the PR title,
+    /// or of the message of the commits of the PR.
+    #[arg(long)]
+    pub prefer_pr_changelog_section: bool,
+    /// Heading of the changelog section in PR bodies.
+    #[arg(long, default_value = "Changelog")]
+    pub pr_changelog_heading: String,
     /// Include all commits in \"since..until\".
     #[arg(long)]
     pub since: Option<String>,
     /// Include all commits in \"since..until\".
     #[arg(long, requires = "since")]
     pub until: Option<String>,
+    /// Prefix of the git tags, stripped to get the versions. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
+}
+
+/// The options of a command when no argument is given.
+pub(crate) fn cli_defaults<T: Args>() -> T {
+    #[derive(Parser)]
+    struct Wrapper<T: Args> {
+        #[command(flatten)]
+        args: T,
+    }
+
+    Wrapper::<T>::parse_from(["changen"]).args
+}
+
+impl Default for Generate {
+    /// Same values as the command line defaults.
+    fn default() -> Self {
+        cli_defaults()
+    }
 }
 
 /// Generate a new release. By default, use the last tag present in the repo.
@@ -255,6 +568,19 @@ pub struct Release {
     /// Print the result on the standard output.
     #[arg(long)]
     pub stdout: bool,
+    /// When no version is given, use the last tag matching this regex.
+    #[arg(long)]
+    pub tag_filter: Option<Regex>,
+    /// Prefix of the git tags, stripped to get the versions. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
+}
+
+impl Default for Release {
+    /// Same values as the command line defaults.
+    fn default() -> Self {
+        cli_defaults()
+    }
 }
 
 /// Validate a changelog syntax
@@ -271,15 +597,100 @@ pub struct Validate {
     /// Format the changelog.
     #[arg(long, alias = "fmt")]
     pub format: bool,
-    /// Path to the commit type to changelog section map.
+    /// Path to the commit type to changelog section map,
+    /// or "keep-a-changelog" for the Keep a Changelog sections.
     #[arg(long, value_hint = ValueHint::FilePath)]
     pub map: Option<PathBuf>,
     /// Show the Abstract Syntax Tree.
     #[arg(long)]
     pub ast: bool,
+    /// Add a "(see Deprecated in X)" line to the removed notes matching a deprecated note of an older release.
+    #[arg(long)]
+    pub link_lifecycle: bool,
+    /// Check that each release has a footer link, and that each footer link named after a version
+    /// corresponds to a release.
+    #[arg(long)]
+    pub footer_links: bool,
+    /// Add the missing footer links, and remove the orphaned ones. Imply --footer-links.
+    #[arg(long)]
+    pub fix: bool,
+    /// With --fix, keep the orphaned footer links, and only report them.
+    #[arg(long, requires = "fix")]
+    pub keep_orphans: bool,
+    /// Send a HEAD request to each footer link, and report the dead ones.
+    #[arg(long)]
+    pub check_urls: bool,
+    /// Git provider of the repo, for the links added by --fix.
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Needed for the links added by --fix. Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Prefix of the git tags, for the links added by --fix. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
     /// Print the result on the standard output.
     #[arg(long)]
     pub stdout: bool,
+    /// Print the diagnostics as a JSON report.
+    #[arg(long, default_value_t, conflicts_with = "stdout")]
+    pub output_format: OutputFormat,
+}
+
+/// Format of the messages of a command.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// For humans
+    #[default]
+    Text,
+    /// A JSON report on stdout, nothing else. The schema is versioned with its "schema_version" field.
+    Json,
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, PartialEq, Eq)]
+pub enum ShowFormat {
+    #[default]
+    Markdown,
+    Json,
+    /// Plain text, without markdown syntax
+    Plain,
+}
+
+impl Display for ShowFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShowFormat::Markdown => write!(f, "markdown"),
+            ShowFormat::Json => write!(f, "json"),
+            ShowFormat::Plain => write!(f, "plain"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeadingStyle {
+    /// The heading, underlined with '=' or '-'
+    #[default]
+    Underline,
+    /// The heading in uppercase
+    Uppercase,
+}
+
+impl Display for HeadingStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeadingStyle::Underline => write!(f, "underline"),
+            HeadingStyle::Uppercase => write!(f, "uppercase"),
+        }
+    }
 }
 
 /// Show a releases on stdout. By default, show the last release.
@@ -293,6 +704,12 @@ pub struct Show {
         value_hint = ValueHint::FilePath,
     )]
     pub file: Option<PathBuf>,
+    /// Version of the release to show, with or without a "v" prefix, or "unreleased".
+    #[arg(conflicts_with_all = ["n", "version"])]
+    pub release: Option<String>,
+    /// Show the newest versioned release.
+    #[arg(long, conflicts_with_all = ["release", "n", "version"])]
+    pub latest: bool,
     /// -1 being unreleased, 0 the last release, ...
     #[arg(
         short,
@@ -309,7 +726,20 @@ pub struct Show {
         default_missing_value=None
     )]
     pub version: Option<Regex>,
+    /// Output format.
+    #[arg(long, alias = "format", default_value_t)]
+    pub output_format: ShowFormat,
+    /// Column at which the plain text is wrapped.
+    #[arg(long, default_value_t = 72)]
+    pub width: usize,
+    /// Style of the headings of the plain text.
+    #[arg(long, default_value_t)]
+    pub heading_style: HeadingStyle,
+    /// Write the links of the plain text as "text (url)", rather than only their text.
+    #[arg(long)]
+    pub link_urls: bool,
 }
+
 /// Create a new changelog file with an accepted syntax
 #[derive(Debug, Clone, Args)]
 pub struct New {
@@ -324,9 +754,27 @@ pub struct New {
     /// Override of existing file.
     #[arg(short, long)]
     pub force: bool,
+    /// Add an empty release for each tag present in the repo.
+    #[arg(long)]
+    pub tags: bool,
+    /// Add a release for each tag present in the repo, with its generated release notes.
+    #[arg(long)]
+    pub backfill: bool,
+    /// We use the Github link to produce the footer links.
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Needed for the footer links. Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Only consider the tags matching this regex. Example: "^v?\d+\.\d+\.\d+$" to ignore nightly tags.
+    #[arg(long)]
+    pub tag_filter: Option<Regex>,
+    /// Prefix of the git tags, stripped to get the versions. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
 }
 
-/// Remove a release
+/// Remove a release, or some of its notes
 #[derive(Debug, Clone, Args)]
 pub struct Remove {
     /// Path to the changelog file.
@@ -343,6 +791,12 @@ pub struct Remove {
 
     #[clap(flatten)]
     pub remove_id: RemoveSelection,
+    /// Only remove the notes whose message matches this regex, instead of the whole release.
+    #[arg(short, long = "match")]
+    pub matches: Option<Regex>,
+    /// Only remove notes of this section. Example: Fixed
+    #[arg(long, requires = "matches")]
+    pub section: Option<String>,
 }
 
 // fixme: move this to an enum https://github.com/clap-rs/clap/issues/2621
@@ -352,7 +806,654 @@ pub struct RemoveSelection {
     /// -1 being unreleased, 0 the last release, ...
     #[arg(short, conflicts_with = "version", allow_hyphen_values = true)]
     pub n: Option<i32>,
-    /// Remove a specific version. Also accept regex. Example: 1.0.0-*
-    #[arg(short, long)]
+    /// Remove a specific version. Also accept regex. Example: 1.0.0-*. "unreleased" selects the Unreleased section.
+    #[arg(short, long, alias = "release")]
     pub version: Option<Regex>,
 }
+
+/// Merge two changelogs. On conflict, the first changelog is preferred. Printed on the standard output unless --output is used.
+#[derive(Debug, Clone, Args)]
+pub struct Merge {
+    /// Path to the first changelog file.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub first: PathBuf,
+    /// Path to the second changelog file.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub second: PathBuf,
+}
+
+/// Generate a changelog with a release for each tag of the repo.
+#[derive(Debug, Clone, Args)]
+pub struct Backfill {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Override of existing file.
+    #[arg(long)]
+    pub force: bool,
+    /// First tag to include.
+    #[arg(long)]
+    pub from: Option<Version>,
+    /// Last tag to include.
+    #[arg(long)]
+    pub to: Option<Version>,
+    /// Path to the commit type to changelog section map,
+    /// or "keep-a-changelog" for the Keep a Changelog sections.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub map: Option<PathBuf>,
+    /// Don't include unidentified commits.
+    #[arg(long)]
+    pub exclude_unidentified: bool,
+    /// We use the Github api to map commit sha to PRs.
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Needed for fetching PRs. Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+    /// Only consider the tags matching this regex. Example: "^v?\d+\.\d+\.\d+$" to ignore nightly tags.
+    #[arg(long)]
+    pub tag_filter: Option<Regex>,
+    /// Prefix of the git tags, stripped to get the versions. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
+}
+
+/// Suggest the next version from the content of the Unreleased section.
+#[derive(Debug, Clone, Args)]
+pub struct Bump {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Version to bump. If omitted, use the last release of the changelog.
+    #[arg(long)]
+    pub current: Option<Version>,
+    /// Use the YYYY.M.P calendar versioning scheme instead of semver.
+    #[arg(long)]
+    pub calver: bool,
+    /// Sections leading to a major bump. Notes starting with "BREAKING" always do.
+    #[arg(long, value_delimiter = ',', default_value = "Breaking Changes")]
+    pub major_sections: Vec<String>,
+    /// Sections leading to a minor bump. Any other section leads to a patch bump.
+    #[arg(long, value_delimiter = ',', default_value = "Added")]
+    pub minor_sections: Vec<String>,
+    /// Create the release with the suggested version.
+    #[arg(long)]
+    pub apply: bool,
+}
+
+/// Normalize the formatting of a changelog.
+#[derive(Debug, Clone, Args)]
+pub struct Fmt {
+    /// Path to the changelog file. "-" means the standard input.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Path to the commit type to changelog section map,
+    /// or "keep-a-changelog" for the Keep a Changelog sections.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub map: Option<PathBuf>,
+    /// Heading written for a section, as SECTION=HEADING, like "Added=✨ Features". Can be repeated.
+    /// The sections written under these headings are read back as their section.
+    #[arg(
+        long = "section-heading",
+        value_name = "SECTION=HEADING",
+        value_parser = parse_section_heading
+    )]
+    pub section_headings: Vec<(String, String)>,
+    /// Don't write anything. Print the diff and fail if the changelog is not formatted.
+    #[arg(long, conflicts_with = "stdout")]
+    pub check: bool,
+    /// Sort the sections of every release. By default, only Unreleased is sorted,
+    /// and the versioned releases keep the order they were written with.
+    #[arg(long)]
+    pub reorder_releases: bool,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+/// Check the changelog in CI. Fail with the unmet requirement.
+#[derive(Debug, Clone, Args)]
+#[command(group(
+    ArgGroup::new("requirement")
+        .required(true)
+        .multiple(true)
+        .args(["require_unreleased_entry", "require_entry_for"]),
+))]
+pub struct Check {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath
+    )]
+    pub file: Option<PathBuf>,
+    /// Fail if the Unreleased section has no entry.
+    #[arg(long)]
+    pub require_unreleased_entry: bool,
+    /// Fail if no entry of the Unreleased section references the PR of this commit. Example: HEAD.
+    /// Commits marked as ignored don't need an entry.
+    #[arg(long)]
+    pub require_entry_for: Option<String>,
+    /// Number of the PR. Found in the commit title if omitted, like "fix: crash (#12)".
+    #[arg(long, requires = "require_entry_for")]
+    pub pr: Option<String>,
+    /// Key of the commit trailer skipping the commit, with the value "skip" or "ignore".
+    /// Example: "Changelog: skip".
+    #[arg(long, default_value = SKIP_TRAILER)]
+    pub skip_trailer: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct Yank {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Version of the release.
+    #[arg(value_parser = parse_version)]
+    pub version: Version,
+    /// Line added before the prose of the release when yanking it, and removed when unyanking it.
+    #[arg(long)]
+    pub warning: Option<String>,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+/// Rewrite the footer links of the releases to the compare links of the git provider.
+#[derive(Debug, Clone, Args)]
+pub struct NormalizeLinks {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Git provider of the repo.
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Prefix of the git tags, stripped to get the versions. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+/// Export the notes of a release, to use them as the body of a Github release.
+/// Printed on the standard output unless --output is used.
+#[derive(Debug, Clone, Args)]
+pub struct Export {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Version of the release. Default to the latest release.
+    #[arg(short, long)]
+    pub version: Option<Version>,
+    /// Append a "Full Changelog: <compare link>" line.
+    #[arg(long)]
+    pub full_changelog: bool,
+    /// Git provider of the repo.
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Needed for the Full Changelog link. Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Text added before the notes. "{version}" is replaced by the version of the release.
+    #[arg(long)]
+    pub header: Option<String>,
+    /// Text added after the notes. "{version}" is replaced by the version of the release.
+    #[arg(long)]
+    pub footer: Option<String>,
+    /// Prefix of the git tags, stripped to get the versions. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
+}
+
+/// Export the releases to the <releases> element of an AppStream metainfo file, used by Flatpak
+/// and the Linux software centers. Printed on the standard output unless --metainfo is used.
+#[derive(Debug, Clone, Args)]
+pub struct ExportAppstream {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Replace the <releases> element of this metainfo file, keeping the rest of the file.
+    /// Example: data/io.github.wiiznokes.fan-control.metainfo.xml
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub metainfo: Option<PathBuf>,
+    /// Print the updated metainfo file instead of writing it.
+    #[arg(long, requires = "metainfo")]
+    pub stdout: bool,
+}
+
+/// Export the releases as a debian/changelog file. Printed on the standard output unless --output is used.
+/// Example: changen export-debian --package fan-control --revision 1 -o debian/changelog
+#[derive(Debug, Clone, Args)]
+pub struct ExportDebian {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Name of the source package.
+    #[arg(long)]
+    pub package: String,
+    /// Distribution of the releases.
+    #[arg(long, default_value = "unstable")]
+    pub distribution: String,
+    /// Urgency of the releases.
+    #[arg(long, default_value = "medium")]
+    pub urgency: String,
+    /// Maintainer signing the releases, like "Name <email>". Default to "$DEBFULLNAME <$DEBEMAIL>".
+    #[arg(long)]
+    pub maintainer: Option<String>,
+    /// Debian revision appended to the versions. Example: '1' for versions like '1.2.0-1'.
+    #[arg(long)]
+    pub revision: Option<String>,
+    /// Write the Unreleased section as an UNRELEASED stanza, dated today.
+    #[arg(long)]
+    pub include_unreleased: bool,
+}
+
+/// Export the releases as the %changelog section of a RPM spec file. Printed on the standard output unless --output is used.
+/// Example: changen export-rpm --maintainer "Alice Doe <alice@example.com>" --since 1.2.0
+#[derive(Debug, Clone, Args)]
+pub struct ExportRpm {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Packager of the releases, like "Name <email>".
+    #[arg(long)]
+    pub maintainer: String,
+    /// RPM release appended to the versions.
+    #[arg(long, default_value = "1")]
+    pub release: String,
+    /// Only export the releases newer than this version, to update an existing spec file.
+    #[arg(long, value_parser = parse_version)]
+    pub since: Option<Version>,
+}
+
+/// Export the releases as an Atom or RSS feed. Printed on the standard output unless --output is used.
+/// Example: changen export-feed --format atom --base-url https://example.com/changelog -o feed.xml
+#[derive(Debug, Clone, Args)]
+pub struct ExportFeed {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Format of the feed.
+    #[arg(long, alias = "format", default_value_t)]
+    pub output_format: FeedFormat,
+    /// URL of the changelog. The id of each entry is this URL, followed by '#' and the version.
+    #[arg(long)]
+    pub base_url: String,
+    /// Title of the feed.
+    #[arg(long, default_value = "Changelog")]
+    pub title: String,
+    /// Date of the releases without a YYYY-MM-DD date. They are skipped when not set.
+    #[arg(long)]
+    pub fallback_date: Option<String>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeedFormat {
+    #[default]
+    Atom,
+    /// RSS 2.0
+    Rss,
+}
+
+impl Display for FeedFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeedFormat::Atom => write!(f, "atom"),
+            FeedFormat::Rss => write!(f, "rss"),
+        }
+    }
+}
+
+/// Compare two releases of a changelog, or two changelog files.
+/// Sections and notes are compared by content, so reordering them is not reported.
+#[derive(Debug, Clone, Args)]
+pub struct Diff {
+    /// Path to the changelog file. Given twice, compare the two files instead of two releases.
+    #[arg(short, long, value_hint = ValueHint::FilePath)]
+    pub file: Vec<PathBuf>,
+    /// The two releases to compare, the oldest first. Example: v1.3.0 v1.4.0
+    #[arg(num_args = 2, value_parser = parse_version)]
+    pub versions: Vec<Version>,
+    /// Print the differences as a JSON report.
+    #[arg(long, default_value_t)]
+    pub output_format: OutputFormat,
+}
+
+/// Edit the last note of the Unreleased section, for quick fixups after its generation.
+#[derive(Debug, Clone, Args)]
+#[command(group(
+    ArgGroup::new("edit")
+        .required(true)
+        .multiple(true)
+        .args(["message", "scope", "section", "append_context"]),
+))]
+pub struct Amend {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// New message of the note.
+    #[arg(short, long)]
+    pub message: Option<String>,
+    /// New scope of the note. An empty scope removes it.
+    #[arg(long)]
+    pub scope: Option<String>,
+    /// Move the note to this section. Example: Fixed
+    #[arg(long)]
+    pub section: Option<String>,
+    /// Add a context line under the note. Can be repeated.
+    #[arg(long)]
+    pub append_context: Vec<String>,
+    /// JSON report of the generate command (--output-format json).
+    /// The last note of the report is amended, instead of the last note of the Unreleased section.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub report: Option<PathBuf>,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+/// Print the notes of a release as plain text, to use as the message of an annotated tag.
+/// Example: changen tag-message 1.4.0 | git tag -a v1.4.0 -F -
+#[derive(Debug, Clone, Args)]
+pub struct TagMessage {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Version of the release.
+    #[arg(value_parser = parse_version)]
+    pub version: Version,
+    /// Create the annotated tag of the release with this message, instead of printing it.
+    /// The working tree must be clean.
+    #[arg(long)]
+    pub create_tag: bool,
+    /// Prefix of the git tags. Example: 'v' for tags like 'v1.2.3'.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
+}
+
+/// Count the notes per release and section, the releases per year, and the notes of each contributor.
+#[derive(Debug, Clone, Args)]
+pub struct Stats {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Output format.
+    #[arg(long, alias = "format", default_value_t)]
+    pub output_format: OutputFormat,
+}
+
+/// Print the notes matching a regex, with their release and section. Example: changen search panic --since 1.2.0
+#[derive(Debug, Clone, Args)]
+pub struct Search {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Regex matched against the message and the context of the notes.
+    pub pattern: Regex,
+    /// Only search the releases newer than this version, and Unreleased.
+    #[arg(long, value_parser = parse_version)]
+    pub since: Option<Version>,
+    /// Only search this section.
+    #[arg(long)]
+    pub section: Option<String>,
+    /// Output format.
+    #[arg(long, alias = "format", default_value_t)]
+    pub output_format: OutputFormat,
+}
+
+/// Print a short "what's new" of a release, for an announcement: the breaking changes,
+/// the top feature and the top fix.
+#[derive(Debug, Clone, Args)]
+pub struct Summarize {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Version of the release, with or without a "v" prefix, or "unreleased".
+    #[arg(default_value = UNRELEASED)]
+    pub release: String,
+    /// Maximum number of bullets.
+    #[arg(long, default_value_t = 3)]
+    pub max_bullets: usize,
+}
+
+/// Add the releases published on the git provider which are not in the changelog, like the GitHub Releases.
+/// The headings of their notes become the sections, their lists the notes, and the rest the header of the release.
+/// The changelog is created if it doesn't exist.
+#[derive(Debug, Clone, Args)]
+pub struct ImportReleases {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Git provider of the repo.
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Prefix of the tags, stripped to get the versions. A release whose tag is not a version
+    /// is imported under its name, if it is one.
+    #[arg(long, default_value = "v")]
+    pub tag_prefix: String,
+    /// Import the draft releases too.
+    #[arg(long)]
+    pub include_drafts: bool,
+    /// Import the prereleases too.
+    #[arg(long)]
+    pub include_prereleases: bool,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+/// Add the notes of towncrier fragment files, like "newsfragments/123.feature", to the Unreleased section,
+/// and delete the fragments. The type of a fragment is mapped to a section like a commit type.
+#[derive(Debug, Clone, Args)]
+pub struct ImportFragments {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Directory of the fragments.
+    #[arg(
+        long,
+        alias = "fragments-dir",
+        default_value = "newsfragments",
+        value_hint = ValueHint::DirPath
+    )]
+    pub dir: PathBuf,
+    /// Path to the commit type to changelog section map,
+    /// or "keep-a-changelog" for the Keep a Changelog sections.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub map: Option<PathBuf>,
+    /// Keep the fragment files. They are deleted once imported by default,
+    /// unless printed on the standard output.
+    #[arg(long)]
+    pub keep: bool,
+    /// Git provider of the repo, to link the fragments named after a PR number, like "123.feature".
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+/// Convert a changelog between formats. Printed on the standard output unless --output is used.
+/// Example: changen convert CHANGELOG.md --to json
+#[derive(Debug, Clone, Args)]
+pub struct Convert {
+    /// Path to the changelog file. "-" means the standard input.
+    #[arg(default_value = "CHANGELOG.md", value_hint = ValueHint::FilePath)]
+    pub input: PathBuf,
+    /// Format of the input. Inferred from its extension by default.
+    #[arg(long)]
+    pub from: Option<DocumentFormat>,
+    /// Format of the output. Inferred from the extension of --output by default.
+    #[arg(long)]
+    pub to: Option<DocumentFormat>,
+}
+
+/// Formats of a whole changelog document.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    /// Keep a Changelog markdown
+    Markdown,
+    /// The model of the changelog, as JSON
+    Json,
+}
+
+impl Display for DocumentFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentFormat::Markdown => write!(f, "markdown"),
+            DocumentFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Parse "SECTION=HEADING".
+fn parse_section_heading(s: &str) -> anyhow::Result<(String, String)> {
+    match s.split_once('=') {
+        Some((section, heading)) if !section.trim().is_empty() && !heading.trim().is_empty() => {
+            Ok((section.trim().to_owned(), heading.trim().to_owned()))
+        }
+        _ => anyhow::bail!("expected SECTION=HEADING, like \"Added=✨ Features\""),
+    }
+}
+
+/// Accept a "v" prefix, as in tag names.
+fn parse_version(s: &str) -> anyhow::Result<Version> {
+    Version::from_str(s.strip_prefix('v').unwrap_or(s))
+}
+
+/// Create the Github release of a tag, with the notes of the release as body.
+/// Update the body if the release already exists.
+#[derive(Debug, Clone, Args)]
+pub struct Publish {
+    /// Path to the changelog file.
+    #[arg(
+        short,
+        long,
+        default_value = "CHANGELOG.md",
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Version of the release. Default to the latest release.
+    #[arg(short, long)]
+    pub version: Option<Version>,
+    /// Tag of the release. Default to the version with --tag-prefix.
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// Prefix of the git tags, added to the version to get the tag of the release.
+    #[arg(long, default_value_t)]
+    pub tag_prefix: String,
+    /// Publish the release as a draft.
+    #[arg(long)]
+    pub draft: bool,
+    /// Mark the release as a prerelease.
+    #[arg(long)]
+    pub prerelease: bool,
+    /// Fail if the release already exists, instead of updating it.
+    #[arg(long)]
+    pub no_overwrite: bool,
+    /// Git provider of the repo.
+    #[arg(long, default_value_t)]
+    pub provider: GitProvider,
+    /// Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
+    #[arg(long)]
+    pub repo: Option<String>,
+}
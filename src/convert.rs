@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use changelog::{
+    de::parse_changelog,
+    ser::{serialize_changelog, Options},
+    ChangeLog,
+};
+use clap::ValueEnum;
+
+use crate::{config::DocumentFormat, report::to_json};
+
+impl DocumentFormat {
+    /// Format of a file, from its extension.
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+
+        match extension.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(DocumentFormat::Markdown),
+            "json" => Ok(DocumentFormat::Json),
+            _ => bail!(
+                "Can't infer the format of {} from its extension. Supported formats: {}",
+                path.display(),
+                supported_formats()
+            ),
+        }
+    }
+}
+
+fn supported_formats() -> String {
+    DocumentFormat::value_variants()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parse `input` as `from`, and serialize it as `to`.
+/// Markdown is serialized with the default options, so markdown -> json -> markdown gives
+/// the formatted markdown back.
+pub fn convert(input: &str, from: DocumentFormat, to: DocumentFormat) -> anyhow::Result<String> {
+    let changelog: ChangeLog = match from {
+        DocumentFormat::Markdown => parse_changelog(input)?,
+        DocumentFormat::Json => {
+            serde_json::from_str(input).context("The input is not a JSON changelog")?
+        }
+    };
+
+    let output = match to {
+        DocumentFormat::Markdown => serialize_changelog(&changelog, &Options::default()),
+        DocumentFormat::Json => to_json(&changelog)?,
+    };
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const MARKDOWN: &str = include_str!("../tests/convert/CHANGELOG.md");
+    const JSON: &str = include_str!("../tests/convert/CHANGELOG.json");
+
+    #[test]
+    fn markdown_json_markdown() {
+        let json = convert(MARKDOWN, DocumentFormat::Markdown, DocumentFormat::Json).unwrap();
+        assert_eq!(json, JSON);
+
+        let markdown = convert(&json, DocumentFormat::Json, DocumentFormat::Markdown).unwrap();
+        assert_eq!(markdown, MARKDOWN);
+    }
+
+    #[test]
+    fn json_markdown_json() {
+        let markdown = convert(JSON, DocumentFormat::Json, DocumentFormat::Markdown).unwrap();
+        let json = convert(&markdown, DocumentFormat::Markdown, DocumentFormat::Json).unwrap();
+
+        assert_eq!(json, JSON);
+    }
+
+    #[test]
+    fn json_without_yanked() {
+        let json = regex::Regex::new(r#",\s*"yanked": false"#)
+            .unwrap()
+            .replace_all(JSON, "");
+        assert!(!json.contains("\"yanked\": false"));
+
+        let markdown = convert(&json, DocumentFormat::Json, DocumentFormat::Markdown).unwrap();
+        assert_eq!(markdown, MARKDOWN);
+    }
+
+    #[test]
+    fn infer_format() {
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("CHANGELOG.md")).unwrap(),
+            DocumentFormat::Markdown
+        );
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("out/changelog.JSON")).unwrap(),
+            DocumentFormat::Json
+        );
+
+        let err = DocumentFormat::from_path(Path::new("CHANGELOG.yaml")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Can't infer the format of CHANGELOG.yaml from its extension. Supported formats: markdown, json"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt::Write;
+
+use anyhow::bail;
+use changelog::{utils::strip_links, ChangeLog, Release};
+
+use crate::{
+    config::ExportDebian,
+    utils::{parse_iso_date, rfc2822, wrap},
+};
+
+/// Width of the lines of a debian changelog.
+const WIDTH: usize = 80;
+/// Distribution of the Unreleased stanza, recognized by dch.
+const UNRELEASED_DISTRIBUTION: &str = "UNRELEASED";
+
+/// The releases of `changelog` as a `debian/changelog`, one stanza per release, the newest first.
+///
+/// The notes become `  * message` lines, wrapped at 80 columns, and their context `    - line` sub-items.
+/// Links are replaced by their text. Each release must have a `YYYY-MM-DD` date, written as an
+/// RFC 2822 date at midnight UTC. With `include_unreleased`, Unreleased is written first with the
+/// `UNRELEASED` distribution, dated `today`, a number of days since 1970-01-01.
+pub fn export_debian(
+    changelog: &ChangeLog,
+    options: &ExportDebian,
+    today: i64,
+) -> anyhow::Result<String> {
+    if !is_package_name(&options.package) {
+        bail!(
+            "\"{}\" is not a debian package name: lowercase letters, digits, '+', '-' and '.', starting with a letter or a digit",
+            options.package
+        );
+    }
+
+    let maintainer = resolve_maintainer(
+        options.maintainer.as_deref(),
+        std::env::var("DEBFULLNAME").ok(),
+        std::env::var("DEBEMAIL").ok(),
+    )?;
+
+    if !(maintainer.contains('<') && maintainer.ends_with('>')) {
+        bail!("The maintainer must be like \"Name <email>\", not \"{maintainer}\"");
+    }
+
+    let mut stanzas = Vec::new();
+
+    if options.include_unreleased {
+        if let Some(unreleased) = &changelog.unreleased {
+            let version = match changelog.last_version() {
+                Some(version) => format!("{version}+unreleased"),
+                None => "0.0.0+unreleased".into(),
+            };
+
+            stanzas.push(stanza(
+                unreleased,
+                &version,
+                UNRELEASED_DISTRIBUTION,
+                &maintainer,
+                options,
+                today,
+            ));
+        }
+    }
+
+    for release in changelog.releases() {
+        let version = release.version();
+
+        let Some(days) = release.title.title.as_deref().and_then(parse_iso_date) else {
+            bail!("The release {version} has no YYYY-MM-DD date");
+        };
+
+        let version = match &options.revision {
+            Some(revision) => format!("{version}-{revision}"),
+            None => version.to_owned(),
+        };
+
+        stanzas.push(stanza(
+            release,
+            &version,
+            &options.distribution,
+            &maintainer,
+            options,
+            days,
+        ));
+    }
+
+    Ok(stanzas.join("\n"))
+}
+
+fn stanza(
+    release: &Release,
+    version: &str,
+    distribution: &str,
+    maintainer: &str,
+    options: &ExportDebian,
+    days: i64,
+) -> String {
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        "{} ({version}) {distribution}; urgency={}\n",
+        options.package, options.urgency
+    )
+    .unwrap();
+
+    let notes = release
+        .note_sections
+        .values()
+        .flat_map(|section| &section.notes)
+        .collect::<Vec<_>>();
+
+    if notes.is_empty() {
+        writeln!(output, "  * New upstream release.").unwrap();
+    }
+
+    for note in notes {
+        let message = match &note.scope {
+            Some(scope) => format!("{scope}: {}", note.plain_message()),
+            None => note.plain_message(),
+        };
+
+        for line in wrap(&message, WIDTH, "  * ", "    ") {
+            writeln!(output, "{line}").unwrap();
+        }
+
+        for context in &note.context {
+            for line in wrap(&strip_links(context), WIDTH, "    - ", "      ") {
+                writeln!(output, "{line}").unwrap();
+            }
+        }
+    }
+
+    writeln!(output, "\n -- {maintainer}  {}", rfc2822(days)).unwrap();
+
+    output
+}
+
+/// `maintainer`, or "$DEBFULLNAME <$DEBEMAIL>" from `name` and `email` when it is not given.
+fn resolve_maintainer(
+    maintainer: Option<&str>,
+    name: Option<String>,
+    email: Option<String>,
+) -> anyhow::Result<String> {
+    match (maintainer, name, email) {
+        (Some(maintainer), _, _) => Ok(maintainer.trim().to_owned()),
+        (None, Some(name), Some(email)) => Ok(format!("{} <{}>", name.trim(), email.trim())),
+        (None, _, _) => {
+            bail!("No maintainer: use --maintainer, or set both $DEBFULLNAME and $DEBEMAIL")
+        }
+    }
+}
+
+fn is_package_name(name: &str) -> bool {
+    name.len() >= 2
+        && name.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "+-.".contains(c))
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use crate::utils::days_from_civil;
+
+    use super::*;
+
+    fn options() -> ExportDebian {
+        ExportDebian {
+            file: None,
+            package: "fan-control".into(),
+            distribution: "unstable".into(),
+            urgency: "medium".into(),
+            maintainer: Some("Alice Doe <alice@example.com>".into()),
+            revision: Some("1".into()),
+            include_unreleased: false,
+        }
+    }
+
+    #[test]
+    fn golden() {
+        let changelog = parse_changelog(include_str!("../tests/debian/CHANGELOG.md")).unwrap();
+
+        assert_eq!(
+            export_debian(&changelog, &options(), 0).unwrap(),
+            include_str!("../tests/debian/changelog")
+        );
+    }
+
+    #[test]
+    fn unreleased() {
+        let changelog = parse_changelog(include_str!("../tests/debian/CHANGELOG.md")).unwrap();
+
+        let mut options = options();
+        options.include_unreleased = true;
+
+        let output = export_debian(&changelog, &options, days_from_civil(2024, 4, 2)).unwrap();
+
+        assert!(
+            output.starts_with(
+                "fan-control (0.2.0+unreleased) UNRELEASED; urgency=medium
+
+  * not released yet
+
+ -- Alice Doe <alice@example.com>  Tue, 02 Apr 2024 00:00:00 +0000
+
+fan-control (0.2.0-1) unstable; urgency=medium
+"
+            ),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let changelog = parse_changelog("## [1.0.0]\n\n### Added\n\n- a\n").unwrap();
+
+        let err = export_debian(&changelog, &options(), 0).unwrap_err();
+        assert_eq!(err.to_string(), "The release 1.0.0 has no YYYY-MM-DD date");
+
+        let mut bad_maintainer = options();
+        bad_maintainer.maintainer = Some("alice".into());
+        export_debian(&changelog, &bad_maintainer, 0).unwrap_err();
+
+        let mut bad_package = options();
+        bad_package.package = "Fan_Control".into();
+        export_debian(&changelog, &bad_package, 0).unwrap_err();
+    }
+
+    #[test]
+    fn maintainer_from_env() {
+        assert_eq!(
+            resolve_maintainer(
+                None,
+                Some("Alice Doe".into()),
+                Some("alice@example.com".into())
+            )
+            .unwrap(),
+            "Alice Doe <alice@example.com>"
+        );
+        assert_eq!(
+            resolve_maintainer(Some(" Bob <bob@example.com> "), None, None).unwrap(),
+            "Bob <bob@example.com>"
+        );
+
+        let err = resolve_maintainer(None, Some("Alice Doe".into()), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No maintainer: use --maintainer, or set both $DEBFULLNAME and $DEBEMAIL"
+        );
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(rfc2822(0), "Thu, 01 Jan 1970 00:00:00 +0000");
+        assert_eq!(
+            rfc2822(parse_iso_date("2024-03-01").unwrap()),
+            "Fri, 01 Mar 2024 00:00:00 +0000"
+        );
+        assert_eq!(parse_iso_date("01/03/2024"), None);
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt::Write;
+
+use anyhow::bail;
+use changelog::{utils::UNRELEASED, ChangeLog, Release, ReleaseSectionNote, Version};
+use serde::{Deserialize, Serialize};
+
+use crate::merge::note_key;
+
+/// Structural difference between two changelogs. Sections and notes are compared by content,
+/// so reordering them is not a change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogDiff {
+    pub releases_added: Vec<String>,
+    pub releases_removed: Vec<String>,
+    pub releases_modified: Vec<ReleaseDiff>,
+}
+
+/// Difference between two releases. Notes are matched by scope and message, without their
+/// PR link and acknowledgement suffix: a note whose suffix changed is modified, not removed and added.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseDiff {
+    pub version: String,
+    /// Sections with notes in the new release only.
+    pub sections_added: Vec<String>,
+    /// Sections with notes in the old release only.
+    pub sections_removed: Vec<String>,
+    pub sections: Vec<SectionDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionDiff {
+    pub title: String,
+    pub added: Vec<ReleaseSectionNote>,
+    pub removed: Vec<ReleaseSectionNote>,
+    pub modified: Vec<ModifiedNote>,
+}
+
+/// A note with the same scope and message, but a different PR link, acknowledgement or context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModifiedNote {
+    pub old: ReleaseSectionNote,
+    pub new: ReleaseSectionNote,
+}
+
+/// Compare the releases of `old` and `new`, Unreleased included.
+pub fn diff_changelogs(old: &ChangeLog, new: &ChangeLog) -> ChangelogDiff {
+    let old_releases = releases(old);
+    let new_releases = releases(new);
+
+    let mut diff = ChangelogDiff::default();
+
+    for (version, new_release) in &new_releases {
+        match old_releases.iter().find(|(v, _)| v == version) {
+            Some((_, old_release)) => {
+                let release_diff = diff_releases(old_release, new_release);
+
+                if !release_diff.is_empty() {
+                    diff.releases_modified.push(release_diff);
+                }
+            }
+            None => diff.releases_added.push(version.clone()),
+        }
+    }
+
+    for (version, _) in &old_releases {
+        if new_releases.iter().all(|(v, _)| v != version) {
+            diff.releases_removed.push(version.clone());
+        }
+    }
+
+    diff
+}
+
+/// Compare two releases of the same changelog. The notes added are the ones of `new`.
+pub fn diff_versions(
+    changelog: &ChangeLog,
+    old: &Version,
+    new: &Version,
+) -> anyhow::Result<ChangelogDiff> {
+    let release = |version: &Version| match changelog.releases.get(version) {
+        Some(release) => Ok(release),
+        None => bail!("Release {version} not found"),
+    };
+
+    let release_diff = diff_releases(release(old)?, release(new)?);
+
+    let mut diff = ChangelogDiff::default();
+
+    if !release_diff.is_empty() {
+        diff.releases_modified.push(release_diff);
+    }
+
+    Ok(diff)
+}
+
+/// Compare the sections and notes of two releases. The version is the one of `new`.
+pub fn diff_releases(old: &Release, new: &Release) -> ReleaseDiff {
+    let has_notes = |release: &Release, title: &String| {
+        release
+            .note_sections
+            .get(title)
+            .is_some_and(|section| !section.notes.is_empty())
+    };
+
+    let with_notes = |release: &Release| {
+        release
+            .note_sections
+            .values()
+            .filter(|section| !section.notes.is_empty())
+            .map(|section| section.title.clone())
+            .collect::<Vec<_>>()
+    };
+
+    ReleaseDiff {
+        version: new.version().to_owned(),
+        sections_added: with_notes(new)
+            .into_iter()
+            .filter(|title| !has_notes(old, title))
+            .collect(),
+        sections_removed: with_notes(old)
+            .into_iter()
+            .filter(|title| !has_notes(new, title))
+            .collect(),
+        sections: diff_sections(old, new),
+    }
+}
+
+fn releases(changelog: &ChangeLog) -> Vec<(String, &Release)> {
+    changelog
+        .unreleased
+        .iter()
+        .map(|unreleased| (UNRELEASED.to_owned(), unreleased))
+        .chain(
+            changelog
+                .releases
+                .iter()
+                .map(|(version, release)| (version.to_string(), release)),
+        )
+        .collect()
+}
+
+fn diff_sections(old: &Release, new: &Release) -> Vec<SectionDiff> {
+    let no_notes = Vec::new();
+
+    let mut titles = new.note_sections.keys().collect::<Vec<_>>();
+    for title in old.note_sections.keys() {
+        if !titles.contains(&title) {
+            titles.push(title);
+        }
+    }
+
+    let mut sections = Vec::new();
+
+    for title in titles {
+        let old_notes = old.note_sections.get(title).map_or(&no_notes, |s| &s.notes);
+        let new_notes = new.note_sections.get(title).map_or(&no_notes, |s| &s.notes);
+
+        let mut section = SectionDiff {
+            title: title.clone(),
+            added: Vec::new(),
+            removed: Vec::new(),
+            modified: Vec::new(),
+        };
+
+        for note in new_notes {
+            if old_notes.contains(note) {
+                continue;
+            }
+
+            match old_notes.iter().find(|n| note_key(n) == note_key(note)) {
+                Some(old_note) => section.modified.push(ModifiedNote {
+                    old: old_note.clone(),
+                    new: note.clone(),
+                }),
+                None => section.added.push(note.clone()),
+            }
+        }
+
+        for note in old_notes {
+            if !new_notes.contains(note) && new_notes.iter().all(|n| note_key(n) != note_key(note))
+            {
+                section.removed.push(note.clone());
+            }
+        }
+
+        if !(section.added.is_empty() && section.removed.is_empty() && section.modified.is_empty())
+        {
+            sections.push(section);
+        }
+    }
+
+    sections
+}
+
+impl ReleaseDiff {
+    pub fn is_empty(&self) -> bool {
+        self.sections_added.is_empty()
+            && self.sections_removed.is_empty()
+            && self.sections.is_empty()
+    }
+}
+
+impl ChangelogDiff {
+    pub fn is_empty(&self) -> bool {
+        self.releases_added.is_empty()
+            && self.releases_removed.is_empty()
+            && self.releases_modified.is_empty()
+    }
+
+    /// Human readable summary.
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "No difference.\n".into();
+        }
+
+        let mut output = String::new();
+
+        for version in &self.releases_added {
+            writeln!(output, "+ {version}").unwrap();
+        }
+
+        for version in &self.releases_removed {
+            writeln!(output, "- {version}").unwrap();
+        }
+
+        for release in &self.releases_modified {
+            writeln!(output, "~ {}", release.version).unwrap();
+
+            for section in &release.sections {
+                writeln!(output, "  {}", section.title).unwrap();
+
+                for note in &section.added {
+                    writeln!(output, "    + {}", note_text(note)).unwrap();
+                }
+                for note in &section.removed {
+                    writeln!(output, "    - {}", note_text(note)).unwrap();
+                }
+                for note in &section.modified {
+                    writeln!(output, "    ~ {}", note_text(&note.new)).unwrap();
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn note_text(note: &ReleaseSectionNote) -> String {
+    match &note.scope {
+        Some(scope) => format!("{scope}: {}", note.message),
+        None => note.message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const OLD: &str = include_str!("../tests/diff/old.md");
+    const NEW: &str = include_str!("../tests/diff/new.md");
+
+    #[test]
+    fn changelogs() {
+        let diff = diff_changelogs(
+            &parse_changelog(OLD).unwrap(),
+            &parse_changelog(NEW).unwrap(),
+        );
+
+        assert_eq!(diff.releases_added, Vec::<String>::new());
+        assert_eq!(diff.releases_removed, vec!["0.1.0".to_owned()]);
+        assert_eq!(
+            diff.summary(),
+            "- 0.1.0
+~ 1.0.0
+  Added
+    + new command
+"
+        );
+    }
+
+    #[test]
+    fn reordered() {
+        let old = parse_changelog(
+            "## [1.0.0]\n\n### Added\n\n- a\n- b\n\n### Fixed\n\n- c in [#1](https://github.com/a/b/pull/1)\n",
+        )
+        .unwrap();
+        let new = parse_changelog(
+            "## [1.0.0]\n\n### Fixed\n\n- c in [#2](https://github.com/a/b/pull/2)\n\n### Added\n\n- b\n- a\n",
+        )
+        .unwrap();
+
+        let diff = diff_changelogs(&old, &new);
+
+        assert_eq!(diff.releases_modified.len(), 1);
+        let sections = &diff.releases_modified[0].sections;
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].title, "Fixed");
+        assert_eq!(sections[0].modified.len(), 1);
+        assert!(sections[0].added.is_empty() && sections[0].removed.is_empty());
+    }
+
+    #[test]
+    fn releases() {
+        let changelog = parse_changelog(NEW).unwrap();
+
+        let diff = diff_versions(
+            &changelog,
+            &Version::from_str("0.2.0").unwrap(),
+            &Version::from_str("1.0.0").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            diff.summary(),
+            "~ 1.0.0
+  Added
+    + ui: new button
+    + new command
+  Fixed
+    - leak
+"
+        );
+
+        diff_versions(
+            &changelog,
+            &Version::from_str("0.2.0").unwrap(),
+            &Version::from_str("3.0.0").unwrap(),
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn sections_and_attribution() {
+        let changelog = parse_changelog(
+            "## [1.1.0]
+
+### Added
+
+- export in [#3](https://github.com/a/b/pull/3) by [@alice](https://github.com/alice)
+
+### Security
+
+- escape the input
+
+## [1.0.0]
+
+### Added
+
+- export in [#2](https://github.com/a/b/pull/2)
+
+### Fixed
+
+- crash
+",
+        )
+        .unwrap();
+
+        let diff = diff_releases(
+            &changelog.releases[&Version::from_str("1.0.0").unwrap()],
+            &changelog.releases[&Version::from_str("1.1.0").unwrap()],
+        );
+
+        assert_eq!(diff.version, "1.1.0");
+        assert_eq!(diff.sections_added, vec!["Security".to_owned()]);
+        assert_eq!(diff.sections_removed, vec!["Fixed".to_owned()]);
+
+        let added = diff.sections.iter().find(|s| s.title == "Added").unwrap();
+        assert!(added.added.is_empty() && added.removed.is_empty());
+        assert_eq!(added.modified.len(), 1);
+    }
+}
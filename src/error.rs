@@ -0,0 +1,101 @@
+use std::fmt::Display;
+
+/// Why a commit gives no note, or why the generation failed.
+/// The skip variants are logged per commit, the other ones abort the generation.
+#[derive(Debug)]
+pub enum GenerationError {
+    /// The commit asked to be skipped, with `pattern` in its title, or as a trailer.
+    Ignored { sha: String, pattern: String },
+    /// In strict parsing, a commit type which maps to no section.
+    UnknownType { sha: String, kind: String },
+    /// In strict parsing, a commit title which is not a conventional commit.
+    InvalidSyntax { sha: String, message: String },
+    /// With `exclude_unidentified`, a commit with no section. `kind` is its commit type, if any.
+    Unidentified { sha: String, kind: Option<String> },
+    /// With the `error` scope policy, a scope which is not allowed.
+    ScopeNotAllowed {
+        sha: String,
+        section: String,
+        scope: String,
+    },
+    /// With `exclude_not_pr`, a commit not attached to a pull request.
+    NotPr { sha: String, section: String },
+    /// With `require_pr`, the commits not attached to a pull request, as sha and title.
+    MissingPrs { commits: Vec<(String, String)> },
+    /// With `strict_sections`, a note which would create a section not expected.
+    UnexpectedSection { section: String, message: String },
+    /// The git provider couldn't answer.
+    Provider(anyhow::Error),
+    /// The section map is invalid.
+    Config(anyhow::Error),
+}
+
+impl GenerationError {
+    /// Whether only this commit is skipped, and the generation goes on.
+    pub fn is_skip(&self) -> bool {
+        !matches!(
+            self,
+            GenerationError::MissingPrs { .. }
+                | GenerationError::UnexpectedSection { .. }
+                | GenerationError::Provider(_)
+                | GenerationError::Config(_)
+        )
+    }
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+impl Display for GenerationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerationError::Ignored { pattern, .. } => {
+                write!(f, "Ignoring commit. \"{pattern}\" was matched.")
+            }
+            GenerationError::UnknownType { kind, .. } => {
+                write!(f, "no corresponding commit type was found for {kind}")
+            }
+            GenerationError::InvalidSyntax { sha, message } => {
+                write!(f, "Commit {}: invalid syntax: {message}", short(sha))
+            }
+            GenerationError::Unidentified {
+                kind: Some(kind), ..
+            } => {
+                write!(f, "No corresponding commit type was found for {kind}")
+            }
+            GenerationError::Unidentified { kind: None, .. } => write!(f, "Not identified."),
+            GenerationError::ScopeNotAllowed { scope, .. } => {
+                write!(f, "The scope {scope} is not allowed")
+            }
+            GenerationError::NotPr { .. } => write!(f, "No upstream pr was found"),
+            GenerationError::MissingPrs { commits } => {
+                write!(
+                    f,
+                    "{} commit(s) not attached to a pull request:",
+                    commits.len()
+                )?;
+
+                for (sha, title) in commits {
+                    write!(f, "\n- {} {title}", short(sha))?;
+                }
+
+                Ok(())
+            }
+            GenerationError::UnexpectedSection { section, message } => write!(
+                f,
+                "The note \"{message}\" would create the section {section}, which is not in the map. Use --allowed-sections to allow it"
+            ),
+            GenerationError::Provider(e) | GenerationError::Config(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GenerationError::Provider(e) | GenerationError::Config(e) => e.source(),
+            _ => None,
+        }
+    }
+}
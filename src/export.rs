@@ -0,0 +1,220 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use anyhow::bail;
+use changelog::{
+    ser::{serialize_release, OptionsRelease},
+    ChangeLog,
+};
+use regex::{Captures, Regex};
+
+use crate::{config::Export, git_provider::DiffTags, utils::TextInterpolate};
+
+/// Notes of a release, standalone. Used as the body of a Github release.
+pub fn export(changelog: &ChangeLog, options: &Export) -> anyhow::Result<String> {
+    let (version, release) = match &options.version {
+        Some(version) => match changelog.releases.get_key_value(version) {
+            Some(release) => release,
+            None => bail!("Release {version} not found"),
+        },
+        None => match changelog.releases_full().next() {
+            Some(release) => release,
+            None => bail!("No release found"),
+        },
+    };
+
+    if release
+        .note_sections
+        .values()
+        .all(|section| section.notes.is_empty())
+    {
+        bail!("Release {version} has no notes");
+    }
+
+    let mut notes = String::new();
+
+    serialize_release(
+        &mut notes,
+        release,
+        &OptionsRelease {
+            serialize_title: false,
+            ..Default::default()
+        },
+    );
+
+    let mut notes = inline_reference_links(&notes, changelog);
+
+    if options.full_changelog && !notes.contains("Full Changelog:") {
+        let Some(repo) = &options.repo else {
+            bail!("The repo is needed for the Full Changelog link");
+        };
+
+        let prev = changelog
+            .releases_keys()
+            .skip_while(|v| *v != version)
+            .nth(1)
+            .cloned();
+
+        let link = options.provider.diff_link(
+            repo,
+            &DiffTags::new(version.clone(), prev)?.with_tag_prefix(&options.tag_prefix),
+        )?;
+
+        notes.push_str(&format!("\nFull Changelog: {link}\n"));
+    }
+
+    let template = |template: &str| {
+        let mut text = TextInterpolate::new(template.to_owned(), "{", "}");
+        text.interpolate("version", &version.to_string());
+        text.text()
+    };
+
+    if let Some(header) = &options.header {
+        notes = format!("{}\n\n{notes}", template(header));
+    }
+
+    if let Some(footer) = &options.footer {
+        notes = format!("{notes}\n{}\n", template(footer));
+    }
+
+    Ok(notes)
+}
+
+/// Replace reference links, like `[text][1.0.0]` or `[1.0.0]`, by inline links,
+/// using the footer links of the changelog.
+fn inline_reference_links(text: &str, changelog: &ChangeLog) -> String {
+    static REFERENCE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\[([^\]\n]+)\](?:\[([^\]\n]*)\])?").unwrap());
+
+    let links = changelog
+        .footer_links
+        .links
+        .iter()
+        .map(|link| (link.text.to_lowercase(), link.link.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    REFERENCE
+        .replace_all(text, |caps: &Captures| {
+            let full = caps.get(0).unwrap();
+
+            // already an inline link
+            if text[full.end()..].starts_with('(') {
+                return full.as_str().to_owned();
+            }
+
+            let label = &caps[1];
+            let reference = match caps.get(2).map(|m| m.as_str()) {
+                Some("") | None => label,
+                Some(reference) => reference,
+            };
+
+            match links.get(&reference.to_lowercase()) {
+                Some(link) => format!("[{label}]({link})"),
+                None => full.as_str().to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use changelog::{de::parse_changelog, Version};
+    use pretty_assertions::assert_eq;
+
+    use crate::git_provider::GitProvider;
+
+    use super::*;
+
+    const CHANGELOG: &str = "## [Unreleased]
+
+## [1.4.0] - 2024-03-01
+
+### Added
+
+- new button, see [the docs][docs]
+- [#12] fixed in [#13](https://github.com/a/b/pull/13)
+
+## [1.3.0]
+
+## [1.2.0]
+
+### Fixed
+
+- crash
+
+[1.4.0]: https://github.com/a/b/compare/1.3.0...1.4.0
+[docs]: https://docs.rs/b
+[#12]: https://github.com/a/b/issues/12
+";
+
+    fn options() -> Export {
+        Export {
+            file: None,
+            version: None,
+            full_changelog: false,
+            provider: GitProvider::Github,
+            repo: None,
+            header: None,
+            footer: None,
+            tag_prefix: String::new(),
+        }
+    }
+
+    #[test]
+    fn latest() {
+        let changelog = parse_changelog(CHANGELOG).unwrap();
+
+        assert_eq!(
+            export(&changelog, &options()).unwrap(),
+            "### Added
+
+- new button, see [the docs](https://docs.rs/b)
+- [#12](https://github.com/a/b/issues/12) fixed in [#13](https://github.com/a/b/pull/13)
+"
+        );
+    }
+
+    #[test]
+    fn templates_and_full_changelog() {
+        let changelog = parse_changelog(CHANGELOG).unwrap();
+
+        let options = Export {
+            version: Some(Version::from_str("1.2.0").unwrap()),
+            full_changelog: true,
+            repo: Some("a/b".into()),
+            header: Some("# Release {version}".into()),
+            footer: Some("Thanks to all contributors!".into()),
+            ..options()
+        };
+
+        assert_eq!(
+            export(&changelog, &options).unwrap(),
+            "# Release 1.2.0
+
+### Fixed
+
+- crash
+
+Full Changelog: https://github.com/a/b/tree/1.2.0
+
+Thanks to all contributors!
+"
+        );
+    }
+
+    #[test]
+    fn no_notes() {
+        let changelog = parse_changelog(CHANGELOG).unwrap();
+
+        let mut options = options();
+
+        options.version = Some(Version::from_str("1.3.0").unwrap());
+        let e = export(&changelog, &options).unwrap_err();
+        assert_eq!(e.to_string(), "Release 1.3.0 has no notes");
+
+        options.version = Some(Version::from_str("2.0.0").unwrap());
+        let e = export(&changelog, &options).unwrap_err();
+        assert_eq!(e.to_string(), "Release 2.0.0 not found");
+    }
+}
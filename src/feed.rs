@@ -0,0 +1,307 @@
+use std::{fmt::Write, sync::LazyLock};
+
+use anyhow::bail;
+use changelog::{ChangeLog, Release, ReleaseSectionNote};
+use regex::Regex;
+
+use crate::{
+    config::{ExportFeed, FeedFormat},
+    utils::{civil_from_days, escape_xml, parse_iso_date, rfc2822},
+};
+
+/// A versioned release, with its date in days since 1970-01-01.
+struct Entry<'a> {
+    release: &'a Release,
+    days: i64,
+}
+
+/// The releases of `changelog` as an Atom or a RSS 2.0 feed, one entry per versioned release,
+/// the newest first.
+///
+/// The id of an entry is the base URL followed by `#` and the version, and its content
+/// is the HTML of the release. A release without a `YYYY-MM-DD` date is dated with
+/// `fallback_date`, or skipped. The feed is updated at the newest release, or `today`,
+/// a number of days since 1970-01-01, without releases.
+pub fn render_feed(
+    changelog: &ChangeLog,
+    options: &ExportFeed,
+    today: i64,
+) -> anyhow::Result<String> {
+    let fallback = match &options.fallback_date {
+        Some(date) => match parse_iso_date(date) {
+            Some(days) => Some(days),
+            None => bail!("The fallback date \"{date}\" is not a YYYY-MM-DD date"),
+        },
+        None => None,
+    };
+
+    let mut entries = Vec::new();
+
+    for release in changelog.releases() {
+        let date = release.title.title.as_deref().and_then(parse_iso_date);
+
+        match date.or(fallback) {
+            Some(days) => entries.push(Entry { release, days }),
+            None => warn!(
+                "The release {} has no YYYY-MM-DD date, skipped",
+                release.version()
+            ),
+        }
+    }
+
+    let updated = entries.iter().map(|e| e.days).max().unwrap_or(today);
+
+    let feed = match options.output_format {
+        FeedFormat::Atom => atom(&entries, options, updated),
+        FeedFormat::Rss => rss(&entries, options, updated),
+    };
+
+    Ok(feed)
+}
+
+fn atom(entries: &[Entry], options: &ExportFeed, updated: i64) -> String {
+    let base_url = options.base_url.trim_end_matches('/');
+    let title = escape_xml(&options.title);
+
+    let mut output = String::new();
+
+    writeln!(output, "<?xml version=\"1.0\" encoding=\"utf-8\"?>").unwrap();
+    writeln!(output, "<feed xmlns=\"http://www.w3.org/2005/Atom\">").unwrap();
+    writeln!(output, "  <title>{title}</title>").unwrap();
+    writeln!(output, "  <id>{}</id>", escape_xml(base_url)).unwrap();
+    writeln!(output, "  <link href=\"{}\"/>", escape_xml(base_url)).unwrap();
+    writeln!(output, "  <updated>{}</updated>", rfc3339(updated)).unwrap();
+    writeln!(output, "  <author>").unwrap();
+    writeln!(output, "    <name>{title}</name>").unwrap();
+    writeln!(output, "  </author>").unwrap();
+
+    for entry in entries {
+        let version = entry.release.version();
+        let url = escape_xml(&format!("{base_url}#{version}"));
+
+        writeln!(output, "  <entry>").unwrap();
+        writeln!(output, "    <title>v{}</title>", escape_xml(version)).unwrap();
+        writeln!(output, "    <id>{url}</id>").unwrap();
+        writeln!(output, "    <link href=\"{url}\"/>").unwrap();
+        writeln!(output, "    <updated>{}</updated>", rfc3339(entry.days)).unwrap();
+        writeln!(
+            output,
+            "    <content type=\"html\">{}</content>",
+            escape_xml(&release_html(entry.release))
+        )
+        .unwrap();
+        writeln!(output, "  </entry>").unwrap();
+    }
+
+    writeln!(output, "</feed>").unwrap();
+
+    output
+}
+
+fn rss(entries: &[Entry], options: &ExportFeed, updated: i64) -> String {
+    let base_url = options.base_url.trim_end_matches('/');
+    let title = escape_xml(&options.title);
+
+    let mut output = String::new();
+
+    writeln!(output, "<?xml version=\"1.0\" encoding=\"utf-8\"?>").unwrap();
+    writeln!(output, "<rss version=\"2.0\">").unwrap();
+    writeln!(output, "  <channel>").unwrap();
+    writeln!(output, "    <title>{title}</title>").unwrap();
+    writeln!(output, "    <link>{}</link>", escape_xml(base_url)).unwrap();
+    writeln!(output, "    <description>Releases of {title}</description>").unwrap();
+    writeln!(
+        output,
+        "    <lastBuildDate>{}</lastBuildDate>",
+        rfc2822(updated)
+    )
+    .unwrap();
+
+    for entry in entries {
+        let version = entry.release.version();
+        let url = escape_xml(&format!("{base_url}#{version}"));
+
+        writeln!(output, "    <item>").unwrap();
+        writeln!(output, "      <title>v{}</title>", escape_xml(version)).unwrap();
+        writeln!(output, "      <link>{url}</link>").unwrap();
+        writeln!(output, "      <guid isPermaLink=\"true\">{url}</guid>").unwrap();
+        writeln!(output, "      <pubDate>{}</pubDate>", rfc2822(entry.days)).unwrap();
+        writeln!(
+            output,
+            "      <description>{}</description>",
+            escape_xml(&release_html(entry.release))
+        )
+        .unwrap();
+        writeln!(output, "    </item>").unwrap();
+    }
+
+    writeln!(output, "  </channel>").unwrap();
+    writeln!(output, "</rss>").unwrap();
+
+    output
+}
+
+/// Midnight UTC of a day, like "2024-03-01T00:00:00Z".
+fn rfc3339(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+
+    format!("{year:04}-{month:02}-{day:02}T00:00:00Z")
+}
+
+/// The sections of a release as HTML: a heading per section, followed by the list of its notes.
+fn release_html(release: &Release) -> String {
+    let mut html = String::new();
+
+    for section in release.note_sections.values() {
+        if section.notes.is_empty() && section.description.is_none() {
+            continue;
+        }
+
+        if !section.title.is_empty() {
+            write!(html, "<h3>{}</h3>", inline_html(&section.title)).unwrap();
+        }
+
+        if let Some(description) = &section.description {
+            write!(html, "<p>{}</p>", inline_html(description.trim())).unwrap();
+        }
+
+        if !section.notes.is_empty() {
+            html.push_str("<ul>");
+
+            for note in &section.notes {
+                write!(html, "<li>{}</li>", note_html(note)).unwrap();
+            }
+
+            html.push_str("</ul>");
+        }
+    }
+
+    html
+}
+
+fn note_html(note: &ReleaseSectionNote) -> String {
+    let mut html = match &note.scope {
+        Some(scope) => format!("<strong>{}</strong>: ", escape_xml(scope)),
+        None => String::new(),
+    };
+
+    html.push_str(&inline_html(&note.message));
+
+    for line in &note.context {
+        html.push_str("<br>");
+        html.push_str(&inline_html(line.trim()));
+    }
+
+    html
+}
+
+/// Escaped text, with the links and the code spans as HTML.
+fn inline_html(markdown: &str) -> String {
+    static INLINE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\[([^\]]*)\]\(([^)\s]+)\)|`([^`]+)`").unwrap());
+
+    let mut html = String::new();
+    let mut last = 0;
+
+    for captures in INLINE.captures_iter(markdown) {
+        let matched = captures.get(0).unwrap();
+
+        html.push_str(&escape_xml(&markdown[last..matched.start()]));
+
+        match (captures.get(1), captures.get(2), captures.get(3)) {
+            (Some(text), Some(url), _) => write!(
+                html,
+                "<a href=\"{}\">{}</a>",
+                escape_xml(url.as_str()),
+                escape_xml(text.as_str())
+            )
+            .unwrap(),
+            (_, _, Some(code)) => {
+                write!(html, "<code>{}</code>", escape_xml(code.as_str())).unwrap()
+            }
+            _ => unreachable!(),
+        }
+
+        last = matched.end();
+    }
+
+    html.push_str(&escape_xml(&markdown[last..]));
+
+    html
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CHANGELOG: &str = include_str!("../tests/feed/CHANGELOG.md");
+
+    fn options(format: FeedFormat) -> ExportFeed {
+        ExportFeed {
+            file: None,
+            output_format: format,
+            base_url: "https://example.com/changelog/".into(),
+            title: "fan-control".into(),
+            fallback_date: None,
+        }
+    }
+
+    #[test]
+    fn atom() {
+        let changelog = parse_changelog(CHANGELOG).unwrap();
+
+        assert_eq!(
+            render_feed(&changelog, &options(FeedFormat::Atom), 0).unwrap(),
+            include_str!("../tests/feed/atom.xml")
+        );
+    }
+
+    #[test]
+    fn rss() {
+        let changelog = parse_changelog(CHANGELOG).unwrap();
+
+        assert_eq!(
+            render_feed(&changelog, &options(FeedFormat::Rss), 0).unwrap(),
+            include_str!("../tests/feed/rss.xml")
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(
+            inline_html("<script> & [a \"link\"](https://a.b/?x=1&y=2) `<T>`"),
+            "&lt;script&gt; &amp; <a href=\"https://a.b/?x=1&amp;y=2\">a &quot;link&quot;</a> <code>&lt;T&gt;</code>"
+        );
+
+        let changelog =
+            parse_changelog("## [1.0.0] - 2024-01-01\n\n### Fixed\n\n- `a<b` & c\n").unwrap();
+        let feed = render_feed(&changelog, &options(FeedFormat::Atom), 0).unwrap();
+
+        // escaped twice: once as HTML, once as the text of the content element
+        assert!(feed.contains(
+            "<content type=\"html\">&lt;h3&gt;Fixed&lt;/h3&gt;&lt;ul&gt;&lt;li&gt;&lt;code&gt;a&amp;lt;b&lt;/code&gt; &amp;amp; c&lt;/li&gt;&lt;/ul&gt;</content>"
+        ));
+    }
+
+    #[test]
+    fn undated_releases() {
+        let changelog = parse_changelog("## [1.1.0]\n\n## [1.0.0] - 2024-01-01\n").unwrap();
+
+        let feed = render_feed(&changelog, &options(FeedFormat::Atom), 0).unwrap();
+        assert!(!feed.contains("<title>v1.1.0</title>"));
+        assert!(feed.contains("<updated>2024-01-01T00:00:00Z</updated>"));
+
+        let mut with_fallback = options(FeedFormat::Atom);
+        with_fallback.fallback_date = Some("2024-02-01".into());
+
+        let feed = render_feed(&changelog, &with_fallback, 0).unwrap();
+        assert!(feed.contains("<title>v1.1.0</title>"));
+        assert!(feed.contains("  <updated>2024-02-01T00:00:00Z</updated>\n  <author>"));
+
+        with_fallback.fallback_date = Some("soon".into());
+        render_feed(&changelog, &with_fallback, 0).unwrap_err();
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use changelog::{
+    de::{self, parse_changelog_with_options},
+    ser::OptionsRelease,
+};
+
+use crate::{config::MapMessageToSection, output::render_with};
+
+/// Parse and serialize the changelog again, sorted with the options of `map`.
+/// The sections of the versioned releases are only sorted if `reorder_releases` is set.
+/// The sections are read and written under `section_headings`.
+/// Formatting a formatted changelog doesn't change it.
+pub fn format(
+    input: &str,
+    map: MapMessageToSection,
+    reorder_releases: bool,
+    section_headings: &HashMap<String, String>,
+) -> anyhow::Result<String> {
+    let de_options = de::Options {
+        section_headings: section_headings.clone(),
+        ..Default::default()
+    };
+
+    let mut changelog = parse_changelog_with_options(input, &de_options)
+        .context("Refusing to format a changelog that can't be parsed")?;
+
+    let options = if reorder_releases {
+        map.to_fmt_options_all_releases()
+    } else {
+        map.to_fmt_options()
+    };
+
+    changelog.sanitize(&options);
+
+    let release_option = OptionsRelease {
+        section_headings: section_headings.clone(),
+        ..Default::default()
+    };
+
+    Ok(render_with(&changelog, &release_option))
+}
+
+/// Lines removed from `old` and added in `new`, prefixed by `-` and `+`.
+pub fn diff(old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // lcs[i][j]: longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut res = String::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            res.push_str(&format!("{:>4} - {}\n", i + 1, old[i]));
+            i += 1;
+        } else {
+            res.push_str(&format!("{:>4} + {}\n", j + 1, new[j]));
+            j += 1;
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn diff_lines() {
+        assert_eq!(diff("a\nb\nc\n", "a\nb\nc\n"), "");
+        assert_eq!(diff("a\nb\nc\n", "a\nd\nc\n"), "   2 - b\n   2 + d\n");
+    }
+}
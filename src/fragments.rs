@@ -0,0 +1,219 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use changelog::{ReleaseSection, ReleaseSectionNote};
+
+use crate::{config::MapMessageToSection, git_provider::GitProvider};
+
+/// Section of the fragments whose type maps to no section, like the unidentified commits.
+const FALLBACK_SECTION: &str = "Unidentified";
+
+/// A note read from a fragment file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewsFragment {
+    pub path: PathBuf,
+    pub section: String,
+    pub note: ReleaseSectionNote,
+}
+
+/// Read a towncrier fragment directory, like `newsfragments/`.
+/// Each file is a note: its type is the extension after the id (`123.feature`, `+orphan.bugfix.md`),
+/// mapped to a section with `map`, and its content is the message. The lines after the first one
+/// are the context of the note. A type which maps to no section goes to "Unidentified".
+/// When the id is a number and `repo` is given, the message ends with a link to the PR of this number.
+/// Fragments are read in the order of their file name.
+pub fn import_news_fragments(
+    dir: &Path,
+    map: &MapMessageToSection,
+    provider: &GitProvider,
+    repo: Option<&str>,
+) -> anyhow::Result<Vec<NewsFragment>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    paths.sort();
+
+    let mut fragments = Vec::new();
+
+    for path in paths {
+        if !path.is_file() {
+            continue;
+        }
+
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        let mut parts = file_name.split('.');
+        let id = parts.next().unwrap_or_default();
+
+        // .gitignore, .gitkeep, template.md, ...
+        let Some(kind) = parts.next().filter(|_| !file_name.starts_with('.')) else {
+            debug!("{} is not a fragment", path.display());
+            continue;
+        };
+
+        let section = match map.map_section(kind) {
+            Some(section) => section,
+            None => {
+                warn!(
+                    "No corresponding section was found for the fragment {}, added to {FALLBACK_SECTION}",
+                    path.display()
+                );
+                FALLBACK_SECTION.to_owned()
+            }
+        };
+
+        let content = fs::read_to_string(&path)?;
+        let mut lines = content
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim().is_empty());
+
+        let Some(message) = lines.next() else {
+            warn!("The fragment {} is empty, skipped", path.display());
+            continue;
+        };
+
+        let mut message = message.trim().to_owned();
+
+        let is_number = !id.is_empty() && id.chars().all(|c| c.is_ascii_digit());
+
+        if let Some(url) = repo
+            .filter(|_| is_number)
+            .and_then(|repo| provider.pr_link(repo, id))
+        {
+            // the link is part of the sentence, like the ones added by the generation
+            if message.ends_with('.') && !message.ends_with("..") {
+                message.pop();
+            }
+
+            message.push_str(&format!(" in [#{id}]({url})"));
+        }
+
+        let note = ReleaseSectionNote {
+            scope: None,
+            message,
+            context: lines.map(str::to_owned).collect(),
+            date: None,
+        };
+
+        fragments.push(NewsFragment {
+            path,
+            section,
+            note,
+        });
+    }
+
+    Ok(fragments)
+}
+
+/// Group the notes by section, in their order of appearance.
+pub fn into_sections(fragments: Vec<NewsFragment>) -> Vec<ReleaseSection> {
+    let mut sections: Vec<ReleaseSection> = Vec::new();
+
+    for NewsFragment {
+        section: title,
+        note,
+        ..
+    } in fragments
+    {
+        match sections.iter_mut().find(|section| section.title == title) {
+            Some(section) => section.notes.push(note),
+            None => sections.push(ReleaseSection {
+                title,
+                description: None,
+                notes: vec![note],
+            }),
+        }
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn fragments() {
+        let dir = std::env::temp_dir().join("changen_news_fragments");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(dir.join("12.feature"), "Add the stats command.\n").unwrap();
+        fs::write(
+            dir.join("13.bugfix.md"),
+            "Fix a crash on an empty body.\n\nOnly with --git-notes.\n",
+        )
+        .unwrap();
+        fs::write(dir.join("+orphan.doc"), "Document the trailers.\n").unwrap();
+        fs::write(dir.join("14.misc"), "").unwrap();
+        fs::write(dir.join(".gitignore"), "!.gitignore\n").unwrap();
+
+        fs::write(dir.join("15.perf"), "Faster startup.\n").unwrap();
+
+        let fragments = import_news_fragments(
+            &dir,
+            &MapMessageToSection::default(),
+            &GitProvider::Github,
+            Some("a/b"),
+        )
+        .unwrap();
+
+        let note = |message: &str, context: &[&str]| ReleaseSectionNote {
+            scope: None,
+            message: message.into(),
+            context: context.iter().map(|c| c.to_string()).collect(),
+            date: None,
+        };
+
+        assert_eq!(
+            fragments
+                .into_iter()
+                .map(|f| (f.section, f.note))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Documentation".into(), note("Document the trailers.", &[])),
+                (
+                    "Added".into(),
+                    note(
+                        "Add the stats command in [#12](https://github.com/a/b/pull/12)",
+                        &[]
+                    )
+                ),
+                (
+                    "Fixed".into(),
+                    note(
+                        "Fix a crash on an empty body in [#13](https://github.com/a/b/pull/13)",
+                        &["Only with --git-notes."]
+                    )
+                ),
+                (
+                    "Unidentified".into(),
+                    note(
+                        "Faster startup in [#15](https://github.com/a/b/pull/15)",
+                        &[]
+                    )
+                ),
+            ]
+        );
+
+        // without a provider, no link
+        let fragments = import_news_fragments(
+            &dir,
+            &MapMessageToSection::default(),
+            &GitProvider::None,
+            Some("a/b"),
+        )
+        .unwrap();
+        assert_eq!(fragments[1].note.message, "Add the stats command.");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
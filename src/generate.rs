@@ -1,23 +1,51 @@
 use crate::{
     commit_parser::{parse_commit, FormattedCommit},
     config::Generate,
-    git_provider::RelatedPr,
-    repository::{Period, RawCommit, Repository},
+    error::GenerationError,
+    git_provider::{is_rate_limited, RelatedPr},
+    merge::note_key,
+    repository::{tag_name, Period, RawCommit, Repository},
+    utils::{self, TextInterpolate},
+    validate_config::validate_config,
 };
-use anyhow::{bail, Result};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::LazyLock,
+};
+
+use anyhow::anyhow;
 use changelog::{
-    ser::{serialize_changelog, serialize_release_section_note},
-    ChangeLog, Release, ReleaseSection, ReleaseSectionNote,
+    utils::parse_note_attribution, ChangeLog, Release, ReleaseSection, ReleaseSectionNote,
 };
+use indexmap::IndexMap;
+use regex::Regex;
 
-use crate::config::{CommitMessageParsing, MapMessageToSection};
+use crate::config::{
+    BodyMode, CommitMessageParsing, FoundSection, MapMessageToSection, ScopePolicy,
+};
 
 pub fn generate<R: Repository>(
     r: &R,
     mut changelog: ChangeLog,
     options: &Generate,
-) -> Result<String> {
-    let map = MapMessageToSection::try_new(options.map.as_ref())?;
+) -> Result<ChangeLog, GenerationError> {
+    let map =
+        MapMessageToSection::try_new(options.map.as_ref()).map_err(GenerationError::Config)?;
+
+    let problems = validate_config(options, &map);
+
+    if !problems.is_empty() {
+        let problems = problems
+            .iter()
+            .map(|problem| format!("- {problem}"))
+            .collect::<Vec<_>>();
+
+        return Err(GenerationError::Config(anyhow!(
+            "Invalid configuration:\n{}",
+            problems.join("\n")
+        )));
+    }
 
     let changelog_cloned = changelog.clone();
 
@@ -25,11 +53,58 @@ pub fn generate<R: Repository>(
 
     gen_release_notes::<R>(r, &changelog_cloned, unreleased, &map, options)?;
 
+    if options.skip_released {
+        skip_released(unreleased, &changelog_cloned);
+    }
+
     changelog.sanitize(&map.to_fmt_options());
 
-    let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
+    Ok(changelog)
+}
+
+/// Remove the notes added to `unreleased` which are already in a versioned release of `before`,
+/// with the same PR, or the same scope and message.
+fn skip_released(unreleased: &mut Release, before: &ChangeLog) {
+    let released = before
+        .releases()
+        .flat_map(|release| release.note_sections.values())
+        .flat_map(|section| &section.notes)
+        .collect::<Vec<_>>();
+
+    let released_prs = released
+        .iter()
+        .filter_map(|note| parse_note_attribution(&note.message).pr)
+        .map(|pr| pr.id)
+        .collect::<HashSet<_>>();
+
+    let released_keys = released
+        .iter()
+        .map(|note| note_key(note))
+        .collect::<HashSet<_>>();
+
+    for section in unreleased.note_sections.values_mut() {
+        let previous = before
+            .unreleased
+            .as_ref()
+            .and_then(|unreleased| unreleased.note_sections.get(&section.title));
 
-    Ok(output)
+        section.notes.retain(|note| {
+            if previous.is_some_and(|previous| previous.notes.contains(note)) {
+                return true;
+            }
+
+            let is_released = parse_note_attribution(&note.message)
+                .pr
+                .is_some_and(|pr| released_prs.contains(&pr.id))
+                || released_keys.contains(&note_key(note));
+
+            if is_released {
+                debug!("note \"{}\": already in a release", note.message);
+            }
+
+            !is_released
+        });
+    }
 }
 
 fn gen_release_notes<R: Repository>(
@@ -38,7 +113,7 @@ fn gen_release_notes<R: Repository>(
     unreleased: &mut Release,
     map: &MapMessageToSection,
     options: &Generate,
-) -> Result<()> {
+) -> Result<(), GenerationError> {
     if let Some(specific) = &options.specific {
         return handle_specific::<R>(r, unreleased, map, options, specific);
     }
@@ -55,60 +130,150 @@ fn handle_milestone(
     map: &MapMessageToSection,
     options: &Generate,
     milestone: &str,
-) -> Result<()> {
-    for pr in options
+) -> Result<(), GenerationError> {
+    let repo = options.repo.clone().unwrap();
+
+    let mut repos = vec![repo.as_str()];
+    repos.extend(options.milestone_repos.iter().map(String::as_str));
+
+    for mut pr in options
         .provider
-        .milestone_prs(&options.repo.clone().unwrap(), milestone)?
+        .milestone_prs(&repos, milestone)
+        .map_err(GenerationError::Provider)?
     {
-        let raw_commit = RawCommit {
+        qualify_pr_id(&mut pr, &repo);
+
+        let mut raw_commit = RawCommit {
             title: pr.title.clone().unwrap_or_default(),
             body: pr.body.clone().unwrap_or_default(),
             sha: "".into(),
             list_files: vec![],
+            is_merge: false,
             author: pr.author.clone().unwrap_or_default(),
+            date: None,
         };
 
+        prefer_pr_changelog_section(&mut raw_commit, Some(&pr), options);
+
         match get_release_note(&raw_commit, Some(&pr), map, options) {
             Ok((section_title, release_note)) => {
-                insert_release_note(unreleased, section_title, release_note);
+                insert_release_note(unreleased, section_title, release_note, map, options)?;
             }
-            Err(e) => eprintln!("commit {}: {e}", raw_commit.short_commit()),
+            Err(e) => info!("commit {}: {e}", raw_commit.short_commit()),
         }
     }
 
     Ok(())
 }
 
+/// Whether `author` is listed in the internal authors. The `@` and the case are ignored.
+fn is_internal(author: &str, options: &Generate) -> bool {
+    options.internal_authors.iter().any(|internal| {
+        internal
+            .trim_start_matches('@')
+            .eq_ignore_ascii_case(author)
+    })
+}
+
+/// Prefix the id of a PR of another repo than `repo` with its repo, like 'owner/name#12'.
+fn qualify_pr_id(pr: &mut RelatedPr, repo: &str) {
+    if pr.is_pr && pr.repo != repo {
+        pr.pr_id = format!("{}{}", pr.repo, pr.pr_id);
+    }
+}
+
+/// With `prefer_pr_changelog_section`, replace the message of the commit by the changelog section
+/// of the body of its PR, when there is one.
+fn prefer_pr_changelog_section(
+    raw_commit: &mut RawCommit,
+    related_pr: Option<&RelatedPr>,
+    options: &Generate,
+) {
+    if !options.prefer_pr_changelog_section {
+        return;
+    }
+
+    let Some(pr) = related_pr.filter(|pr| pr.is_pr) else {
+        return;
+    };
+
+    if let Some(note) = pr
+        .body
+        .as_deref()
+        .and_then(|body| pr_changelog_section(body, &options.pr_changelog_heading))
+    {
+        debug!("pr {}: using the changelog section of its body", pr.pr_id);
+        raw_commit.body.clear();
+        raw_commit.apply_note(&note);
+    }
+}
+
+/// Content of the section titled `heading` in a PR body, without HTML comments.
+/// The section ends at the next heading of the same or a higher level.
+fn pr_changelog_section(body: &str, heading: &str) -> Option<String> {
+    static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+    let body = COMMENT.replace_all(body, "");
+
+    let heading_level = |line: &str| {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        (level > 0 && line[level..].starts_with(' ')).then_some(level)
+    };
+
+    let mut lines = body.lines();
+
+    let level = lines.find_map(|line| {
+        let line = line.trim();
+        heading_level(line).filter(|level| line[*level..].trim().eq_ignore_ascii_case(heading))
+    })?;
+
+    let section = lines
+        .take_while(|line| heading_level(line.trim()).is_none_or(|l| l > level))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let section = section.trim();
+
+    if section.is_empty() {
+        None
+    } else {
+        Some(section.to_owned())
+    }
+}
+
 fn handle_specific<R: Repository>(
     r: &R,
     unreleased: &mut Release,
     map: &MapMessageToSection,
     options: &Generate,
     specific: &str,
-) -> Result<()> {
-    let raw_commit = RawCommit::from_sha(r, specific);
+) -> Result<(), GenerationError> {
+    let raw_commit = raw_commit(r, specific, options);
 
     let related_pr = match &options.repo {
         Some(repo) => match options.provider.related_pr(repo, &raw_commit.sha) {
             Ok(related_pr) => Some(related_pr),
             Err(e) => {
-                eprintln!("error while requesting pr link: {}", e);
+                warn!("error while requesting pr link: {}", e);
                 None
             }
         },
         None => None,
     };
 
-    match get_release_note(&raw_commit, related_pr.as_ref(), map, options) {
-        Ok((section_title, release_note)) => {
-            let mut added = String::new();
-            serialize_release_section_note(&mut added, &release_note);
+    let mut raw_commit = raw_commit;
+    prefer_pr_title(&mut raw_commit, related_pr.as_ref(), options);
+    prefer_pr_changelog_section(&mut raw_commit, related_pr.as_ref(), options);
 
-            insert_release_note(unreleased, section_title.clone(), release_note);
+    if options.require_pr {
+        require_pr([(&raw_commit, related_pr.as_ref())])?;
+    }
 
-            eprintln!("Release note:\n{added}successfully added in the {section_title} section.")
+    match get_release_note(&raw_commit, related_pr.as_ref(), map, options) {
+        Ok((section_title, release_note)) => {
+            insert_release_note(unreleased, section_title, release_note, map, options)?;
         }
-        Err(e) => eprintln!("commit {}: {e}", raw_commit.short_commit()),
+        Err(e) => info!("commit {}: {e}", raw_commit.short_commit()),
     }
 
     Ok(())
@@ -120,37 +285,99 @@ fn handle_period<R: Repository>(
     unreleased: &mut Release,
     map: &MapMessageToSection,
     options: &Generate,
-) -> Result<()> {
-    let since = options
-        .since
-        .clone()
-        .or_else(|| changelog.last_version().map(|e| e.to_string()));
+) -> Result<(), GenerationError> {
+    let since = options.since.clone().or_else(|| {
+        changelog
+            .last_version()
+            .map(|e| tag_name(&options.tag_prefix, &e))
+    });
 
     let period = Period {
         since,
         until: options.until.clone(),
     };
 
-    info!("generate period: {:?}", period);
+    debug!("generate period: {:?}", period);
 
-    let commits = r.commits_between_tags(&period);
+    gen_release_notes_between(r, unreleased, map, options, &period)
+}
+
+/// Generate the release notes of all commits in `period` into `release`.
+pub fn gen_release_notes_between<R: Repository>(
+    r: &R,
+    release: &mut Release,
+    map: &MapMessageToSection,
+    options: &Generate,
+    period: &Period,
+) -> Result<(), GenerationError> {
+    let commits = r.commits_between_tags(period);
 
     let mut last_prs = match &options.repo {
-        Some(repo) => match options.provider.last_prs(repo, commits.len()) {
+        Some(repo) => match options.provider.last_prs(
+            repo,
+            &options
+                .provider
+                .resolve_default_branch(r, Some(repo), &options.default_branch),
+            commits.len(),
+        ) {
             Ok(last_prs) => Some(last_prs),
+            Err(e) if is_rate_limited(&e) => return Err(GenerationError::Provider(e)),
             Err(e) => {
-                eprintln!("error while requesting pr link: {}", e);
+                warn!("error while requesting pr link: {}", e);
                 None
             }
         },
         None => None,
     };
 
+    let mut raw_commits = Vec::new();
+
     for sha in commits {
-        let raw_commit = RawCommit::from_sha::<R>(r, &sha);
+        let raw_commit = raw_commit(r, &sha, options);
+
+        if options.merges_only && !raw_commit.is_merge {
+            debug!("commit {}: not a merge commit", raw_commit.short_commit());
+            continue;
+        }
+
+        if !touches_included_files(&raw_commit, options) {
+            debug!(
+                "commit {}: no file touched under the path, or all excluded",
+                raw_commit.short_commit()
+            );
+            continue;
+        }
+
+        raw_commits.push(raw_commit);
+    }
+
+    // commits of a PR are not its merge commit
+    if let (Some(repo), true) = (&options.repo, options.group_by_pr) {
+        let missing = raw_commits
+            .iter()
+            .filter(|c| {
+                !last_prs
+                    .as_ref()
+                    .is_some_and(|prs| prs.contains_key(&c.sha))
+            })
+            .map(|c| c.sha.clone())
+            .collect::<Vec<_>>();
 
+        let related_prs = options
+            .provider
+            .related_prs(repo, &missing, options.pr_concurrency)
+            .map_err(GenerationError::Provider)?;
+
+        last_prs
+            .get_or_insert_with(HashMap::new)
+            .extend(related_prs);
+    }
+
+    let mut sources = Vec::new();
+
+    for raw_commit in raw_commits {
         let related_pr = match last_prs {
-            Some(ref mut last_prs) => last_prs.remove(&sha),
+            Some(ref mut last_prs) => last_prs.remove(&raw_commit.sha),
             None => None,
         };
 
@@ -163,129 +390,652 @@ fn handle_period<R: Repository>(
             },
         };
 
+        let mut raw_commit = raw_commit;
+        prefer_pr_title(&mut raw_commit, related_pr.as_ref(), options);
+        prefer_pr_changelog_section(&mut raw_commit, related_pr.as_ref(), options);
+
+        sources.push((raw_commit, related_pr));
+    }
+
+    if options.require_pr {
+        require_pr(sources.iter().map(|(c, pr)| (c, pr.as_ref())))?;
+    }
+
+    if options.group_by_pr {
+        for (raw_commit, related_pr, commits) in group_by_pr(&mut sources) {
+            match get_release_note(&raw_commit, Some(&related_pr), map, options) {
+                Ok((section_title, mut release_note)) => {
+                    release_note.context = commits;
+                    insert_release_note(release, section_title, release_note, map, options)?;
+                }
+                Err(e) => info!("pr {}: {e}", related_pr.pr_id),
+            }
+        }
+    }
+
+    for (raw_commit, related_pr) in sources {
         match get_release_note(&raw_commit, related_pr.as_ref(), map, options) {
             Ok((section_title, release_note)) => {
-                insert_release_note(unreleased, section_title, release_note);
+                insert_release_note(release, section_title, release_note, map, options)?;
             }
-            Err(e) => eprintln!("commit {}: {e}", raw_commit.short_commit()),
+            Err(e) => info!("commit {}: {e}", raw_commit.short_commit()),
         }
     }
 
     Ok(())
 }
 
+/// With `prefer_pr_title`, replace the subject of the commit by the title of its PR, when there is one.
+fn prefer_pr_title(raw_commit: &mut RawCommit, related_pr: Option<&RelatedPr>, options: &Generate) {
+    if !options.prefer_pr_title {
+        return;
+    }
+
+    if let Some(title) = related_pr
+        .filter(|pr| pr.is_pr)
+        .and_then(|pr| pr.title.as_ref())
+        .filter(|title| !title.trim().is_empty())
+    {
+        debug!(
+            "commit {}: using the title of its pr: {title}",
+            raw_commit.short_commit()
+        );
+        raw_commit.title = title.clone();
+    }
+}
+
+/// Fail if a commit is not attached to a PR.
+fn require_pr<'a>(
+    sources: impl IntoIterator<Item = (&'a RawCommit, Option<&'a RelatedPr>)>,
+) -> Result<(), GenerationError> {
+    let commits = sources
+        .into_iter()
+        .filter(|(_, pr)| pr.is_none_or(|pr| !pr.is_pr))
+        .map(|(commit, _)| (commit.sha.clone(), commit.title.clone()))
+        .collect::<Vec<_>>();
+
+    if !commits.is_empty() {
+        return Err(GenerationError::MissingPrs { commits });
+    }
+
+    Ok(())
+}
+
+/// Take the commits attached to a PR out of `sources`, and merge them in one commit per PR, titled by the PR.
+/// The titles of the merged commits are returned with it.
+fn group_by_pr(
+    sources: &mut Vec<(RawCommit, Option<RelatedPr>)>,
+) -> Vec<(RawCommit, RelatedPr, Vec<String>)> {
+    let mut groups: IndexMap<String, (RelatedPr, Vec<RawCommit>)> = IndexMap::new();
+
+    for (raw_commit, related_pr) in
+        sources.extract_if(.., |(_, pr)| pr.as_ref().is_some_and(|pr| pr.is_pr))
+    {
+        let related_pr = related_pr.unwrap();
+
+        groups
+            .entry(related_pr.pr_id.clone())
+            .or_insert_with(|| (related_pr, Vec::new()))
+            .1
+            .push(raw_commit);
+    }
+
+    groups
+        .into_values()
+        .map(|(related_pr, commits)| {
+            let title = related_pr
+                .title
+                .clone()
+                .unwrap_or_else(|| commits[0].title.clone());
+
+            let context = commits
+                .iter()
+                .filter(|commit| !commit.is_merge && commit.title != title)
+                .map(|commit| commit.title.clone())
+                .collect();
+
+            let raw_commit = RawCommit {
+                author: related_pr
+                    .author
+                    .clone()
+                    .unwrap_or_else(|| commits[0].author.clone()),
+                title,
+                body: related_pr.body.clone().unwrap_or_default(),
+                sha: commits[0].sha.clone(),
+                list_files: commits
+                    .iter()
+                    .flat_map(|commit| commit.list_files.clone())
+                    .collect(),
+                is_merge: false,
+                date: None,
+            };
+
+            (raw_commit, related_pr, context)
+        })
+        .collect()
+}
+
+fn raw_commit<R: Repository>(r: &R, sha: &str, options: &Generate) -> RawCommit {
+    let mut raw_commit = RawCommit::from_sha(r, sha);
+
+    if options.git_notes {
+        if let Some(note) = r.commit_note(sha) {
+            debug!("commit {}: using git note", raw_commit.short_commit());
+            raw_commit.apply_note(&note);
+        }
+    }
+
+    // "Merge pull request #1 from ...": the message is in the body
+    if options.merges_only && raw_commit.is_merge && raw_commit.title.starts_with("Merge ") {
+        let body = std::mem::take(&mut raw_commit.body);
+
+        if !body.is_empty() {
+            raw_commit.apply_note(&body);
+        }
+    }
+
+    raw_commit
+}
+
+/// Whether the commit touches a file under `--path`, not matching any `--exclude-glob`.
+fn touches_included_files(raw_commit: &RawCommit, options: &Generate) -> bool {
+    if options.path_filter.is_none() && options.exclude_globs.is_empty() {
+        return true;
+    }
+
+    let exclude_globs = options
+        .exclude_globs
+        .iter()
+        .map(|glob| utils::glob_regex(glob))
+        .collect::<Vec<_>>();
+
+    raw_commit.list_files.iter().any(|file| {
+        options
+            .path_filter
+            .as_deref()
+            .is_none_or(|path| utils::is_under_path(file, path))
+            && !exclude_globs.iter().any(|glob| glob.is_match(file))
+    })
+}
+
+/// Section named by a scope, either directly or through the map.
+fn scope_section(map: &MapMessageToSection, scope: &str) -> Option<String> {
+    map.0
+        .keys()
+        .find(|section| section.eq_ignore_ascii_case(scope))
+        .cloned()
+        .or_else(|| map.map_section(scope))
+}
+
+/// Path taken to resolve the section of a commit, see [`explain_section`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionDecision {
+    /// The conventional commit type is a keyword of the section.
+    Mapped { section: String, keyword: String },
+    /// A keyword of the section was found in the message or the description,
+    /// because the commit is not conventional or its type is unknown.
+    Found(FoundSection),
+    /// No keyword matched. The commit goes to "Unidentified", or is excluded.
+    Fallback,
+}
+
+impl Display for SectionDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionDecision::Mapped { section, keyword } => {
+                write!(f, "{section}, because the commit type is \"{keyword}\"")
+            }
+            SectionDecision::Found(found) => write!(
+                f,
+                "{}, because \"{}\" was found in the {}",
+                found.section,
+                found.keyword,
+                if found.in_description {
+                    "description"
+                } else {
+                    "message"
+                }
+            ),
+            SectionDecision::Fallback => write!(f, "no section matched"),
+        }
+    }
+}
+
+/// Explain which section `map` gives to a commit, before the deprecation and breaking change rules.
+/// Useful to tune a map.
+pub fn explain_section(message: &str, desc: &str, map: &MapMessageToSection) -> SectionDecision {
+    if let Ok(commit) = parse_commit(message) {
+        if let Some((section, keyword)) = map.map_section_keyword(&commit.kind) {
+            return SectionDecision::Mapped { section, keyword };
+        }
+    }
+
+    match map.try_find_section_keyword((message, desc)) {
+        Some(found) => SectionDecision::Found(found),
+        None => SectionDecision::Fallback,
+    }
+}
+
 fn get_release_note(
     raw_commit: &RawCommit,
     related_pr: Option<&RelatedPr>,
     map: &MapMessageToSection,
     options: &Generate,
-) -> Result<(String, ReleaseSectionNote)> {
-    if let Response::Yes { reason } = commit_should_be_ignored(raw_commit) {
-        bail!("Ignoring commit. {reason}");
-    }
-
-    let mut commit = match parse_commit(&raw_commit.title) {
-        Ok(mut commit) => {
-            let section = match map.map_section(&commit.section) {
-                Some(section) => section,
-                None => {
-                    if options.parsing == CommitMessageParsing::Strict {
-                        bail!(
-                            "no corresponding commit type was found for {}",
-                            commit.section
-                        );
-                    }
+) -> Result<(String, ReleaseSectionNote), GenerationError> {
+    let sha = || raw_commit.sha.clone();
+
+    if let Response::Yes { pattern, .. } =
+        commit_should_be_ignored(raw_commit, &options.skip_trailer)
+    {
+        return Err(GenerationError::Ignored {
+            sha: sha(),
+            pattern,
+        });
+    }
+
+    let deprecated = is_deprecation(raw_commit, &options.deprecation_keywords);
+
+    if log_enabled!(log::Level::Trace) {
+        trace!(
+            "commit {}: {}",
+            raw_commit.short_commit(),
+            explain_section(&raw_commit.title, &raw_commit.body, map)
+        );
+    }
 
-                    if let Some(section) =
-                        map.try_find_section((&raw_commit.title, &raw_commit.body))
-                    {
-                        section
-                    } else {
-                        if options.exclude_unidentified {
-                            bail!(
-                                "No corresponding commit type was found for {}",
-                                commit.section
-                            );
+    // the section is still found from the subject
+    let message_override =
+        trailer(&raw_commit.body, &options.message_trailer).filter(|value| !is_skip_value(value));
+
+    let trailer_section = trailer(&raw_commit.body, &options.section_trailer)
+        .map(|section| scope_section(map, section).unwrap_or(section.to_owned()));
+
+    let mut commit = if let Some(section) = &trailer_section {
+        match parse_commit(&raw_commit.title) {
+            Ok(commit) => FormattedCommit {
+                section: section.clone(),
+                ..commit
+            },
+            Err(_) => FormattedCommit {
+                kind: String::new(),
+                section: section.clone(),
+                scope: None,
+                message: raw_commit.title.clone(),
+                breaking: false,
+            },
+        }
+    } else {
+        match parse_commit(&raw_commit.title) {
+            Ok(mut commit) => {
+                let section = match map.map_section(&commit.kind) {
+                    _ if deprecated => DEPRECATED.into(),
+                    Some(section) => section,
+                    None => {
+                        if options.parsing == CommitMessageParsing::Strict {
+                            return Err(GenerationError::UnknownType {
+                                sha: sha(),
+                                kind: commit.kind,
+                            });
+                        }
+
+                        if let Some(section) =
+                            map.try_find_section((&raw_commit.title, &raw_commit.body))
+                        {
+                            section
+                        } else {
+                            if options.exclude_unidentified {
+                                return Err(GenerationError::Unidentified {
+                                    sha: sha(),
+                                    kind: Some(commit.kind),
+                                });
+                            }
+                            "Unidentified".into()
                         }
-                        "Unidentified".into()
                     }
+                };
+
+                commit.section = section;
+                commit
+            }
+            Err(e) => {
+                if options.parsing == CommitMessageParsing::Strict {
+                    return Err(GenerationError::InvalidSyntax {
+                        sha: sha(),
+                        message: e.to_string(),
+                    });
                 }
-            };
 
-            commit.section = section;
-            commit
+                let section = if deprecated {
+                    DEPRECATED.into()
+                } else if let Some(section) =
+                    map.try_find_section((&raw_commit.title, &raw_commit.body))
+                {
+                    section
+                } else {
+                    if options.exclude_unidentified {
+                        return Err(GenerationError::Unidentified {
+                            sha: sha(),
+                            kind: None,
+                        });
+                    }
+                    "Unidentified".into()
+                };
+
+                FormattedCommit {
+                    kind: String::new(),
+                    section,
+                    scope: None,
+                    message: raw_commit.title.clone(),
+                    breaking: false,
+                }
+            }
         }
-        Err(e) => {
-            if options.parsing == CommitMessageParsing::Strict {
-                bail!(
-                    "Commit {}: invalid syntax: {}",
-                    raw_commit.short_commit(),
-                    e
-                );
+    };
+
+    let breaking_change = breaking_change(&raw_commit.body);
+
+    let mut hidden_trailers = vec![
+        options.section_trailer.as_str(),
+        &options.skip_trailer,
+        &options.message_trailer,
+    ];
+    hidden_trailers.extend(options.context_footers.iter().map(String::as_str));
+
+    let mut context = body_context(
+        &without_trailers(&without_breaking_change(&raw_commit.body), &hidden_trailers),
+        &options.multiline_body,
+    );
+
+    // after the body, whatever the body mode
+    context.extend(trailer_lines(&raw_commit.body, &options.context_footers));
+
+    if commit.breaking || breaking_change.is_some() {
+        commit.breaking = true;
+        commit.section = BREAKING.into();
+
+        // the explanation is more useful than the subject, but not than the message of the trailer
+        if let Some(description) = breaking_change {
+            match message_override {
+                Some(_) => context.insert(0, description),
+                None => context.insert(0, std::mem::replace(&mut commit.message, description)),
             }
+        }
+    }
 
-            let section = if let Some(section) =
-                map.try_find_section((&raw_commit.title, &raw_commit.body))
-            {
-                section
-            } else {
-                if options.exclude_unidentified {
-                    bail!("Not identified.");
-                }
-                "Unidentified".into()
-            };
+    if let Some(message) = message_override {
+        commit.message = message.to_owned();
+    }
 
-            FormattedCommit {
-                section,
-                scope: None,
-                message: raw_commit.title.clone(),
+    if options.promote_scope_to_section && !deprecated && !commit.breaking {
+        if let Some(section) = commit
+            .scope
+            .as_deref()
+            .and_then(|scope| scope_section(map, scope))
+        {
+            commit.section = section;
+            commit.scope = None;
+        }
+    }
+
+    // the trailer has the last word
+    if let Some(section) = trailer_section {
+        commit.section = section;
+    }
+
+    if let (Some(allowed_scopes), Some(scope)) = (&options.allowed_scopes, &commit.scope) {
+        if !allowed_scopes.contains(scope) {
+            match options.scope_policy {
+                ScopePolicy::Warn => {
+                    warn!(
+                        "commit {}: the scope {scope} is not allowed",
+                        raw_commit.short_commit()
+                    )
+                }
+                ScopePolicy::Drop => commit.scope = None,
+                ScopePolicy::Error => {
+                    return Err(GenerationError::ScopeNotAllowed {
+                        sha: sha(),
+                        section: commit.section,
+                        scope: scope.clone(),
+                    })
+                }
             }
         }
-    };
+    }
 
     if let Some(related_pr) = &related_pr {
         if !related_pr.is_pr && options.exclude_not_pr {
-            bail!("No upstream pr was found");
+            return Err(GenerationError::NotPr {
+                sha: sha(),
+                section: commit.section,
+            });
         }
 
         if !options.omit_pr_link {
-            commit
-                .message
-                .push_str(&format!(" in [{}]({})", related_pr.pr_id, related_pr.url));
+            let mut link = TextInterpolate::new(options.pr_link_format.clone(), "{", "}");
+            link.interpolate("id", &related_pr.pr_id);
+            link.interpolate("url", &related_pr.url);
+
+            commit.message.push(' ');
+            commit.message.push_str(&link.text());
         }
 
         if !options.omit_thanks {
-            if let (Some(author), Some(author_link)) = (&related_pr.author, &related_pr.author_link)
+            if let Some(author) = related_pr
+                .author
+                .as_ref()
+                .filter(|author| !(options.thanks_only_external && is_internal(author, options)))
             {
-                commit
-                    .message
-                    .push_str(&format!(" by [@{author}]({author_link})"));
+                // a link built from the login, rather than a broken one
+                let author_link = related_pr
+                    .author_link
+                    .clone()
+                    .filter(|link| link.starts_with("https://") || link.starts_with("http://"))
+                    .or_else(|| {
+                        options.author_link_template.as_ref().map(|template| {
+                            let mut link = TextInterpolate::new(template.clone(), "{", "}");
+                            link.interpolate("author", author);
+                            link.text()
+                        })
+                    })
+                    .or_else(|| options.provider.profile_link(author));
+
+                if let Some(author_link) = author_link {
+                    commit
+                        .message
+                        .push_str(&format!(" by [@{author}]({author_link})"));
+                }
             }
         }
     } else if options.exclude_not_pr {
-        bail!("no upstream pr was found");
+        return Err(GenerationError::NotPr {
+            sha: sha(),
+            section: commit.section,
+        });
     };
 
+    if commit.kind.is_empty() {
+        debug!(
+            "commit {}: not conventional, in {}",
+            raw_commit.short_commit(),
+            commit.section
+        );
+    } else {
+        debug!(
+            "commit {}: type \"{}\", in {}",
+            raw_commit.short_commit(),
+            commit.kind,
+            commit.section
+        );
+    }
+
     Ok((
         commit.section,
         ReleaseSectionNote {
             scope: commit.scope,
             message: commit.message,
-            context: vec![],
+            context,
+            date: raw_commit.date.clone(),
         },
     ))
 }
 
+const DEPRECATED: &str = "Deprecated";
+const BREAKING: &str = "Breaking Changes";
+
+fn is_breaking_change_footer(line: &str) -> Option<&str> {
+    line.trim_start()
+        .strip_prefix("BREAKING CHANGE:")
+        .or_else(|| line.trim_start().strip_prefix("BREAKING-CHANGE:"))
+}
+
+/// Text of the "BREAKING CHANGE:" footer of a commit body, up to the next blank line.
+fn breaking_change(body: &str) -> Option<String> {
+    let mut lines = body.lines();
+
+    let first = lines.find_map(is_breaking_change_footer)?;
+
+    let description = std::iter::once(first)
+        .chain(lines.take_while(|line| !line.trim().is_empty()))
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let description = description.trim();
+
+    if description.is_empty() {
+        None
+    } else {
+        Some(description.to_owned())
+    }
+}
+
+/// The body without its "BREAKING CHANGE:" footer.
+fn without_breaking_change(body: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_footer = false;
+
+    for line in body.lines() {
+        if is_breaking_change_footer(line).is_some() {
+            in_footer = true;
+        } else if line.trim().is_empty() {
+            in_footer = false;
+        }
+
+        if !in_footer {
+            lines.push(line);
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Value of the `key: value` trailer of a commit body. The key is case insensitive.
+fn trailer<'a>(body: &'a str, key: &str) -> Option<&'a str> {
+    body.lines().find_map(|line| {
+        let (line_key, value) = line.trim().split_once(':')?;
+        let value = value.trim();
+
+        (line_key.eq_ignore_ascii_case(key) && !value.is_empty()).then_some(value)
+    })
+}
+
+/// The `key: value` lines of the `keys` trailers, in the order of the body.
+fn trailer_lines(body: &str, keys: &[String]) -> Vec<String> {
+    body.lines()
+        .map(str::trim)
+        .filter(|line| {
+            line.split_once(':').is_some_and(|(key, value)| {
+                !value.trim().is_empty() && keys.iter().any(|k| key.eq_ignore_ascii_case(k))
+            })
+        })
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// The body without the lines of the `keys` trailers.
+fn without_trailers(body: &str, keys: &[&str]) -> String {
+    body.lines()
+        .filter(|line| {
+            line.trim()
+                .split_once(':')
+                .is_none_or(|(key, _)| !keys.iter().any(|k| key.eq_ignore_ascii_case(k)))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The title mentions one of the keywords, or the body has a `DEPRECATED:` trailer.
+fn is_deprecation(raw_commit: &RawCommit, keywords: &[String]) -> bool {
+    if raw_commit
+        .body
+        .lines()
+        .any(|line| line.trim_start().starts_with("DEPRECATED:"))
+    {
+        return true;
+    }
+
+    raw_commit
+        .title
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| {
+            keywords
+                .iter()
+                .any(|keyword| !keyword.is_empty() && keyword.eq_ignore_ascii_case(word))
+        })
+}
+
+fn body_context(body: &str, mode: &BodyMode) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = Vec::new();
+
+    for line in body.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current.join(" "));
+                current.clear();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+
+    if !current.is_empty() {
+        paragraphs.push(current.join(" "));
+    }
+
+    match mode {
+        BodyMode::Ignore => vec![],
+        BodyMode::FirstParagraph => paragraphs.into_iter().take(1).collect(),
+        BodyMode::AllParagraphs => paragraphs,
+    }
+}
+
+/// Add the note to its section of `unreleased`. With `strict_sections`, a section which is not in
+/// `unreleased`, in the map, or in the allowed sections is an error rather than created.
 fn insert_release_note(
     unreleased: &mut Release,
     section_title: String,
     release_note: ReleaseSectionNote,
-) {
+    map: &MapMessageToSection,
+    options: &Generate,
+) -> Result<(), GenerationError> {
     let section = if let Some(section) = unreleased.note_sections.get_mut(&section_title) {
         section
     } else {
+        if options.strict_sections
+            && !map.0.contains_key(&section_title)
+            && !options.allowed_sections.contains(&section_title)
+        {
+            return Err(GenerationError::UnexpectedSection {
+                section: section_title,
+                message: release_note.message,
+            });
+        }
+
         let release_section = ReleaseSection {
             title: section_title.clone(),
+            description: None,
             notes: vec![],
         };
 
@@ -296,11 +1046,17 @@ fn insert_release_note(
     };
 
     section.notes.push(release_note);
+
+    Ok(())
 }
 
 #[derive(Debug, Clone)]
-enum Response {
-    Yes { reason: String },
+pub enum Response {
+    /// `pattern` is the matched text, or the skip trailer.
+    Yes {
+        pattern: String,
+        reason: String,
+    },
     No,
 }
 
@@ -314,9 +1070,21 @@ impl Response {
     }
 }
 
-fn commit_should_be_ignored(raw: &RawCommit) -> Response {
+fn is_skip_value(value: &str) -> bool {
+    value.eq_ignore_ascii_case("skip") || value.eq_ignore_ascii_case("ignore")
+}
+
+/// `skip_trailer` is the key of the trailer skipping the commit, like "Changelog: skip".
+pub fn commit_should_be_ignored(raw: &RawCommit, skip_trailer: &str) -> Response {
     debug!("{:?}", raw);
 
+    if let Some(value) = trailer(&raw.body, skip_trailer).filter(|value| is_skip_value(value)) {
+        return Response::Yes {
+            pattern: format!("{skip_trailer}: {value}"),
+            reason: format!("The trailer \"{skip_trailer}: {value}\" was found."),
+        };
+    }
+
     let names = ["changelog", "log", "chglog", "notes"];
 
     let match_pat = |pat: &str| raw.title.contains(pat);
@@ -331,6 +1099,7 @@ fn commit_should_be_ignored(raw: &RawCommit) -> Response {
         for pattern in &patterns {
             if match_pat(pattern) {
                 return Response::Yes {
+                    pattern: pattern.clone(),
                     reason: format!(
                         "\"{pattern}\" was matched in the commit title or description."
                     ),
@@ -344,22 +1113,764 @@ fn commit_should_be_ignored(raw: &RawCommit) -> Response {
 
 #[cfg(test)]
 mod test {
-    use crate::{generate::commit_should_be_ignored, repository::RawCommit};
+    use crate::{
+        config::{
+            BodyMode, CommitMessageParsing, FoundSection, Generate, MapMessageToSection,
+            ScopePolicy, SKIP_TRAILER,
+        },
+        error::GenerationError,
+        generate::{
+            body_context, commit_should_be_ignored, explain_section, get_release_note, group_by_pr,
+            insert_release_note, prefer_pr_changelog_section, prefer_pr_title, skip_released,
+            SectionDecision,
+        },
+        git_provider::{GitProvider, RelatedPr},
+        integration_test::{pr, raw, DEFAULT_GENERATE},
+        repository::RawCommit,
+    };
 
     #[test]
     fn ignore_commit() {
-        let mut raw = RawCommit {
-            title: "fix: something !log".into(),
-            body: "".into(),
-            sha: "".into(),
-            list_files: vec![],
-            author: "".into(),
-        };
+        let mut raw = raw("fix: something !log", "");
 
-        assert!(commit_should_be_ignored(&raw).bool());
+        assert!(commit_should_be_ignored(&raw, SKIP_TRAILER).bool());
 
         raw.title = "fix: something log".into();
 
-        assert!(!commit_should_be_ignored(&raw).bool());
+        assert!(!commit_should_be_ignored(&raw, SKIP_TRAILER).bool());
+    }
+
+    #[test]
+    fn pr_link_format() {
+        let raw = raw("fix: something", "");
+        let related_pr = pr("#1", None);
+
+        let mut options = DEFAULT_GENERATE.clone();
+        options.pr_link_format = "(PR {id})".into();
+
+        let (_, note) = get_release_note(
+            &raw,
+            Some(&related_pr),
+            &MapMessageToSection::default(),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(note.message, "something (PR #1)");
+    }
+
+    #[test]
+    fn multiline_body() {
+        let body = "first line\nof the summary\n\nsecond paragraph\n\n\nthird";
+
+        assert!(body_context(body, &BodyMode::Ignore).is_empty());
+
+        assert_eq!(
+            body_context(body, &BodyMode::FirstParagraph),
+            vec!["first line of the summary".to_owned()]
+        );
+
+        assert_eq!(
+            body_context(body, &BodyMode::AllParagraphs),
+            vec![
+                "first line of the summary".to_owned(),
+                "second paragraph".to_owned(),
+                "third".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecation() {
+        let note_section = |title: &str, body: &str| {
+            let raw = raw(title, body);
+
+            get_release_note(
+                &raw,
+                None,
+                &MapMessageToSection::default(),
+                &DEFAULT_GENERATE,
+            )
+            .map(|(section, _)| section)
+            .unwrap()
+        };
+
+        assert_eq!(
+            note_section("feat: deprecate the old api", ""),
+            "Deprecated"
+        );
+        assert_eq!(note_section("feat(api): Deprecated: v1", ""), "Deprecated");
+        assert_eq!(
+            note_section("feat: new api", "DEPRECATED: the old api"),
+            "Deprecated"
+        );
+        assert_eq!(note_section("fix: deprecation warning", ""), "Fixed");
+
+        let mut options = DEFAULT_GENERATE.clone();
+        options.deprecation_keywords = vec!["obsolete".into()];
+
+        let raw = raw("fix: deprecated call", "");
+
+        let (section, _) =
+            get_release_note(&raw, None, &MapMessageToSection::default(), &options).unwrap();
+        assert_eq!(section, "Fixed");
+    }
+
+    #[test]
+    fn note_date() {
+        let raw = RawCommit {
+            date: Some("2024-01-02T09:00:00+00:00".into()),
+            ..raw("fix: crash", "")
+        };
+
+        let (_, note) = get_release_note(
+            &raw,
+            None,
+            &MapMessageToSection::default(),
+            &DEFAULT_GENERATE,
+        )
+        .unwrap();
+        assert_eq!(note.date.as_deref(), Some("2024-01-02T09:00:00+00:00"));
+    }
+
+    #[test]
+    fn allowed_scopes() {
+        let scope = |options: &crate::config::Generate| {
+            let raw = raw("fix(uit): something", "");
+
+            get_release_note(&raw, None, &MapMessageToSection::default(), options)
+                .map(|(_, note)| note.scope)
+        };
+
+        let mut options = DEFAULT_GENERATE.clone();
+        assert_eq!(scope(&options).unwrap(), Some("uit".into()));
+
+        options.allowed_scopes = Some(vec!["ui".into(), "data".into()]);
+        assert_eq!(scope(&options).unwrap(), Some("uit".into()));
+
+        options.scope_policy = ScopePolicy::Drop;
+        assert_eq!(scope(&options).unwrap(), None);
+
+        options.scope_policy = ScopePolicy::Error;
+        scope(&options).unwrap_err();
+
+        options.allowed_scopes = Some(vec!["uit".into()]);
+        assert_eq!(scope(&options).unwrap(), Some("uit".into()));
+    }
+
+    #[test]
+    fn promote_scope_to_section() {
+        let note = |title: &str, options: &crate::config::Generate| {
+            let raw = raw(title, "");
+
+            get_release_note(&raw, None, &MapMessageToSection::default(), options)
+                .map(|(section, note)| (section, note.scope))
+                .unwrap()
+        };
+
+        let mut options = DEFAULT_GENERATE.clone();
+        assert_eq!(
+            note("fix(security): leak", &options),
+            ("Fixed".into(), Some("security".into()))
+        );
+
+        options.promote_scope_to_section = true;
+        assert_eq!(
+            note("fix(security): leak", &options),
+            ("Security".into(), None)
+        );
+        assert_eq!(
+            note("fix(Removed): old api", &options),
+            ("Removed".into(), None)
+        );
+        assert_eq!(
+            note("fix(ui): crash", &options),
+            ("Fixed".into(), Some("ui".into()))
+        );
+    }
+
+    #[test]
+    fn pr_changelog_section() {
+        let body = "## Description
+
+Refactor the parser.
+
+## Changelog
+
+<!-- The user facing note -->
+fix: crash on empty files
+
+### Details
+
+more context
+
+## Checklist
+
+- [x] tests
+";
+
+        assert_eq!(
+            super::pr_changelog_section(body, "changelog").unwrap(),
+            "fix: crash on empty files\n\n### Details\n\nmore context"
+        );
+
+        assert_eq!(super::pr_changelog_section(body, "Notes"), None);
+        assert_eq!(
+            super::pr_changelog_section("## Changelog\n<!-- todo -->\n## Next", "Changelog"),
+            None
+        );
+    }
+
+    #[test]
+    fn qualify_pr_id() {
+        let mut pr = RelatedPr {
+            repo: "a/lib".into(),
+            url: "https://github.com/a/lib/pull/3".into(),
+            title: Some("feat: new api".into()),
+            ..pr("#3", None)
+        };
+
+        super::qualify_pr_id(&mut pr, "a/app");
+        assert_eq!(pr.pr_id, "a/lib#3");
+
+        let (_, note) = get_release_note(
+            &raw("feat: new api", ""),
+            Some(&pr),
+            &MapMessageToSection::default(),
+            &DEFAULT_GENERATE,
+        )
+        .unwrap();
+        assert_eq!(
+            note.message,
+            "new api in [a/lib#3](https://github.com/a/lib/pull/3)"
+        );
+
+        pr.repo = "a/app".into();
+        pr.pr_id = "#4".into();
+        super::qualify_pr_id(&mut pr, "a/app");
+        assert_eq!(pr.pr_id, "#4");
+    }
+
+    #[test]
+    fn author_link_fallback() {
+        let pr = pr("#3", Some("alice"));
+
+        let (_, note) = get_release_note(
+            &raw("fix: crash", ""),
+            Some(&pr),
+            &MapMessageToSection::default(),
+            &Generate {
+                provider: GitProvider::Github,
+                ..DEFAULT_GENERATE.clone()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            note.message,
+            "crash in [#3](https://github.com/a/b/pull/3) by [@alice](https://github.com/alice)"
+        );
+
+        let options = Generate {
+            provider: GitProvider::Gitlab,
+            author_link_template: Some("https://git.example.com/{author}".into()),
+            ..DEFAULT_GENERATE.clone()
+        };
+        let raw_commit = raw("fix: crash", "");
+
+        let (_, note) = get_release_note(
+            &raw_commit,
+            Some(&pr),
+            &MapMessageToSection::default(),
+            &options,
+        )
+        .unwrap();
+        assert!(note
+            .message
+            .ends_with("by [@alice](https://git.example.com/alice)"));
+
+        // a full link of the provider is kept
+        let pr = RelatedPr {
+            author_link: Some("https://gitlab.com/alice".into()),
+            ..pr
+        };
+        let (_, note) = get_release_note(
+            &raw_commit,
+            Some(&pr),
+            &MapMessageToSection::default(),
+            &options,
+        )
+        .unwrap();
+        assert!(note
+            .message
+            .ends_with("by [@alice](https://gitlab.com/alice)"));
+    }
+
+    #[test]
+    fn group_commits_by_pr() {
+        let commit = |title: &str, sha: &str| RawCommit {
+            sha: sha.into(),
+            author: "bob".into(),
+            ..raw(title, "")
+        };
+
+        let pr = |id: &str, is_pr: bool| RelatedPr {
+            title: Some("feat: new parser".into()),
+            is_pr,
+            ..pr(id, Some("alice"))
+        };
+
+        let mut sources = vec![
+            (commit("wip", "1"), Some(pr("#1", true))),
+            (commit("fix: typo", "2"), Some(pr("2", false))),
+            (commit("feat: new parser", "3"), Some(pr("#1", true))),
+            (commit("tests", "4"), Some(pr("#1", true))),
+            (commit("fix: crash", "5"), None),
+        ];
+
+        let groups = group_by_pr(&mut sources);
+
+        assert_eq!(groups.len(), 1);
+
+        let (raw_commit, related_pr, commits) = &groups[0];
+        assert_eq!(raw_commit.title, "feat: new parser");
+        assert_eq!(raw_commit.author, "alice");
+        assert_eq!(raw_commit.sha, "1");
+        assert_eq!(related_pr.pr_id, "#1");
+        assert_eq!(commits, &vec!["wip".to_owned(), "tests".to_owned()]);
+
+        assert_eq!(
+            sources
+                .iter()
+                .map(|(commit, _)| commit.sha.as_str())
+                .collect::<Vec<_>>(),
+            vec!["2", "5"]
+        );
+    }
+
+    #[test]
+    fn breaking_change() {
+        let note = |title: &str, body: &str| {
+            let raw = raw(title, body);
+
+            let mut options = DEFAULT_GENERATE.clone();
+            options.multiline_body = BodyMode::AllParagraphs;
+
+            get_release_note(&raw, None, &MapMessageToSection::default(), &options).unwrap()
+        };
+
+        let (section, release_note) = note(
+            "refactor: rework API",
+            "Some context.\n\nBREAKING CHANGE: signatures\nchanged\n\nReviewed-by: bob",
+        );
+        assert_eq!(section, "Breaking Changes");
+        assert_eq!(release_note.message, "signatures changed");
+        assert_eq!(
+            release_note.context,
+            vec![
+                "rework API".to_owned(),
+                "Some context.".to_owned(),
+                "Reviewed-by: bob".to_owned()
+            ]
+        );
+
+        let (section, release_note) = note("feat(api)!: new signatures", "");
+        assert_eq!(section, "Breaking Changes");
+        assert_eq!(release_note.message, "new signatures");
+        assert_eq!(release_note.scope.as_deref(), Some("api"));
+
+        let (section, _) = note("fix: crash", "BREAKING: not a footer");
+        assert_eq!(section, "Fixed");
+    }
+
+    #[test]
+    fn explain() {
+        let map = MapMessageToSection::default();
+
+        assert_eq!(
+            explain_section("fix(ui): crash", "", &map),
+            SectionDecision::Mapped {
+                section: "Fixed".into(),
+                keyword: "fix".into()
+            }
+        );
+
+        assert_eq!(
+            explain_section("unknown: crash", "fix the crash", &map),
+            SectionDecision::Found(FoundSection {
+                section: "Fixed".into(),
+                keyword: "fix".into(),
+                in_description: true,
+            })
+        );
+
+        assert_eq!(
+            explain_section("readme", "", &map),
+            SectionDecision::Fallback
+        );
+    }
+
+    #[test]
+    fn trailers() {
+        let mut options = DEFAULT_GENERATE.clone();
+        options.multiline_body = BodyMode::AllParagraphs;
+        options.parsing = CommitMessageParsing::Strict;
+
+        let map = MapMessageToSection::default();
+
+        let (section, note) = get_release_note(
+            &raw(
+                "fix: escape the input",
+                "Some context.\n\nChangelog-Section: security",
+            ),
+            None,
+            &map,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(section, "Security");
+        assert_eq!(note.context, vec!["Some context."]);
+
+        let (section, note) = get_release_note(
+            &raw("update dependencies", "changelog-section: Changed"),
+            None,
+            &map,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(section, "Changed");
+        assert_eq!(note.message, "update dependencies");
+
+        get_release_note(&raw("feat: wip", "Changelog: skip"), None, &map, &options).unwrap_err();
+
+        let (section, note) = get_release_note(
+            &raw(
+                "fix(cache): bump the hash seed",
+                "Changelog: stale results after an upgrade",
+            ),
+            None,
+            &map,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(section, "Fixed");
+        assert_eq!(note.scope.as_deref(), Some("cache"));
+        assert_eq!(note.message, "stale results after an upgrade");
+        assert!(note.context.is_empty());
+
+        // the description of a breaking change is kept
+        options.message_trailer = "Changelog-Message".into();
+        let (section, note) = get_release_note(
+            &raw(
+                "feat!: new cache",
+                "BREAKING CHANGE: the old entries are dropped\n\nChangelog-Message: faster cache",
+            ),
+            None,
+            &map,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(section, "Breaking Changes");
+        assert_eq!(note.message, "faster cache");
+        assert_eq!(note.context, vec!["the old entries are dropped"]);
+
+        options.context_footers = vec!["Refs".into(), "reviewed-by".into()];
+        let (_, note) = get_release_note(
+            &raw(
+                "fix: crash on start",
+                "Some context.\n\nRefs: #10\nReviewed-by: alice\nSigned-off-by: bob",
+            ),
+            None,
+            &map,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            note.context,
+            vec![
+                "Some context.",
+                "Signed-off-by: bob",
+                "Refs: #10",
+                "Reviewed-by: alice"
+            ]
+        );
+
+        options.skip_trailer = "Release-Note".into();
+        get_release_note(
+            &raw("feat: wip", "Release-Note: ignore"),
+            None,
+            &map,
+            &options,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn skip_released_notes() {
+        let before = changelog::de::parse_changelog(
+            "## [Unreleased]
+
+### Fixed
+
+- manual note
+
+## [1.0.0]
+
+### Added
+
+- ui: export in [#3](https://github.com/a/b/pull/3)
+- import
+",
+        )
+        .unwrap();
+
+        let mut after = before.clone();
+        let unreleased = after.unreleased.as_mut().unwrap();
+
+        unreleased.insert_release_notes(
+            changelog::de::parse_changelog(
+                "## [Unreleased]
+
+### Added
+
+- export the data in [#3](https://github.com/a/b/pull/3)
+- import in [#4](https://github.com/a/b/pull/4)
+- new
+
+### Fixed
+
+- crash
+",
+            )
+            .unwrap()
+            .unreleased
+            .unwrap()
+            .note_sections
+            .into_values(),
+        );
+
+        skip_released(unreleased, &before);
+
+        let messages = |title: &str| {
+            unreleased.note_sections[title]
+                .notes
+                .iter()
+                .map(|n| n.message.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(messages("Added"), vec!["new"]);
+        assert_eq!(messages("Fixed"), vec!["manual note", "crash"]);
+    }
+
+    #[test]
+    fn thanks_only_external() {
+        let pr = |author: &str| RelatedPr {
+            author_link: Some(format!("https://github.com/{author}")),
+            ..pr("#3", Some(author))
+        };
+
+        let options = Generate {
+            thanks_only_external: true,
+            internal_authors: vec!["@Alice".into(), "renovate[bot]".into()],
+            ..DEFAULT_GENERATE.clone()
+        };
+
+        let message = |author: &str| {
+            get_release_note(
+                &raw("fix: crash", ""),
+                Some(&pr(author)),
+                &MapMessageToSection::default(),
+                &options,
+            )
+            .unwrap()
+            .1
+            .message
+        };
+
+        assert_eq!(
+            message("alice"),
+            "crash in [#3](https://github.com/a/b/pull/3)"
+        );
+        assert_eq!(
+            message("renovate[bot]"),
+            "crash in [#3](https://github.com/a/b/pull/3)"
+        );
+        assert_eq!(
+            message("bob"),
+            "crash in [#3](https://github.com/a/b/pull/3) by [@bob](https://github.com/bob)"
+        );
+    }
+
+    #[test]
+    fn pr_title() {
+        let mut pr = RelatedPr {
+            title: Some("feat(ui): dark mode".into()),
+            ..pr("#3", None)
+        };
+
+        let raw = raw("wip dark (#3)", "");
+
+        let options = Generate {
+            prefer_pr_title: true,
+            ..DEFAULT_GENERATE.clone()
+        };
+
+        let mut commit = raw.clone();
+        prefer_pr_title(&mut commit, Some(&pr), &DEFAULT_GENERATE);
+        assert_eq!(commit.title, "wip dark (#3)");
+
+        prefer_pr_title(&mut commit, Some(&pr), &options);
+        assert_eq!(commit.title, "feat(ui): dark mode");
+
+        // not a PR, or no PR: the subject of the commit
+        pr.is_pr = false;
+        let mut commit = raw.clone();
+        prefer_pr_title(&mut commit, Some(&pr), &options);
+        prefer_pr_title(&mut commit, None, &options);
+        assert_eq!(commit.title, "wip dark (#3)");
+    }
+
+    #[test]
+    fn pr_body_changelog_section() {
+        let mut pr = RelatedPr {
+            title: Some("wip".into()),
+            body: Some(
+                "Some context.\n\n## Changelog\n\nfix(ui): the dark mode\nOn resume.\n".into(),
+            ),
+            ..pr("#3", None)
+        };
+
+        let raw = raw("wip dark (#3)", "squashed commits");
+
+        let options = Generate {
+            prefer_pr_changelog_section: true,
+            ..DEFAULT_GENERATE.clone()
+        };
+
+        let mut commit = raw.clone();
+        prefer_pr_changelog_section(&mut commit, Some(&pr), &DEFAULT_GENERATE);
+        assert_eq!(
+            (commit.title.as_str(), commit.body.as_str()),
+            ("wip dark (#3)", "squashed commits")
+        );
+
+        prefer_pr_changelog_section(&mut commit, Some(&pr), &options);
+        assert_eq!(commit.title, "fix(ui): the dark mode");
+        assert_eq!(commit.body, "On resume.");
+
+        // no body, or not a PR: the message of the commit
+        let mut commit = raw.clone();
+        pr.body = None;
+        prefer_pr_changelog_section(&mut commit, Some(&pr), &options);
+        prefer_pr_changelog_section(&mut commit, None, &options);
+        assert_eq!(
+            (commit.title.as_str(), commit.body.as_str()),
+            ("wip dark (#3)", "squashed commits")
+        );
+    }
+
+    #[test]
+    fn error_variants() {
+        let raw = |title: &str, body: &str| RawCommit {
+            sha: "0001234567".into(),
+            ..raw(title, body)
+        };
+
+        let map = MapMessageToSection::default();
+        let mut options = DEFAULT_GENERATE.clone();
+
+        let err = |raw: &RawCommit, options: &Generate| {
+            get_release_note(raw, None, &map, options).unwrap_err()
+        };
+
+        assert!(matches!(
+            err(&raw("fix: a !log", ""), &options),
+            GenerationError::Ignored { sha, pattern } if sha == "0001234567" && pattern == "!log"
+        ));
+        assert!(matches!(
+            err(&raw("fix: a", "Changelog: skip"), &options),
+            GenerationError::Ignored { pattern, .. } if pattern == "Changelog: skip"
+        ));
+
+        options.exclude_unidentified = true;
+        assert!(matches!(
+            err(&raw("unknown: a", ""), &options),
+            GenerationError::Unidentified { kind: Some(kind), .. } if kind == "unknown"
+        ));
+        assert!(matches!(
+            err(&raw("readme", ""), &options),
+            GenerationError::Unidentified { kind: None, .. }
+        ));
+
+        options.exclude_unidentified = false;
+        options.parsing = CommitMessageParsing::Strict;
+        assert!(matches!(
+            err(&raw("unknown: a", ""), &options),
+            GenerationError::UnknownType { kind, .. } if kind == "unknown"
+        ));
+        assert!(matches!(
+            err(&raw("readme", ""), &options),
+            GenerationError::InvalidSyntax { .. }
+        ));
+
+        options.allowed_scopes = Some(vec!["ui".into()]);
+        options.scope_policy = ScopePolicy::Error;
+        assert!(matches!(
+            err(&raw("fix(data): a", ""), &options),
+            GenerationError::ScopeNotAllowed { section, scope, .. }
+                if section == "Fixed" && scope == "data"
+        ));
+
+        options.allowed_scopes = None;
+        options.exclude_not_pr = true;
+        assert!(matches!(
+            err(&raw("fix: a", ""), &options),
+            GenerationError::NotPr { section, .. } if section == "Fixed"
+        ));
+
+        let commit = raw("fix: a", "");
+        let err = super::require_pr([(&commit, None)]).unwrap_err();
+        assert!(!err.is_skip());
+        assert!(matches!(
+            &err,
+            GenerationError::MissingPrs { commits } if commits[0].0 == "0001234567"
+        ));
+    }
+
+    #[test]
+    fn strict_sections() {
+        let map = MapMessageToSection::keep_a_changelog_default();
+        let mut release = changelog::utils::DEFAULT_UNRELEASED.clone();
+        let note = |message: &str| changelog::ReleaseSectionNote::new(message);
+
+        let mut options = DEFAULT_GENERATE.clone();
+
+        insert_release_note(&mut release, "Fxed".into(), note("a"), &map, &options).unwrap();
+
+        options.strict_sections = true;
+
+        // already in the release
+        insert_release_note(&mut release, "Fxed".into(), note("b"), &map, &options).unwrap();
+        insert_release_note(&mut release, "Fixed".into(), note("c"), &map, &options).unwrap();
+
+        let err = insert_release_note(&mut release, "Adedd".into(), note("d"), &map, &options)
+            .unwrap_err();
+        assert!(!err.is_skip());
+        assert_eq!(
+            err.to_string(),
+            "The note \"d\" would create the section Adedd, which is not in the map. Use --allowed-sections to allow it"
+        );
+        assert!(!release.note_sections.contains_key("Adedd"));
+
+        options.allowed_sections = vec!["Unidentified".into()];
+        insert_release_note(
+            &mut release,
+            "Unidentified".into(),
+            note("e"),
+            &map,
+            &options,
+        )
+        .unwrap();
     }
 }
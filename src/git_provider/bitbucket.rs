@@ -0,0 +1,86 @@
+use super::*;
+
+pub fn diff_link(repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
+    let base = format!("https://bitbucket.org/{repo}");
+
+    let link = match diff_tags.prev_tag() {
+        Some(prev) => {
+            format!("{base}/compare/{prev}..{}", diff_tags.new_tag())
+        }
+        None => {
+            format!("{base}/src/{}", diff_tags.new_tag())
+        }
+    };
+
+    Ok(link)
+}
+
+pub fn unreleased_link(repo: &str, last: &str) -> anyhow::Result<String> {
+    Ok(format!("https://bitbucket.org/{repo}/compare/{last}..HEAD"))
+}
+
+pub fn release_link(repo: &str, tag: &str) -> anyhow::Result<String> {
+    Ok(format!("https://bitbucket.org/{repo}/src/{tag}"))
+}
+
+pub fn pr_link(repo: &str, number: &str) -> String {
+    format!("https://bitbucket.org/{repo}/pull-requests/{number}")
+}
+
+pub fn profile_link(login: &str) -> String {
+    format!("https://bitbucket.org/{login}")
+}
+
+pub fn offline_related_pr(repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
+    Some(RelatedPr {
+        repo: repo.to_owned(),
+        url: format!("https://bitbucket.org/{repo}/commits/{}", raw_commit.sha),
+        pr_id: raw_commit.short_commit().into(),
+        author: Some(raw_commit.author.clone()),
+        // built from the login by the generation
+        author_link: None,
+        title: Some(raw_commit.title.clone()),
+        body: Some(raw_commit.body.clone()),
+        merge_commit: Some(raw_commit.sha.clone()),
+        is_pr: false,
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn link() {
+        let res = diff_link(
+            "wiiznokes/fan-control",
+            &DiffTags {
+                prev: None,
+                new: Version::new(0, 1, 0),
+                tag_prefix: String::new(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            res,
+            "https://bitbucket.org/wiiznokes/fan-control/src/0.1.0".to_owned()
+        );
+
+        let res = diff_link(
+            "wiiznokes/fan-control",
+            &DiffTags {
+                prev: Some(Version::new(0, 1, 0)),
+                new: Version::new(0, 1, 1),
+                tag_prefix: String::new(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            res,
+            "https://bitbucket.org/wiiznokes/fan-control/compare/0.1.0..0.1.1".to_owned()
+        );
+    }
+}
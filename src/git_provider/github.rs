@@ -1,9 +1,11 @@
 use std::env;
 
 use anyhow::{anyhow, bail};
+use cached::proc_macro::cached;
 use reqwest::{
-    blocking::{Client, RequestBuilder},
+    blocking::{Client, RequestBuilder, Response},
     header::USER_AGENT,
+    StatusCode,
 };
 use serde::Deserialize;
 use serde_json::{json, Value};
@@ -19,15 +21,35 @@ trait ClientExt {
 impl ClientExt for RequestBuilder {
     fn bearer_auth_env(self, name: &str) -> Self {
         if let Ok(token) = env::var(name) {
-            info!("github token is used");
+            debug!("github token is used");
             self.bearer_auth(token)
         } else {
-            info!("no github token used");
+            debug!("no github token used");
             self
         }
     }
 }
 
+/// Url of the api, "https://api.github.com" unless overridden by `GITHUB_API_URL`.
+fn api_url() -> String {
+    #[cfg(test)]
+    if let Some(url) = MOCK_API_URL.with_borrow(Clone::clone) {
+        return url;
+    }
+
+    env::var("GITHUB_API_URL").unwrap_or("https://api.github.com".into())
+}
+
+/// The api answers 429, or 403 with no request remaining, when the rate limit is exceeded.
+fn is_rate_limited(response: &Response) -> bool {
+    response.status() == StatusCode::TOO_MANY_REQUESTS
+        || response.status() == StatusCode::FORBIDDEN
+            && response
+                .headers()
+                .get("x-ratelimit-remaining")
+                .is_some_and(|remaining| remaining == "0")
+}
+
 fn request_github(api: &str) -> anyhow::Result<Value> {
     let client = Client::new();
 
@@ -37,6 +59,13 @@ fn request_github(api: &str) -> anyhow::Result<Value> {
         .bearer_auth_env("GITHUB_TOKEN")
         .send()?;
 
+    if is_rate_limited(&response) {
+        return Err(RateLimited {
+            provider: GitProvider::Github,
+        }
+        .into());
+    }
+
     if response.status().is_success() {
         let obj = response.json()?;
         Ok(obj)
@@ -57,12 +86,19 @@ fn request_github_graphql(query: &str) -> anyhow::Result<Value> {
     });
 
     let response = client
-        .post("https://api.github.com/graphql")
+        .post(format!("{}/graphql", api_url()))
         .header(USER_AGENT, "my-github-client")
         .bearer_auth_env("GITHUB_TOKEN")
         .json(&request_body)
         .send()?;
 
+    if is_rate_limited(&response) {
+        return Err(RateLimited {
+            provider: GitProvider::Github,
+        }
+        .into());
+    }
+
     if response.status().is_success() {
         let obj = response.json()?;
         Ok(obj)
@@ -75,9 +111,7 @@ fn request_github_graphql(query: &str) -> anyhow::Result<Value> {
 }
 
 pub fn request_related_pr(repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
-    let json = request_github(&format!(
-        "https://api.github.com/repos/{repo}/commits/{sha}/pulls"
-    ))?;
+    let json = request_github(&format!("{}/repos/{repo}/commits/{sha}/pulls", api_url()))?;
 
     match json.get(0) {
         Some(obj) => {
@@ -96,38 +130,34 @@ pub fn request_related_pr(repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
 
             let pr_id = format!("#{}", pr_id);
 
-            let author = obj
-                .get("user")
-                .ok_or(anyhow!("no user found"))?
-                .get("login")
-                .ok_or(anyhow!("no login found"))?
-                .as_str()
-                .unwrap()
-                .to_string();
-
-            let author_link = format!("https://github.com/{}", author);
+            let (author, author_link) = user_of(obj.get("user").ok_or(anyhow!("no user found"))?)?;
 
             let title = obj
                 .get("title")
                 .ok_or(anyhow!("no title found"))?
-                .to_string();
-            let body = obj.get("body").ok_or(anyhow!("no body found"))?.to_string();
+                .as_str()
+                .map(ToOwned::to_owned);
+            // null when the PR has no description
+            let body = obj
+                .get("body")
+                .ok_or(anyhow!("no body found"))?
+                .as_str()
+                .map(ToOwned::to_owned);
 
             Ok(RelatedPr {
+                repo: repo.to_owned(),
                 url,
                 author: Some(author),
                 pr_id,
                 author_link: Some(author_link),
-                title: Some(title),
-                body: Some(body),
+                title,
+                body,
                 merge_commit: Some(sha.into()),
                 is_pr: true,
             })
         }
         None => {
-            let obj = request_github(&format!(
-                "https://api.github.com/repos/{repo}/commits/{sha}"
-            ))?;
+            let obj = request_github(&format!("{}/repos/{repo}/commits/{sha}", api_url()))?;
 
             let url = obj
                 .get("html_url")
@@ -136,18 +166,11 @@ pub fn request_related_pr(repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
                 .unwrap()
                 .to_string();
 
-            let author = obj
-                .get("author")
-                .ok_or(anyhow!("no user found"))?
-                .get("login")
-                .ok_or(anyhow!("no login found"))?
-                .as_str()
-                .unwrap()
-                .to_string();
-
-            let author_link = format!("https://github.com/{}", author);
+            let (author, author_link) =
+                user_of(obj.get("author").ok_or(anyhow!("no user found"))?)?;
 
             Ok(RelatedPr {
+                repo: repo.to_owned(),
                 url,
                 author: Some(author),
                 pr_id: sha[..7].into(),
@@ -161,28 +184,47 @@ pub fn request_related_pr(repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
     }
 }
 
+#[cached(result = true)]
+pub fn default_branch(repo: String) -> anyhow::Result<String> {
+    let json = request_github(&format!("{}/repos/{repo}", api_url()))?;
+
+    let branch = json
+        .get("default_branch")
+        .ok_or(anyhow!("no default_branch found"))?
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    Ok(branch)
+}
+
 pub fn diff_link(repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
     let base = format!("https://github.com/{repo}");
 
-    let link = match &diff_tags.prev {
+    let link = match diff_tags.prev_tag() {
         Some(prev) => {
-            format!("{base}/compare/{prev}...{}", diff_tags.new)
+            format!("{base}/compare/{prev}...{}", diff_tags.new_tag())
         }
         None => {
-            format!("{base}/commits/{}", diff_tags.new)
+            format!("{base}/tree/{}", diff_tags.new_tag())
         }
     };
 
     Ok(link)
 }
 
+pub fn unreleased_link(repo: &str, last: &str) -> anyhow::Result<String> {
+    Ok(format!("https://github.com/{repo}/compare/{last}...HEAD"))
+}
+
 pub fn release_link(repo: &str, tag: &str) -> anyhow::Result<String> {
     Ok(format!("https://github.com/{repo}/releases/tag/{tag}"))
 }
 
 pub fn milestone_prs(repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
     let json = request_github(&format!(
-        "https://api.github.com/search/issues?q=repo:{repo}+is:pr+is:merged+milestone:{milestone}"
+        "{}/search/issues?q=repo:{repo}+is:pr+is:merged+milestone:{milestone}",
+        api_url()
     ))?;
 
     let array = json
@@ -209,33 +251,29 @@ pub fn milestone_prs(repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedP
 
         let pr_id = format!("#{}", pr_id);
 
-        let author = obj
-            .get("user")
-            .ok_or(anyhow!("no user found"))?
-            .get("login")
-            .ok_or(anyhow!("no login found"))?
-            .as_str()
-            .unwrap()
-            .to_string();
-
-        let author_link = format!("https://github.com/{}", author);
+        let (author, author_link) = user_of(obj.get("user").ok_or(anyhow!("no user found"))?)?;
 
         let title = obj
             .get("title")
             .ok_or(anyhow!("no title found"))?
+            .as_str()
+            .unwrap()
             .to_string();
+        // null when the PR has no description
         let body = obj
             .get("body")
-            .ok_or(anyhow!("no title found"))?
-            .to_string();
+            .ok_or(anyhow!("no body found"))?
+            .as_str()
+            .map(ToOwned::to_owned);
 
         res.push(RelatedPr {
+            repo: repo.to_owned(),
             url,
             pr_id,
             author: Some(author),
             author_link: Some(author_link),
             title: Some(title),
-            body: Some(body),
+            body,
             merge_commit: None,
             is_pr: true,
         });
@@ -244,12 +282,13 @@ pub fn milestone_prs(repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedP
     Ok(res)
 }
 
-pub fn last_prs(repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+pub fn last_prs(repo: &str, base_branch: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
     let query = r##"
 {
   repository(name: "#name", owner: "#owner") {
     pullRequests(
       first: #first
+      baseRefName: "#base"
       states: MERGED
       orderBy: { field: UPDATED_AT, direction: DESC }
     ) {
@@ -260,6 +299,7 @@ pub fn last_prs(repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
         url
         author {
           login
+          url
         }
         mergeCommit {
           oid
@@ -277,6 +317,7 @@ pub fn last_prs(repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
     interpolate.interpolate("name", &repo.name);
     interpolate.interpolate("owner", &repo.owner);
     interpolate.interpolate("first", &n.to_string());
+    interpolate.interpolate("base", base_branch);
 
     let value = request_github_graphql(&interpolate.text())?;
 
@@ -315,6 +356,7 @@ pub fn last_prs(repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
     #[derive(Debug, Deserialize)]
     struct Author {
         login: String,
+        url: Option<String>,
     }
 
     #[derive(Debug, Deserialize)]
@@ -331,9 +373,10 @@ pub fn last_prs(repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
         .nodes
         .into_iter()
         .map(|e| RelatedPr {
+            repo: format!("{}/{}", repo.owner, repo.name),
             url: e.url,
             pr_id: format!("#{}", e.number),
-            author_link: Some(format!("https://github.com/{}", e.author.login)),
+            author_link: Some(e.author.url.unwrap_or(profile_link(&e.author.login))),
             author: Some(e.author.login),
             title: Some(e.title),
             body: Some(e.body),
@@ -345,12 +388,192 @@ pub fn last_prs(repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
     Ok(res)
 }
 
+pub fn create_or_update_release(repo: &str, release: &GitRelease) -> anyhow::Result<String> {
+    let Ok(token) = env::var("GITHUB_TOKEN") else {
+        bail!("GITHUB_TOKEN is needed to publish a release");
+    };
+
+    let api = api_url();
+
+    publish_release(&api, &token, repo, release)
+}
+
+fn publish_release(
+    api: &str,
+    token: &str,
+    repo: &str,
+    release: &GitRelease,
+) -> anyhow::Result<String> {
+    let client = Client::new();
+
+    let request = |builder: RequestBuilder| {
+        builder
+            .header(USER_AGENT, "my-github-client")
+            .bearer_auth(token)
+            .send()
+    };
+
+    let check = |response: Response, action: &str| -> anyhow::Result<Value> {
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response.json()?);
+        }
+
+        let text = response.text().unwrap_or_default();
+
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                bail!("GitHub API refused to {action}, check GITHUB_TOKEN: {status}")
+            }
+            _ => bail!("GitHub API returned status {status} while trying to {action}: {text}"),
+        }
+    };
+
+    let tag = &release.tag;
+
+    let response = request(client.get(format!("{api}/repos/{repo}/git/ref/tags/{tag}")))?;
+    if response.status() == StatusCode::NOT_FOUND {
+        bail!("The tag {tag} doesn't exist in {repo}. Push it before publishing the release");
+    }
+    check(response, "find the tag")?;
+
+    let json = json!({
+        "tag_name": tag,
+        "name": release.name,
+        "body": release.body,
+        "draft": release.draft,
+        "prerelease": release.prerelease,
+    });
+
+    let response = request(client.get(format!("{api}/repos/{repo}/releases/tags/{tag}")))?;
+
+    let obj = if response.status() == StatusCode::NOT_FOUND {
+        debug!("creating the release {tag}");
+
+        let response = request(
+            client
+                .post(format!("{api}/repos/{repo}/releases"))
+                .json(&json),
+        )?;
+        check(response, "create the release")?
+    } else {
+        let existing = check(response, "find the release")?;
+
+        if !release.overwrite {
+            bail!("The release {tag} already exists");
+        }
+
+        let id = existing
+            .get("id")
+            .ok_or(anyhow!("no id found"))?
+            .as_u64()
+            .unwrap();
+
+        debug!("updating the release {tag}");
+
+        let response = request(
+            client
+                .patch(format!("{api}/repos/{repo}/releases/{id}"))
+                .json(&json),
+        )?;
+        check(response, "update the release")?
+    };
+
+    let url = obj
+        .get("html_url")
+        .ok_or(anyhow!("no html_url found"))?
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    Ok(url)
+}
+
+/// Releases listed per request, the maximum of the api.
+const RELEASES_PER_PAGE: usize = 100;
+
+pub fn list_releases(repo: &str) -> anyhow::Result<Vec<PublishedRelease>> {
+    let api = api_url();
+
+    fetch_releases(&api, repo)
+}
+
+fn fetch_releases(api: &str, repo: &str) -> anyhow::Result<Vec<PublishedRelease>> {
+    #[derive(Debug, Deserialize)]
+    struct Release {
+        tag_name: String,
+        name: Option<String>,
+        published_at: Option<String>,
+        body: Option<String>,
+        draft: bool,
+        prerelease: bool,
+    }
+
+    let mut releases = Vec::new();
+
+    for page in 1.. {
+        let value = request_github(&format!(
+            "{api}/repos/{repo}/releases?per_page={RELEASES_PER_PAGE}&page={page}"
+        ))?;
+
+        let page = serde_json::value::from_value::<Vec<Release>>(value)?;
+        let is_last = page.len() < RELEASES_PER_PAGE;
+
+        releases.extend(page.into_iter().map(|e| {
+            PublishedRelease {
+                tag: e.tag_name,
+                name: e.name.filter(|name| !name.trim().is_empty()),
+                // like "2024-01-02T10:00:00Z"
+                date: e
+                    .published_at
+                    .and_then(|date| date.get(..10).map(ToOwned::to_owned)),
+                body: e.body.unwrap_or_default(),
+                draft: e.draft,
+                prerelease: e.prerelease,
+            }
+        }));
+
+        if is_last {
+            break;
+        }
+    }
+
+    Ok(releases)
+}
+
+pub fn pr_link(repo: &str, number: &str) -> String {
+    format!("https://github.com/{repo}/pull/{number}")
+}
+
+pub fn profile_link(login: &str) -> String {
+    format!("https://github.com/{login}")
+}
+
+/// Login and profile link of a user object of the api.
+/// The profile link is built from the login when the `html_url` is missing.
+fn user_of(user: &Value) -> anyhow::Result<(String, String)> {
+    let login = user
+        .get("login")
+        .and_then(Value::as_str)
+        .ok_or(anyhow!("no login found"))?;
+
+    let link = match user.get("html_url").and_then(Value::as_str) {
+        Some(link) if !link.is_empty() => link.to_owned(),
+        _ => profile_link(login),
+    };
+
+    Ok((login.to_owned(), link))
+}
+
 pub fn offline_related_pr(repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
     Some(RelatedPr {
+        repo: repo.to_owned(),
         url: format!("https://github.com/{repo}/commit/{}", raw_commit.sha),
         pr_id: raw_commit.sha[..7].into(),
         author: Some(raw_commit.author.clone()),
-        author_link: Some(format!("https://github.com/{}", raw_commit.author)),
+        // built from the login by the generation
+        author_link: None,
         title: Some(raw_commit.title.clone()),
         body: Some(raw_commit.body.clone()),
         merge_commit: Some(raw_commit.sha.clone()),
@@ -360,9 +583,26 @@ pub fn offline_related_pr(repo: &str, raw_commit: &RawCommit) -> Option<RelatedP
 
 #[cfg(test)]
 mod test {
+    use crate::integration_test::mock_server;
 
     use super::*;
 
+    #[test]
+    fn user_without_html_url() {
+        let (login, link) = user_of(&json!({ "login": "alice" })).unwrap();
+        assert_eq!(login, "alice");
+        assert_eq!(link, "https://github.com/alice");
+
+        let (_, link) = user_of(&json!({
+            "login": "alice",
+            "html_url": "https://github.example.com/alice"
+        }))
+        .unwrap();
+        assert_eq!(link, "https://github.example.com/alice");
+
+        user_of(&json!({ "html_url": "https://github.com/alice" })).unwrap_err();
+    }
+
     #[ignore = "403"]
     #[test]
     fn pr() {
@@ -382,13 +622,14 @@ mod test {
             &DiffTags {
                 prev: None,
                 new: Version::new(0, 1, 0),
+                tag_prefix: String::new(),
             },
         )
         .unwrap();
 
         assert_eq!(
             res,
-            "https://github.com/wiiznokes/fan-control/commits/0.1.0".to_owned()
+            "https://github.com/wiiznokes/fan-control/tree/0.1.0".to_owned()
         );
 
         let res = diff_link(
@@ -396,6 +637,7 @@ mod test {
             &DiffTags {
                 prev: Some(Version::new(0, 1, 0)),
                 new: Version::new(0, 1, 1),
+                tag_prefix: String::new(),
             },
         )
         .unwrap();
@@ -417,8 +659,145 @@ mod test {
     #[ignore = "403"]
     #[test]
     fn lasts() {
-        let res = last_prs("iced-rs/iced", 3).unwrap();
+        let res = last_prs("iced-rs/iced", "master", 3).unwrap();
 
         dbg!(&res);
     }
+
+    #[ignore = "403"]
+    #[test]
+    fn default_branch() {
+        let res = super::default_branch("iced-rs/iced".into()).unwrap();
+
+        assert_eq!(res, "master");
+    }
+
+    fn release() -> GitRelease {
+        GitRelease {
+            tag: "v1.4.0".into(),
+            name: "1.4.0".into(),
+            body: "notes".into(),
+            draft: false,
+            prerelease: true,
+            overwrite: true,
+        }
+    }
+
+    #[test]
+    fn related_prs_stop_on_rate_limit() {
+        let (api, server) = mock_server(vec![
+            (429, r#"{"message": "API rate limit exceeded"}"#),
+            (200, "[]"),
+        ]);
+        MOCK_API_URL.set(Some(api.clone()));
+
+        let shas = ["0000001", "0000002", "0000003"].map(String::from);
+        let e = GitProvider::Github
+            .related_prs("a/b", &shas, 1)
+            .unwrap_err();
+        assert!(crate::git_provider::is_rate_limited(&e));
+
+        // the next request is this one, not the one of another commit
+        Client::new().get(format!("{api}/done")).send().unwrap();
+
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                "GET /repos/a/b/commits/0000001/pulls HTTP/1.1",
+                "GET /done HTTP/1.1"
+            ]
+        );
+    }
+
+    const RELEASE: &str = r#"{"id": 7, "html_url": "https://github.com/a/b/releases/tag/v1.4.0"}"#;
+
+    #[test]
+    fn publish_create() {
+        let (api, server) = mock_server(vec![(200, "{}"), (404, "{}"), (201, RELEASE)]);
+
+        let url = publish_release(&api, "token", "a/b", &release()).unwrap();
+        assert_eq!(url, "https://github.com/a/b/releases/tag/v1.4.0");
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0], "GET /repos/a/b/git/ref/tags/v1.4.0 HTTP/1.1");
+        assert_eq!(requests[1], "GET /repos/a/b/releases/tags/v1.4.0 HTTP/1.1");
+        assert!(requests[2].starts_with("POST /repos/a/b/releases HTTP/1.1 {"));
+        assert!(requests[2].contains(r#""body":"notes""#));
+        assert!(requests[2].contains(r#""prerelease":true"#));
+    }
+
+    #[test]
+    fn publish_update() {
+        let (api, server) = mock_server(vec![(200, "{}"), (200, RELEASE), (200, RELEASE)]);
+
+        publish_release(&api, "token", "a/b", &release()).unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[2].starts_with("PATCH /repos/a/b/releases/7 HTTP/1.1 {"));
+
+        let (api, server) = mock_server(vec![(200, "{}"), (200, RELEASE)]);
+
+        let mut release = release();
+        release.overwrite = false;
+        let e = publish_release(&api, "token", "a/b", &release).unwrap_err();
+        assert_eq!(e.to_string(), "The release v1.4.0 already exists");
+
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn publish_missing_tag() {
+        let (api, server) = mock_server(vec![(404, "{}")]);
+
+        let e = publish_release(&api, "token", "a/b", &release()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "The tag v1.4.0 doesn't exist in a/b. Push it before publishing the release"
+        );
+
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn releases() {
+        let (api, server) = mock_server(vec![(
+            200,
+            r#"[
+                {"tag_name": "v1.1.0", "name": "", "published_at": "2024-02-01T10:00:00Z", "body": "- fix", "draft": false, "prerelease": false},
+                {"tag_name": "v1.2.0", "name": "Next", "published_at": null, "body": null, "draft": true, "prerelease": false}
+            ]"#,
+        )]);
+
+        let releases = fetch_releases(&api, "a/b").unwrap();
+
+        assert_eq!(
+            releases,
+            vec![
+                PublishedRelease {
+                    tag: "v1.1.0".into(),
+                    name: None,
+                    date: Some("2024-02-01".into()),
+                    body: "- fix".into(),
+                    draft: false,
+                    prerelease: false,
+                },
+                PublishedRelease {
+                    tag: "v1.2.0".into(),
+                    name: Some("Next".into()),
+                    date: None,
+                    body: "".into(),
+                    draft: true,
+                    prerelease: false,
+                },
+            ]
+        );
+
+        assert_eq!(
+            server.join().unwrap(),
+            vec!["GET /repos/a/b/releases?per_page=100&page=1 HTTP/1.1"]
+        );
+
+        let (api, _) = mock_server(vec![(404, "{}")]);
+        fetch_releases(&api, "a/b").unwrap_err();
+    }
 }
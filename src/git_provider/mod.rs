@@ -1,16 +1,23 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, ops::ControlFlow};
 
 use anyhow::bail;
 use changelog::Version;
 
-use crate::repository::RawCommit;
+use crate::{
+    repository::{tag_name, RawCommit, Repository},
+    utils,
+};
 
+mod bitbucket;
 mod github;
+mod gitlab;
 
 #[derive(clap::ValueEnum, Debug, Clone, Default, PartialEq, Eq)]
 pub enum GitProvider {
     #[default]
     Github,
+    Gitlab,
+    Bitbucket,
     None,
 }
 // todo: use derive_more::Display when this issue is resolved
@@ -19,6 +26,8 @@ impl Display for GitProvider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             GitProvider::Github => write!(f, "github"),
+            GitProvider::Gitlab => write!(f, "gitlab"),
+            GitProvider::Bitbucket => write!(f, "bitbucket"),
             GitProvider::None => write!(f, "none "),
         }
     }
@@ -26,34 +35,151 @@ impl Display for GitProvider {
 
 #[derive(Debug, Clone)]
 pub struct RelatedPr {
+    /// The repo of the PR. Example: 'wiiznokes/changen'.
+    pub repo: String,
     pub url: String,
     pub pr_id: String,
     pub author: Option<String>,
     pub author_link: Option<String>,
     pub title: Option<String>,
+    /// The description of the PR, in markdown. `None` when it is empty, or not a PR.
     pub body: Option<String>,
     pub merge_commit: Option<String>,
     pub is_pr: bool,
 }
 
+/// A release to publish on the git provider.
+#[derive(Debug, Clone)]
+pub struct GitRelease {
+    pub tag: String,
+    pub name: String,
+    pub body: String,
+    pub draft: bool,
+    pub prerelease: bool,
+    /// Update the body of an existing release.
+    pub overwrite: bool,
+}
+
+/// A release published on the git provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedRelease {
+    pub tag: String,
+    pub name: Option<String>,
+    /// Publication date, like "2024-01-02". Drafts are not published.
+    pub date: Option<String>,
+    /// Notes of the release, in markdown.
+    pub body: String,
+    pub draft: bool,
+    pub prerelease: bool,
+}
+
+/// The provider refused a request because too many were made. Retrying later can succeed.
+#[derive(Debug)]
+pub struct RateLimited {
+    pub provider: GitProvider,
+}
+
+impl Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The rate limit of the {} api is exceeded", self.provider)
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Whether `e` is a refusal of the provider to answer more requests.
+pub fn is_rate_limited(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<RateLimited>().is_some()
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Url of the mock server answering the api requests of the current thread.
+    pub static MOCK_API_URL: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+}
+
 /// Represent two or one tag to produce a diff link.
 #[derive(Debug, Clone)]
 pub struct DiffTags {
     pub prev: Option<Version>,
     pub new: Version,
+    /// Prepended to the versions to get the tag names. Example: "v".
+    pub tag_prefix: String,
 }
 
 impl GitProvider {
     pub fn related_pr(&self, repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
         match self {
             GitProvider::Github => github::request_related_pr(repo, sha),
+            GitProvider::Gitlab | GitProvider::Bitbucket => {
+                bail!("The {self} api is not supported yet")
+            }
             GitProvider::None => bail!("No git provider was selected"),
         }
     }
 
+    /// The PRs of the commits `shas`, requested on at most `concurrency` threads.
+    /// A commit without PR, or whose request failed, is not in the map. The failures are
+    /// logged in the order of `shas`. Fail if the rate limit is exceeded, without sending
+    /// the requests which were not started yet.
+    pub fn related_prs(
+        &self,
+        repo: &str,
+        shas: &[String],
+        concurrency: usize,
+    ) -> anyhow::Result<HashMap<String, RelatedPr>> {
+        #[cfg(test)]
+        let mock_api_url = MOCK_API_URL.with_borrow(Clone::clone);
+
+        let results = utils::map_concurrent(shas, concurrency, |sha| {
+            #[cfg(test)]
+            MOCK_API_URL.set(mock_api_url.clone());
+
+            match self.related_pr(repo, sha) {
+                Err(e) if is_rate_limited(&e) => ControlFlow::Break(Err(e)),
+                result => ControlFlow::Continue(result),
+            }
+        });
+
+        let mut related_prs = HashMap::new();
+
+        for (sha, result) in shas.iter().zip(results) {
+            match result {
+                Some(Ok(related_pr)) => {
+                    related_prs.insert(sha.clone(), related_pr);
+                }
+                Some(Err(e)) if is_rate_limited(&e) => return Err(e),
+                Some(Err(e)) => warn!("error while requesting pr link: {}", e),
+                // skipped after the rate limit
+                None => {}
+            }
+        }
+
+        Ok(related_prs)
+    }
+
     pub fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
         match self {
             GitProvider::Github => github::diff_link(repo, diff_tags),
+            GitProvider::Gitlab => gitlab::diff_link(repo, diff_tags),
+            GitProvider::Bitbucket => bitbucket::diff_link(repo, diff_tags),
+            GitProvider::None => bail!("No git provider was selected"),
+        }
+    }
+
+    pub fn unreleased_link(
+        &self,
+        repo: &str,
+        last: &Version,
+        tag_prefix: &str,
+    ) -> anyhow::Result<String> {
+        let last = tag_name(tag_prefix, last);
+        let last = last.as_str();
+
+        match self {
+            GitProvider::Github => github::unreleased_link(repo, last),
+            GitProvider::Gitlab => gitlab::unreleased_link(repo, last),
+            GitProvider::Bitbucket => bitbucket::unreleased_link(repo, last),
             GitProvider::None => bail!("No git provider was selected"),
         }
     }
@@ -61,20 +187,90 @@ impl GitProvider {
     pub fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String> {
         match self {
             GitProvider::Github => github::release_link(repo, tag),
+            GitProvider::Gitlab => gitlab::release_link(repo, tag),
+            GitProvider::Bitbucket => bitbucket::release_link(repo, tag),
             GitProvider::None => bail!("No git provider was selected"),
         }
     }
 
-    pub fn milestone_prs(&self, repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+    /// Profile page of a user, built from its login.
+    pub fn profile_link(&self, login: &str) -> Option<String> {
         match self {
-            GitProvider::Github => github::milestone_prs(repo, milestone),
+            GitProvider::Github => Some(github::profile_link(login)),
+            GitProvider::Gitlab => Some(gitlab::profile_link(login)),
+            GitProvider::Bitbucket => Some(bitbucket::profile_link(login)),
+            GitProvider::None => None,
+        }
+    }
+
+    /// Page of a PR of `repo`, built from its number.
+    pub fn pr_link(&self, repo: &str, number: &str) -> Option<String> {
+        match self {
+            GitProvider::Github => Some(github::pr_link(repo, number)),
+            GitProvider::Gitlab => Some(gitlab::pr_link(repo, number)),
+            GitProvider::Bitbucket => Some(bitbucket::pr_link(repo, number)),
+            GitProvider::None => None,
+        }
+    }
+
+    /// PRs of the milestone named `milestone` in each of the `repos`.
+    pub fn milestone_prs(&self, repos: &[&str], milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        let mut prs = Vec::new();
+
+        for repo in repos {
+            match self {
+                GitProvider::Github => prs.extend(github::milestone_prs(repo, milestone)?),
+                GitProvider::Gitlab | GitProvider::Bitbucket => {
+                    bail!("The {self} api is not supported yet")
+                }
+                GitProvider::None => bail!("No git provider was selected"),
+            }
+        }
+
+        Ok(prs)
+    }
+
+    pub fn default_branch(&self, repo: &str) -> anyhow::Result<String> {
+        match self {
+            GitProvider::Github => github::default_branch(repo.to_owned()),
+            GitProvider::Gitlab | GitProvider::Bitbucket => {
+                bail!("The {self} api is not supported yet")
+            }
             GitProvider::None => bail!("No git provider was selected"),
         }
     }
 
-    pub fn last_prs(&self, repo: &str, n: usize) -> anyhow::Result<HashMap<String, RelatedPr>> {
+    /// Ask the provider, then git, and use `fallback` if both failed.
+    pub fn resolve_default_branch<R: Repository>(
+        &self,
+        r: &R,
+        repo: Option<&str>,
+        fallback: &str,
+    ) -> String {
+        if let Some(repo) = repo {
+            match self.default_branch(repo) {
+                Ok(branch) => return branch,
+                Err(e) => debug!("error while requesting the default branch: {e}"),
+            }
+        }
+
+        r.default_branch().unwrap_or_else(|| {
+            debug!("couldn't detect the default branch, using {fallback}");
+            fallback.to_owned()
+        })
+    }
+
+    pub fn last_prs(
+        &self,
+        repo: &str,
+        base_branch: &str,
+        n: usize,
+    ) -> anyhow::Result<HashMap<String, RelatedPr>> {
         let prs = match self {
-            GitProvider::Github => github::last_prs(repo, n),
+            GitProvider::Github => github::last_prs(repo, base_branch, n),
+            GitProvider::Gitlab | GitProvider::Bitbucket => {
+                bail!("The {self} api is not supported yet")
+            }
             GitProvider::None => bail!("No git provider was selected"),
         }?;
 
@@ -87,10 +283,38 @@ impl GitProvider {
         Ok(hashmap)
     }
 
+    /// Create the release, or update it if it already exists. Return the link of the release.
+    pub fn create_or_update_release(
+        &self,
+        repo: &str,
+        release: &GitRelease,
+    ) -> anyhow::Result<String> {
+        match self {
+            GitProvider::Github => github::create_or_update_release(repo, release),
+            GitProvider::Gitlab | GitProvider::Bitbucket => {
+                bail!("The {self} api is not supported yet")
+            }
+            GitProvider::None => bail!("No git provider was selected"),
+        }
+    }
+
+    /// The releases of the repo, the newest first.
+    pub fn list_releases(&self, repo: &str) -> anyhow::Result<Vec<PublishedRelease>> {
+        match self {
+            GitProvider::Github => github::list_releases(repo),
+            GitProvider::Gitlab | GitProvider::Bitbucket => {
+                bail!("The {self} api is not supported yet")
+            }
+            GitProvider::None => bail!("No git provider was selected"),
+        }
+    }
+
     /// Fallback function
     pub fn offline_related_pr(&self, repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
         match self {
             GitProvider::Github => github::offline_related_pr(repo, raw_commit),
+            GitProvider::Gitlab => gitlab::offline_related_pr(repo, raw_commit),
+            GitProvider::Bitbucket => bitbucket::offline_related_pr(repo, raw_commit),
             GitProvider::None => None,
         }
     }
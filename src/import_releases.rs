@@ -0,0 +1,336 @@
+use std::str::FromStr;
+
+use changelog::{
+    utils::NO_SECTION, ChangeLog, Release, ReleaseSection, ReleaseSectionNote, Version,
+};
+
+use crate::{config::ImportReleases, git_provider::PublishedRelease};
+
+/// Add the `releases` to `changelog`, skipping the versions already in it. Return the imported versions.
+///
+/// The version of a release is its tag without `tag_prefix`, or else its name. The drafts and
+/// prereleases are skipped, unless included by the options.
+pub fn import_releases(
+    changelog: &mut ChangeLog,
+    releases: &[PublishedRelease],
+    options: &ImportReleases,
+) -> anyhow::Result<Vec<Version>> {
+    let mut imported = Vec::new();
+
+    for published in releases {
+        if published.draft && !options.include_drafts {
+            debug!("release {}: draft, skipped", published.tag);
+            continue;
+        }
+
+        if published.prerelease && !options.include_prereleases {
+            debug!("release {}: prerelease, skipped", published.tag);
+            continue;
+        }
+
+        let tag = published.tag.trim();
+        let version = Version::from_str(tag.strip_prefix(&options.tag_prefix).unwrap_or(tag))
+            .ok()
+            .or_else(|| {
+                let name = published.name.as_deref()?.trim();
+                Version::from_str(name.strip_prefix(&options.tag_prefix).unwrap_or(name)).ok()
+            });
+
+        let Some(version) = version else {
+            warn!(
+                "release {}: its tag and name are not versions, skipped",
+                published.tag
+            );
+            continue;
+        };
+
+        if changelog.releases.contains_key(&version) {
+            debug!("release {version}: already in the changelog");
+            continue;
+        }
+
+        let release = release_from_body(
+            &version.to_string(),
+            published.date.as_deref(),
+            &published.body,
+        )?;
+
+        changelog.releases.insert(version.clone(), release);
+        imported.push(version);
+    }
+
+    Ok(imported)
+}
+
+/// Convert the markdown notes of a published release.
+///
+/// A heading starts a section titled like it, whatever its level, and the items of a list are its notes,
+/// with their indented lines as context. The items before any heading are notes without section.
+/// Everything else, code blocks included, is kept in order in the header of the release.
+/// The prose of a heading without notes is preceded by the title of the heading, in bold.
+pub fn release_from_body(version: &str, date: Option<&str>, body: &str) -> anyhow::Result<Release> {
+    let body = body.replace("\r\n", "\n");
+
+    let mut header: Vec<String> = Vec::new();
+    let mut sections: Vec<ReleaseSection> = Vec::new();
+    // title, and whether the prose under it is already titled in the header
+    let mut heading: Option<(String, bool)> = None;
+    // section of the last note, while its context can follow
+    let mut note_section: Option<usize> = None;
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            if let Some(title) = heading_title(line) {
+                heading = Some((title, false));
+                note_section = None;
+                continue;
+            }
+
+            if let Some(index) =
+                note_section.filter(|_| line.starts_with([' ', '\t']) && !trimmed.is_empty())
+            {
+                let context = line.strip_prefix("  ").unwrap_or(trimmed);
+                let note = sections[index].notes.last_mut().unwrap();
+                note.context.push(context.to_owned());
+                continue;
+            }
+
+            if let Some(message) = ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|bullet| trimmed.strip_prefix(bullet))
+            {
+                // the prose after the notes of a heading is not titled by it
+                if let Some((_, written)) = &mut heading {
+                    *written = true;
+                }
+
+                let title = heading.as_ref().map_or(NO_SECTION, |(title, _)| title);
+
+                // the notes of a section stay together, in the order of their first appearance
+                let index = match sections.iter().position(|s| s.title == title) {
+                    Some(index) => index,
+                    None => {
+                        sections.push(ReleaseSection::new(title));
+                        sections.len() - 1
+                    }
+                };
+
+                sections[index]
+                    .notes
+                    .push(ReleaseSectionNote::new(message.trim()));
+                note_section = Some(index);
+                continue;
+            }
+        }
+
+        note_section = None;
+
+        if let Some((title, written)) = &mut heading {
+            if !*written && !trimmed.is_empty() {
+                header.push(format!("**{title}**"));
+                header.push(String::new());
+                *written = true;
+            }
+        }
+
+        if !in_fence && trimmed.is_empty() && header.last().is_none_or(String::is_empty) {
+            continue;
+        }
+
+        header.push(line.trim_end().to_owned());
+    }
+
+    let header = header.join("\n").trim().to_owned();
+
+    let mut builder = Release::builder(version);
+
+    if let Some(date) = date {
+        builder = builder.date(date);
+    }
+
+    if !header.is_empty() {
+        builder = builder.header(header);
+    }
+
+    // the notes without section must come first, or they would be read back in the previous section
+    sections.sort_by_key(|section| section.title != NO_SECTION);
+
+    for section in sections {
+        builder = builder.section(section);
+    }
+
+    builder.build()
+}
+
+/// Text of a markdown heading, like "Fixed" for "## Fixed".
+fn heading_title(line: &str) -> Option<String> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+
+    if !(1..=6).contains(&level) || !line[level..].starts_with(' ') {
+        return None;
+    }
+
+    let title = line[level..].trim().trim_end_matches('#').trim();
+
+    if title.is_empty() {
+        None
+    } else {
+        Some(title.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::{de::parse_changelog, ser::serialize_changelog};
+    use pretty_assertions::assert_eq;
+
+    use crate::git_provider::GitProvider;
+
+    use super::*;
+
+    fn options() -> ImportReleases {
+        ImportReleases {
+            file: None,
+            provider: GitProvider::Github,
+            repo: Some("wiiznokes/fan-control".into()),
+            tag_prefix: "v".into(),
+            include_drafts: false,
+            include_prereleases: false,
+            stdout: false,
+        }
+    }
+
+    fn published(tag: &str, date: Option<&str>, body: &str) -> PublishedRelease {
+        PublishedRelease {
+            tag: tag.into(),
+            name: None,
+            date: date.map(Into::into),
+            body: body.into(),
+            draft: false,
+            prerelease: false,
+        }
+    }
+
+    /// What the provider answers, the newest first.
+    fn releases() -> Vec<PublishedRelease> {
+        vec![
+            PublishedRelease {
+                draft: true,
+                ..published("v0.4.0", None, "- wip")
+            },
+            PublishedRelease {
+                prerelease: true,
+                ..published("v0.4.0-beta.1", Some("2024-05-10"), "- try the new ui")
+            },
+            published(
+                "v0.3.0",
+                Some("2024-05-01"),
+                include_str!("../tests/import_releases/v0.3.0.md"),
+            ),
+            published(
+                "v0.2.0",
+                Some("2024-04-19"),
+                include_str!("../tests/import_releases/v0.2.0.md"),
+            ),
+            PublishedRelease {
+                name: Some("0.1.5".into()),
+                ..published("nightly", Some("2024-04-10"), "- more logs")
+            },
+            published("v0.1.0", Some("2024-04-05"), "Already in the changelog."),
+        ]
+    }
+
+    #[test]
+    fn import() {
+        let mut changelog =
+            parse_changelog(include_str!("../tests/import_releases/CHANGELOG.md")).unwrap();
+
+        let imported = import_releases(&mut changelog, &releases(), &options()).unwrap();
+
+        assert_eq!(
+            imported.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["0.3.0", "0.2.0", "0.1.5"]
+        );
+
+        let output = serialize_changelog(&changelog, &Default::default());
+
+        assert_eq!(
+            output,
+            include_str!("../tests/import_releases/CHANGELOG.expect.md")
+        );
+
+        // the result can be read back
+        assert_eq!(
+            serialize_changelog(&parse_changelog(&output).unwrap(), &Default::default()),
+            output
+        );
+    }
+
+    #[test]
+    fn include_drafts_and_prereleases() {
+        let mut changelog = ChangeLog::new();
+
+        let mut options = options();
+        options.include_drafts = true;
+        options.include_prereleases = true;
+
+        let imported = import_releases(&mut changelog, &releases(), &options).unwrap();
+
+        assert_eq!(imported[0].to_string(), "0.4.0");
+        assert_eq!(imported[1].to_string(), "0.4.0-beta.1");
+        assert_eq!(changelog.releases.len(), 6);
+    }
+
+    #[test]
+    fn sections() {
+        let release = release_from_body(
+            "1.0.0",
+            None,
+            "- first\r\n\r\n### Fixed\r\n\r\n* crash\r\n  on resume\r\n* leak\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(release.header, None);
+        assert_eq!(release.note_sections[NO_SECTION].notes.len(), 1);
+        assert_eq!(
+            release.note_sections["Fixed"].notes,
+            vec![
+                ReleaseSectionNote::new("crash").context("on resume"),
+                ReleaseSectionNote::new("leak"),
+            ]
+        );
+    }
+
+    #[test]
+    fn free_form() {
+        let release = release_from_body(
+            "1.0.0",
+            Some("2024-01-02"),
+            "Faster.\n\n\n```sh\n- not a note\n## nor a heading\n```\n\n## Thanks\nTo everyone.",
+        )
+        .unwrap();
+
+        assert!(release.note_sections.is_empty());
+        assert_eq!(
+            release.header.as_deref(),
+            Some("Faster.\n\n```sh\n- not a note\n## nor a heading\n```\n\n**Thanks**\n\nTo everyone.")
+        );
+
+        // the code block is read back in the header, not as a note
+        let mut changelog = ChangeLog::new();
+        changelog
+            .releases
+            .insert(Version::from_str("1.0.0").unwrap(), release.clone());
+
+        let output = serialize_changelog(&changelog, &Default::default());
+        let read_back = parse_changelog(&output).unwrap();
+
+        assert_eq!(read_back.releases().next(), Some(&release));
+        assert_eq!(serialize_changelog(&read_back, &Default::default()), output);
+    }
+}
@@ -0,0 +1,185 @@
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use crate::{
+    backfill::{backfill_changelog, footer_links},
+    config::Backfill,
+    git_provider::{GitProvider, MOCK_API_URL},
+};
+
+use super::*;
+
+fn repo() -> FsTest {
+    let dated_tag = |name: &str, sha: &str, date: &str| Tag {
+        date: Some(date.to_owned()),
+        ..tag(name, sha)
+    };
+
+    FsTest {
+        commits: vec![
+            raw_commit("feat: a", "000"),
+            raw_commit("release 0.1.0", "001"),
+            raw_commit("fix: b", "002"),
+            raw_commit("release 0.2.0", "003"),
+            raw_commit("feat: c", "004"),
+            raw_commit("release 0.3.0", "005"),
+        ],
+        tags: vec![
+            dated_tag("0.1.0", "001", "2024-01-01"),
+            dated_tag("0.2.0", "003", "2024-02-01"),
+            dated_tag("0.3.0", "005", "2024-03-01"),
+        ],
+        notes: vec![],
+    }
+}
+
+fn backfill_options() -> Backfill {
+    Backfill {
+        file: None,
+        force: false,
+        from: None,
+        to: None,
+        map: None,
+        exclude_unidentified: true,
+        provider: GitProvider::None,
+        repo: None,
+        stdout: true,
+        tag_filter: None,
+        tag_prefix: String::new(),
+    }
+}
+
+#[test]
+fn backfill_all_tags() {
+    let output = backfill_changelog(&repo(), &backfill_options()).unwrap();
+
+    let expected = format!(
+        "{}
+## [Unreleased]
+
+## [0.3.0] - 2024-03-01
+
+### Added
+
+- c
+
+## [0.2.0] - 2024-02-01
+
+### Fixed
+
+- b
+
+## [0.1.0] - 2024-01-01
+
+### Added
+
+- a
+",
+        include_str!("../../res/CHANGELOG_DEFAULT.md")
+    );
+
+    assert_eq!(output, expected);
+    parse_changelog(&output).unwrap();
+}
+
+#[test]
+fn backfill_range() {
+    let mut options = backfill_options();
+    options.from = Some(Version::new(0, 2, 0));
+    options.to = Some(Version::new(0, 2, 0));
+
+    let output = backfill_changelog(&repo(), &options).unwrap();
+
+    assert!(output.ends_with("## [Unreleased]\n\n## [0.2.0] - 2024-02-01\n\n### Fixed\n\n- b\n"));
+}
+
+#[test]
+fn backfill_tag_filter() {
+    let mut r = repo();
+    r.tags.push(Tag {
+        date: Some("2024-01-15".into()),
+        ..tag("0.2.0-nightly.1", "002")
+    });
+
+    let mut options = backfill_options();
+    options.tag_filter = Some(Regex::new(r"^\d+\.\d+\.\d+$").unwrap());
+
+    let output = backfill_changelog(&r, &options).unwrap();
+
+    assert!(!output.contains("nightly"));
+    assert!(output.contains("## [0.2.0] - 2024-02-01\n\n### Fixed\n\n- b\n"));
+}
+
+#[test]
+fn backfill_tag_prefix() {
+    let mut r = repo();
+    for tag in &mut r.tags {
+        tag.name = format!("v{}", tag.name);
+    }
+
+    let mut options = backfill_options();
+    options.tag_prefix = "v".into();
+
+    let output = backfill_changelog(&r, &options).unwrap();
+
+    assert!(output.contains("## [0.2.0] - 2024-02-01\n\n### Fixed\n\n- b\n"));
+
+    let links = footer_links(
+        &parse_changelog(&output).unwrap(),
+        &GitProvider::Github,
+        "a/b",
+        "v",
+    )
+    .unwrap()
+    .into_iter()
+    .map(|link| link.link)
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+        links,
+        vec![
+            "https://github.com/a/b/compare/v0.3.0...HEAD",
+            "https://github.com/a/b/compare/v0.2.0...v0.3.0",
+            "https://github.com/a/b/compare/v0.1.0...v0.2.0",
+            "https://github.com/a/b/tree/v0.1.0",
+        ]
+    );
+}
+
+#[test]
+fn backfill_resume_on_rate_limit() {
+    let (api, server) = mock_server(vec![
+        (200, r#"{"default_branch": "main"}"#),
+        (
+            200,
+            r#"{"data": {"repository": {"pullRequests": {"nodes": []}}}}"#,
+        ),
+        (429, r#"{"message": "API rate limit exceeded"}"#),
+    ]);
+    MOCK_API_URL.set(Some(api));
+
+    let mut options = backfill_options();
+    options.provider = GitProvider::Github;
+    options.repo = Some("rate/limited".into());
+
+    // the links of the commits show 7 characters of their sha
+    let mut r = repo();
+    for commit in &mut r.commits {
+        commit.sha = format!("{:0>7}", commit.sha);
+    }
+    for tag in &mut r.tags {
+        tag.sha = format!("{:0>7}", tag.sha);
+    }
+
+    let output = backfill_changelog(&r, &options).unwrap();
+
+    // the release generated before the rate limit is kept
+    assert!(output.contains("## [0.1.0] - 2024-01-01\n\n### Added\n\n- a in [0000000]"));
+    assert!(!output.contains("## [0.2.0]"));
+    assert!(!output.contains("## [0.3.0]"));
+
+    let requests = server.join().unwrap();
+    assert_eq!(requests[0], "GET /repos/rate/limited HTTP/1.1");
+    assert!(requests[1].starts_with("POST /graphql HTTP/1.1"));
+    assert!(requests[2].starts_with("POST /graphql HTTP/1.1"));
+}
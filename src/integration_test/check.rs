@@ -0,0 +1,81 @@
+use changelog::de::parse_changelog;
+
+use crate::{check::check, config::Check};
+
+use super::*;
+
+fn repo() -> FsTest {
+    FsTest {
+        commits: vec![
+            raw_commit("feat: new button (#12)", "0000001"),
+            raw_commit("fix: typo !changelog", "0000002"),
+            raw_commit("fix: crash", "0000003"),
+            RawCommit {
+                body: "Release-Note: skip".into(),
+                ..raw_commit("ci: cache", "0000004")
+            },
+        ],
+        tags: vec![],
+        notes: vec![],
+    }
+}
+
+fn options(rev: Option<&str>) -> Check {
+    Check {
+        file: None,
+        require_unreleased_entry: true,
+        require_entry_for: rev.map(Into::into),
+        pr: None,
+        skip_trailer: "Changelog".into(),
+    }
+}
+
+#[test]
+fn empty_unreleased() {
+    let changelog = parse_changelog("## [Unreleased]\n").unwrap();
+
+    let e = check(&repo(), &changelog, &options(None)).unwrap_err();
+    assert_eq!(e.to_string(), "The Unreleased section has no entry.");
+}
+
+#[test]
+fn entry_for_commit() {
+    let changelog = parse_changelog(
+        "## [Unreleased]\n\n### Added\n\n- new button in [#12](https://github.com/a/b/pull/12)\n",
+    )
+    .unwrap();
+
+    check(&repo(), &changelog, &options(Some("0000001"))).unwrap();
+
+    let e = check(&repo(), &changelog, &options(Some("0000003"))).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "No entry of the Unreleased section references 0000003, for the commit 0000003."
+    );
+
+    let mut options = options(Some("0000003"));
+    options.pr = Some("1".into());
+    let e = check(&repo(), &changelog, &options).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "No entry of the Unreleased section references #1, for the commit 0000003."
+    );
+}
+
+#[test]
+fn skip_marker() {
+    let changelog = parse_changelog("## [Unreleased]\n\n### Added\n\n- a\n").unwrap();
+
+    check(&repo(), &changelog, &options(Some("0000002"))).unwrap();
+}
+
+#[test]
+fn skip_trailer() {
+    let changelog = parse_changelog("## [Unreleased]\n\n### Added\n\n- a\n").unwrap();
+
+    check(&repo(), &changelog, &options(Some("0000004"))).unwrap_err();
+
+    let mut options = options(Some("0000004"));
+    options.skip_trailer = "Release-Note".into();
+    check(&repo(), &changelog, &options).unwrap();
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+
+use crate::{config::MapMessageToSection, fmt::format};
+
+use super::*;
+
+#[test]
+fn messy() {
+    let input = read_file("src/integration_test/fmt/messy.init").unwrap();
+    let expected = read_file("src/integration_test/fmt/messy.expect").unwrap();
+
+    let output = format(
+        &input,
+        MapMessageToSection::default(),
+        false,
+        &HashMap::new(),
+    )
+    .unwrap();
+
+    assert_eq!(output, expected);
+
+    let output = format(
+        &output,
+        MapMessageToSection::default(),
+        false,
+        &HashMap::new(),
+    )
+    .unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn refuse_unparsable() {
+    format(
+        "## [Unreleased]\n\n## [Unreleased]\n",
+        MapMessageToSection::default(),
+        false,
+        &HashMap::new(),
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn keep_release_section_order() {
+    let input = "## [Unreleased]
+
+### Fixed
+
+- b
+
+### Added
+
+- a
+
+## [1.0.0]
+
+### Fixed
+
+- d
+
+### Added
+
+- c
+";
+
+    let output = format(
+        input,
+        MapMessageToSection::default(),
+        false,
+        &HashMap::new(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        output,
+        "## [Unreleased]
+
+### Added
+
+- a
+
+### Fixed
+
+- b
+
+## [1.0.0]
+
+### Fixed
+
+- d
+
+### Added
+
+- c
+"
+    );
+
+    let output = format(input, MapMessageToSection::default(), true, &HashMap::new()).unwrap();
+
+    assert!(output.ends_with("## [1.0.0]\n\n### Added\n\n- c\n\n### Fixed\n\n- d\n"));
+}
+
+#[test]
+fn section_headings() {
+    let headings = HashMap::from([("Added".to_owned(), "✨ Features".to_owned())]);
+
+    let input = "## [Unreleased]
+
+### Fixed
+
+- b
+
+### ✨ Features
+
+- a
+
+### Added
+
+- c
+";
+
+    let output = format(input, MapMessageToSection::default(), false, &headings).unwrap();
+
+    assert_eq!(
+        output,
+        "## [Unreleased]
+
+### ✨ Features
+
+- a
+- c
+
+### Fixed
+
+- b
+"
+    );
+}
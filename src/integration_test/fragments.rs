@@ -0,0 +1,116 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use pretty_assertions::assert_eq;
+
+use crate::{
+    config::{Cli, Commands, ImportFragments},
+    git_provider::GitProvider,
+    run_generic,
+};
+
+use super::*;
+
+fn import(dir: &Path, keep: bool, output: Option<&str>) {
+    let cli = Cli {
+        command: Commands::ImportFragments(ImportFragments {
+            file: Some(dir.join("CHANGELOG.md")),
+            dir: dir.join("changes"),
+            map: None,
+            keep,
+            provider: GitProvider::Github,
+            repo: Some("a/b".into()),
+            stdout: false,
+        }),
+        output: output.map(PathBuf::from),
+        verify_roundtrip: false,
+        quiet: false,
+        verbose: 0,
+    };
+
+    let r = FsTest {
+        commits: vec![],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    run_generic(&r, cli).unwrap();
+}
+
+fn setup(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("changes")).unwrap();
+
+    fs::write(
+        dir.join("CHANGELOG.md"),
+        include_str!("../../tests/fragments/CHANGELOG.md"),
+    )
+    .unwrap();
+
+    fs::write(dir.join("changes/12.feature.md"), "dark theme\n").unwrap();
+    fs::write(
+        dir.join("changes/13.bugfix.md"),
+        "crash on resume\n\nThe worker could panic.\n",
+    )
+    .unwrap();
+    fs::write(dir.join("changes/+tests.misc.md"), "more tests\n").unwrap();
+    fs::write(dir.join("changes/.gitkeep"), "").unwrap();
+
+    dir
+}
+
+#[test]
+fn collect_fragments() {
+    let dir = setup("changen_collect_fragments");
+
+    import(&dir, false, None);
+
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        include_str!("../../tests/fragments/CHANGELOG.expect.md")
+    );
+
+    // the consumed fragments are deleted, not the other files
+    let mut left = fs::read_dir(dir.join("changes"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    left.sort();
+    assert_eq!(left, vec![".gitkeep"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn collect_fragments_keep() {
+    let dir = setup("changen_collect_fragments_keep");
+
+    import(&dir, true, None);
+
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        include_str!("../../tests/fragments/CHANGELOG.expect.md")
+    );
+    assert_eq!(fs::read_dir(dir.join("changes")).unwrap().count(), 4);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn collect_fragments_to_stdout() {
+    let dir = setup("changen_collect_fragments_to_stdout");
+
+    import(&dir, false, Some("-"));
+
+    // printed only: the fragments are still pending
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        include_str!("../../tests/fragments/CHANGELOG.md")
+    );
+    assert_eq!(fs::read_dir(dir.join("changes")).unwrap().count(), 4);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
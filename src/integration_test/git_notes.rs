@@ -0,0 +1,66 @@
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use crate::{generate::generate, output::render_to_string};
+
+use super::*;
+
+fn repo() -> FsTest {
+    FsTest {
+        commits: vec![
+            raw_commit("feat: a", "000"),
+            raw_commit("wip", "001"),
+            raw_commit("fix: c", "002"),
+        ],
+        tags: vec![],
+        notes: vec![
+            ("001".into(), "fix(ui): curated message".into()),
+            ("002".into(), "feat: c, but better\n\nmore context".into()),
+        ],
+    }
+}
+
+#[test]
+fn notes_override_message() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.git_notes = true;
+
+    let output =
+        render_to_string(&generate(&repo(), parse_changelog("").unwrap(), &options).unwrap());
+
+    assert_eq!(
+        output,
+        "## [Unreleased]
+
+### Added
+
+- a
+- c, but better
+
+### Fixed
+
+- ui: curated message
+"
+    );
+}
+
+#[test]
+fn notes_ignored_by_default() {
+    let output = render_to_string(
+        &generate(&repo(), parse_changelog("").unwrap(), &DEFAULT_GENERATE).unwrap(),
+    );
+
+    assert_eq!(
+        output,
+        "## [Unreleased]
+
+### Added
+
+- a
+
+### Fixed
+
+- c
+"
+    );
+}
@@ -0,0 +1,144 @@
+use std::fs;
+
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use crate::{
+    config::{Cli, Commands, New},
+    git_provider::GitProvider,
+    new::new_changelog,
+    run_generic,
+};
+
+use super::*;
+
+fn new_options() -> New {
+    New {
+        path: None,
+        force: false,
+        tags: false,
+        backfill: false,
+        provider: GitProvider::None,
+        repo: None,
+        tag_filter: None,
+        tag_prefix: String::new(),
+    }
+}
+
+fn repo() -> FsTest {
+    FsTest {
+        commits: vec![
+            raw_commit("feat: 1", "000"),
+            raw_commit("fix: 2", "001"),
+            raw_commit("feat: 3", "002"),
+        ],
+        tags: vec![tag("0.1.0", "001"), tag("0.2.0", "002")],
+        notes: vec![],
+    }
+}
+
+#[test]
+fn init_without_tags() {
+    let r = FsTest {
+        commits: vec![raw_commit("feat: 1", "000")],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    let mut options = new_options();
+    options.tags = true;
+    options.repo = Some("wiiznokes/changen".into());
+
+    let output = new_changelog(&r, &options).unwrap();
+
+    let expected = format!(
+        "{}\n## [Unreleased]\n",
+        include_str!("../../res/CHANGELOG_DEFAULT.md")
+    );
+
+    assert_eq!(output, expected);
+    parse_changelog(&output).unwrap();
+}
+
+#[test]
+fn init_with_tags() {
+    let mut options = new_options();
+    options.tags = true;
+    options.provider = GitProvider::Github;
+    options.repo = Some("wiiznokes/changen".into());
+
+    let output = new_changelog(&repo(), &options).unwrap();
+
+    assert!(output.ends_with(
+        "## [Unreleased]
+
+## [0.2.0]
+
+## [0.1.0]
+
+[Unreleased]: https://github.com/wiiznokes/changen/compare/0.2.0...HEAD
+[0.2.0]: https://github.com/wiiznokes/changen/compare/0.1.0...0.2.0
+[0.1.0]: https://github.com/wiiznokes/changen/tree/0.1.0
+"
+    ));
+
+    let changelog = parse_changelog(&output).unwrap();
+    assert_eq!(changelog.releases.len(), 2);
+}
+
+#[test]
+fn init_backfill() {
+    let mut options = new_options();
+    options.backfill = true;
+
+    let output = new_changelog(&repo(), &options).unwrap();
+
+    assert!(output.ends_with(
+        "## [0.1.0]
+
+### Added
+
+- 1
+
+### Fixed
+
+- 2
+"
+    ));
+}
+
+#[test]
+fn init_refuse_overwrite() {
+    let dir = std::env::temp_dir().join("changen_init_refuse_overwrite");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("CHANGELOG.md");
+
+    fs::write(&path, "# My changelog\n").unwrap();
+
+    let mut options = new_options();
+    options.path = Some(path.clone());
+
+    let cli = Cli {
+        command: Commands::New(options.clone()),
+        output: None,
+        verify_roundtrip: false,
+        quiet: false,
+        verbose: 0,
+    };
+    run_generic(&repo(), cli).unwrap_err();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# My changelog\n");
+
+    options.force = true;
+
+    let cli = Cli {
+        command: Commands::New(options),
+        output: None,
+        verify_roundtrip: false,
+        quiet: false,
+        verbose: 0,
+    };
+    run_generic(&repo(), cli).unwrap();
+    parse_changelog(&fs::read_to_string(&path).unwrap()).unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+}
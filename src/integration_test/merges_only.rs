@@ -0,0 +1,45 @@
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use crate::{generate::generate, output::render_to_string};
+
+use super::*;
+
+#[test]
+fn merges_only() {
+    let merge_commit = |title: &str, body: &str, sha: &str| RawCommit {
+        body: body.to_owned(),
+        is_merge: true,
+        ..raw_commit(title, sha)
+    };
+
+    let r = FsTest {
+        commits: vec![
+            raw_commit("feat: a", "000"),
+            merge_commit("Merge pull request #2 from a/b", "fix: b\n\ndetails", "001"),
+            raw_commit("feat: wip", "002"),
+            merge_commit("feat: c (#3)", "", "003"),
+        ],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    let mut options = DEFAULT_GENERATE.clone();
+    options.merges_only = true;
+
+    let output = render_to_string(&generate(&r, parse_changelog("").unwrap(), &options).unwrap());
+
+    assert_eq!(
+        output,
+        "## [Unreleased]
+
+### Added
+
+- c (#3)
+
+### Fixed
+
+- b
+"
+    );
+}
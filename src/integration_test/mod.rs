@@ -1,23 +1,45 @@
-use std::{collections::VecDeque, fs::File, io::Read, path::Path, str::FromStr, sync::LazyLock};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::Path,
+    sync::LazyLock,
+    thread::{self, JoinHandle},
+};
 
 use changelog::{de::parse_changelog, ChangeLog, Version};
+use regex::Regex;
 
 use crate::{
-    config::{CommitMessageParsing, Generate},
-    git_provider::GitProvider,
-    repository::{Period, RawCommit, Repository},
+    config::{BodyMode, CommitMessageParsing, Generate, OutputFormat, ScopePolicy},
+    git_provider::{GitProvider, RelatedPr},
+    repository::{tag_version, Period, RawCommit, Repository},
 };
 
+mod backfill;
+mod check;
+mod fmt;
+mod fragments;
+mod git_notes;
+mod init;
+mod merges_only;
+mod path_filter;
+mod report;
+mod require_pr;
 mod test1;
 
 struct Tag {
     pub name: String,
     pub sha: String,
+    pub date: Option<String>,
 }
 
 struct FsTest {
     pub commits: Vec<RawCommit>,
     pub tags: Vec<Tag>,
+    /// (sha, note)
+    pub notes: Vec<(String, String)>,
 }
 
 impl Repository for FsTest {
@@ -61,6 +83,26 @@ impl Repository for FsTest {
             .clone()
     }
 
+    fn commit_is_merge(&self, sha: &str) -> bool {
+        self.commits.iter().find(|e| e.sha == sha).unwrap().is_merge
+    }
+
+    fn commit_date(&self, sha: &str) -> Option<String> {
+        self.commits
+            .iter()
+            .find(|e| e.sha == sha)
+            .unwrap()
+            .date
+            .clone()
+    }
+
+    fn commit_note(&self, sha: &str) -> Option<String> {
+        self.notes
+            .iter()
+            .find(|(e, _)| e == sha)
+            .map(|(_, note)| note.clone())
+    }
+
     fn commits_between_tags(&self, tags: &Period) -> Vec<String> {
         let mut res = Vec::new();
 
@@ -84,54 +126,210 @@ impl Repository for FsTest {
                     None => self.commits.iter().position(|e| e.sha == repo_ref).unwrap(),
                 },
             )
+            .map(|pos| pos + 1)
             .unwrap_or(self.commits.len());
 
-        for e in &self.commits[start..=end] {
+        for e in &self.commits[start..end] {
             res.push(e.sha.clone());
         }
         res
     }
 
-    fn tags_list(&self) -> anyhow::Result<VecDeque<Version>> {
-        Ok(self
+    fn tags_list(
+        &self,
+        filter: Option<&Regex>,
+        tag_prefix: &str,
+    ) -> anyhow::Result<VecDeque<Version>> {
+        let mut tags = self
             .tags
             .iter()
-            .filter_map(|e| Version::from_str(&e.name).ok())
-            .collect())
+            .filter(|e| filter.is_none_or(|filter| filter.is_match(&e.name)))
+            .filter_map(|e| tag_version(tag_prefix, &e.name))
+            .collect::<Vec<_>>();
+
+        tags.sort();
+
+        Ok(tags.into())
+    }
+
+    fn tag_date(&self, tag: &str) -> Option<String> {
+        self.tags
+            .iter()
+            .find(|e| e.name == tag)
+            .and_then(|e| e.date.clone())
+    }
+
+    fn default_branch(&self) -> Option<String> {
+        None
+    }
+
+    fn is_clean(&self) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+
+    fn create_tag(&self, _tag: &str, _message: &str) -> anyhow::Result<()> {
+        Ok(())
     }
 }
 
-static DEFAULT_GENERATE: LazyLock<Generate> = LazyLock::new(|| Generate {
+pub static DEFAULT_GENERATE: LazyLock<Generate> = LazyLock::new(|| Generate {
     file: None,
     map: None,
     parsing: CommitMessageParsing::Smart,
+    multiline_body: BodyMode::Ignore,
     exclude_unidentified: true,
+    prefer_pr_title: false,
     exclude_not_pr: false,
+    require_pr: false,
+    skip_released: false,
+    git_notes: false,
+    merges_only: false,
+    path_filter: None,
+    exclude_globs: vec![],
+    section_trailer: "Changelog-Section".into(),
+    skip_trailer: "Changelog".into(),
+    message_trailer: "Changelog".into(),
+    context_footers: vec![],
+    group_by_pr: false,
+    pr_concurrency: 8,
+    deprecation_keywords: vec!["deprecate".into(), "deprecated".into()],
+    allowed_scopes: None,
+    scope_policy: ScopePolicy::Warn,
+    promote_scope_to_section: false,
+    strict_sections: false,
+    allowed_sections: vec![],
+    section_headings: vec![],
+    sort_notes_by_date: false,
     provider: GitProvider::None,
     repo: None,
     omit_pr_link: false,
+    pr_link_format: "in [{id}]({url})".into(),
     omit_thanks: false,
+    thanks_only_external: false,
+    internal_authors: vec![],
+    author_link_template: None,
+    default_branch: "main".into(),
     stdout: false,
+    dry_run: false,
+    output_format: OutputFormat::Text,
+    step_summary: false,
     specific: None,
     milestone: None,
+    milestone_repos: vec![],
+    prefer_pr_changelog_section: false,
+    pr_changelog_heading: "Changelog".into(),
     since: None,
     until: None,
+    tag_prefix: String::new(),
 });
 
 fn raw_commit(title: &str, sha: &str) -> RawCommit {
     RawCommit {
         author: "wiiznokes".to_owned(),
-        title: title.to_owned(),
-        body: "".to_owned(),
         sha: sha.to_owned(),
+        ..raw(title, "")
+    }
+}
+
+/// A commit without author nor sha.
+pub fn raw(title: &str, body: &str) -> RawCommit {
+    RawCommit {
+        author: "".to_owned(),
+        title: title.to_owned(),
+        body: body.to_owned(),
+        sha: "".to_owned(),
         list_files: vec![],
+        is_merge: false,
+        date: None,
     }
 }
 
+/// The pull request `id` of "a/b", like "#3", on GitHub.
+pub fn pr(id: &str, author: Option<&str>) -> RelatedPr {
+    RelatedPr {
+        repo: "a/b".to_owned(),
+        url: format!("https://github.com/a/b/pull/{}", id.trim_start_matches('#')),
+        pr_id: id.to_owned(),
+        author: author.map(ToOwned::to_owned),
+        author_link: None,
+        title: None,
+        body: None,
+        merge_commit: None,
+        is_pr: true,
+    }
+}
+
+/// Answer each request with the next response, and return the received requests.
+pub fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+    mock_server_with(
+        responses
+            .into_iter()
+            .map(|(status, body)| (status, "application/json", body))
+            .collect(),
+    )
+}
+
+/// Like [`mock_server`], with the content type of each response.
+pub fn mock_server_with(
+    responses: Vec<(u16, &'static str, &'static str)>,
+) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+
+        for (status, content_type, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request = String::new();
+            let mut content_length = 0;
+
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+
+                if line == "\r\n" {
+                    break;
+                }
+                if request.is_empty() {
+                    request = line.trim().to_owned();
+                }
+            }
+
+            let mut content = vec![0; content_length];
+            reader.read_exact(&mut content).unwrap();
+            request.push(' ');
+            request.push_str(&String::from_utf8(content).unwrap());
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {status} X\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+
+            requests.push(request.trim().to_owned());
+        }
+
+        requests
+    });
+
+    (url, handle)
+}
+
 fn tag(name: &str, sha: &str) -> Tag {
     Tag {
         name: name.to_owned(),
         sha: sha.to_owned(),
+        date: None,
     }
 }
 
@@ -0,0 +1,49 @@
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use crate::{generate::generate, output::render_to_string};
+
+use super::*;
+
+#[test]
+fn exclude_docs_only() {
+    let touching = |title: &str, sha: &str, files: &[&str]| RawCommit {
+        list_files: files.iter().map(ToString::to_string).collect(),
+        ..raw_commit(title, sha)
+    };
+
+    let r = FsTest {
+        commits: vec![
+            touching(
+                "feat: code and docs",
+                "000",
+                &["pkg/src/lib.rs", "pkg/README.md"],
+            ),
+            touching("fix: typo", "001", &["pkg/README.md", "pkg/docs/guide.md"]),
+            touching("feat: other package", "002", &["other/src/lib.rs"]),
+            touching("fix: crash", "003", &["pkg/src/main.rs"]),
+        ],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    let mut options = DEFAULT_GENERATE.clone();
+    options.path_filter = Some("pkg".into());
+    options.exclude_globs = vec!["*.md".into()];
+
+    let output = render_to_string(&generate(&r, parse_changelog("").unwrap(), &options).unwrap());
+
+    assert_eq!(
+        output,
+        "## [Unreleased]
+
+### Added
+
+- code and docs
+
+### Fixed
+
+- crash
+"
+    );
+}
@@ -0,0 +1,98 @@
+use changelog::de::{parse_changelog, parse_changelog_with_warnings, Options};
+use pretty_assertions::assert_eq;
+
+use crate::{
+    config::Generate,
+    generate::generate,
+    report::{to_json, GeneratedNote, GenerationReport, ValidateReport, SCHEMA_VERSION},
+};
+
+use super::*;
+
+#[test]
+fn generation_report() {
+    let repo = FsTest {
+        commits: vec![
+            raw_commit("feat: a", "000"),
+            raw_commit("fix(ui): b", "001"),
+        ],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    let before = parse_changelog("## [Unreleased]\n\n### Added\n\n- a\n").unwrap();
+    let after = generate(&repo, before.clone(), &DEFAULT_GENERATE).unwrap();
+
+    let output = to_json(&GenerationReport::new(&before, &after)).unwrap();
+
+    let report: GenerationReport = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(report.schema_version, SCHEMA_VERSION);
+    assert_eq!(report.notes.len(), 1);
+    assert_eq!(report.notes[0].section, "Fixed");
+    assert_eq!(report.notes[0].note.scope.as_deref(), Some("ui"));
+    assert_eq!(report.notes[0].note.message, "b");
+
+    assert!(output.contains("\"section\": \"Fixed\",\n      \"scope\": \"ui\""));
+
+    assert_eq!(
+        GeneratedNote::summary(&report.notes),
+        "### Fixed\n\n- ui: b\n"
+    );
+
+    let preview = GeneratedNote::preview(&report.notes);
+    assert_eq!(
+        preview,
+        "\u{1b}[1m### Fixed\u{1b}[0m\n\n\u{1b}[32m- ui: b\u{1b}[0m\n"
+    );
+    assert_eq!(
+        anstream::adapter::strip_str(&preview).to_string(),
+        GeneratedNote::summary(&report.notes)
+    );
+}
+
+#[test]
+fn specific_commit_report() {
+    let repo = FsTest {
+        commits: vec![raw_commit("fix(ui): b", "001")],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    let options = Generate {
+        specific: Some("001".into()),
+        ..DEFAULT_GENERATE.clone()
+    };
+
+    let before = parse_changelog("## [Unreleased]\n").unwrap();
+    let after = generate(&repo, before.clone(), &options).unwrap();
+
+    let report = GenerationReport::new(&before, &after);
+
+    assert_eq!(report.notes.len(), 1);
+    assert_eq!(
+        report.notes[0].added_message(),
+        "Release note:\n- ui: b\nsuccessfully added in the Fixed section."
+    );
+}
+
+#[test]
+fn validate_report() {
+    let (_, warnings) = parse_changelog_with_warnings(
+        "## [1.0.0]\n\n### Added\n\n- a\n\n### Added\n\n- b\n",
+        &Options::default(),
+    )
+    .unwrap();
+
+    let output = to_json(&ValidateReport {
+        schema_version: SCHEMA_VERSION,
+        diagnostics: warnings.clone(),
+    })
+    .unwrap();
+
+    let report: ValidateReport = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(report.diagnostics, warnings);
+    assert_eq!(report.diagnostics[0].rule, "duplicate-section");
+    assert_eq!(report.diagnostics[0].line, 7);
+}
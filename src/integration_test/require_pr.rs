@@ -0,0 +1,33 @@
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use crate::generate::generate;
+
+use super::*;
+
+#[test]
+fn require_pr() {
+    let r = FsTest {
+        commits: vec![
+            raw_commit("feat: a", "0001234567"),
+            raw_commit("fix: b", "0011234567"),
+        ],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    let mut options = DEFAULT_GENERATE.clone();
+    options.require_pr = true;
+
+    let err = generate(&r, parse_changelog("").unwrap(), &options).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "2 commit(s) not attached to a pull request:
+- 0001234 feat: a
+- 0011234 fix: b"
+    );
+
+    options.require_pr = false;
+    generate(&r, parse_changelog("").unwrap(), &options).unwrap();
+}
@@ -1,4 +1,4 @@
-use crate::generate::generate;
+use crate::{generate::generate, output::render_to_string};
 use pretty_assertions::assert_eq;
 
 use super::*;
@@ -23,13 +23,14 @@ fn test_repo() {
             tag("0.1.1", "004"),
             tag("0.2.1", "005"),
         ],
+        notes: vec![],
     };
 
     options.until = Some("004".into());
 
     let changelog = read_changelog("src/integration_test/test1/test1.init").unwrap();
 
-    let output = generate(&r, changelog, &options).unwrap();
+    let output = render_to_string(&generate(&r, changelog, &options).unwrap());
 
     let expected = read_file("src/integration_test/test1/test1.expect").unwrap();
 
@@ -37,3 +38,19 @@ fn test_repo() {
 
     assert_eq!(output, expected);
 }
+
+#[test]
+fn empty_repo() {
+    let r = FsTest {
+        commits: vec![],
+        tags: vec![],
+        notes: vec![],
+    };
+
+    let period = Period {
+        since: None,
+        until: None,
+    };
+
+    assert!(r.commits_between_tags(&period).is_empty());
+}
@@ -1,35 +1,80 @@
-#![feature(btree_extract_if)]
-#![feature(if_let_guard)]
-#![feature(let_chains)]
-
-use core::str;
 use std::{
-    borrow::Cow,
-    fs::{File, OpenOptions},
+    collections::HashMap,
+    fs::{self, File},
     io::{self, IsTerminal, Read, Write},
     path::{Path, PathBuf},
 };
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 use changelog::{
-    de::parse_changelog,
-    ser::{serialize_changelog, serialize_release, OptionsRelease},
+    de::{parse_changelog, parse_changelog_with_options, parse_changelog_with_warnings},
+    ser::OptionsRelease,
+    ChangeLog,
+};
+use config::{
+    Cli, Commands, DocumentFormat, Export, MapMessageToSection, Merge, OutputFormat, Validate,
 };
-use config::{Cli, Commands, MapMessageToSection, New, Remove, Show, Validate};
 use generate::generate;
+use git_provider::GitRelease;
+use log::{Level, LevelFilter};
+use report::{
+    DiffReport, GeneratedNote, GenerationReport, SearchReport, StatsReport, ValidateReport,
+};
 use repository::{Fs, Repository};
 use utils::try_get_repo;
 
+pub use commit_parser::{parse_commit, FormattedCommit};
+pub use diff::{diff_releases, ModifiedNote, ReleaseDiff, SectionDiff};
+pub use error::GenerationError;
+pub use generate::{explain_section, SectionDecision};
+pub use remote::parse_change_log_from_url;
+pub use summarize::summarize_release;
+pub use validate_config::{validate_config, ConfigProblem};
+pub use workspace::{
+    parse_changelogs_matching, parse_workspace_changelogs, WORKSPACE_CHANGELOG_GLOB,
+};
+
 #[macro_use]
 extern crate log;
 
+mod amend;
+mod appstream;
+mod backfill;
+mod bump;
+mod check;
 mod commit_parser;
 pub mod config;
+mod convert;
+mod debian;
+mod diff;
+mod error;
+mod export;
+mod feed;
+mod fmt;
+mod fragments;
 mod generate;
 mod git_provider;
+mod import_releases;
+mod links;
+mod merge;
+mod new;
+mod output;
+mod plain;
 mod release;
+mod remote;
+mod remove;
+mod report;
 mod repository;
+mod rpm;
+mod search;
+mod show;
+mod stats;
+mod summarize;
+mod tag_message;
 mod utils;
+mod validate_config;
+mod workspace;
+mod yank;
 
 #[cfg(test)]
 mod test_res;
@@ -44,13 +89,18 @@ fn get_changelog_path(path: Option<PathBuf>) -> PathBuf {
 fn read_file(path: &Path) -> anyhow::Result<String> {
     let mut buf = String::new();
 
+    if path == Path::new(STDIO) {
+        io::stdin().read_to_string(&mut buf)?;
+        return Ok(buf);
+    }
+
     let mut from_stdin = !io::stdin().is_terminal();
 
     if from_stdin {
         io::stdin().read_to_string(&mut buf)?;
 
         if buf.is_empty() {
-            info!("Read stdin because is was not a terminal, but it is empty. Fallback to file.");
+            debug!("Read stdin because is was not a terminal, but it is empty. Fallback to file.");
             from_stdin = false;
         }
     }
@@ -63,14 +113,95 @@ fn read_file(path: &Path) -> anyhow::Result<String> {
     Ok(buf)
 }
 
-fn write_output(output: &str, path: &Path, stdout: bool) -> anyhow::Result<()> {
+/// Diagnostics go to stderr, so that stdout only contains the documents and reports.
+/// `RUST_LOG` takes precedence over `level`.
+pub fn init_logger(level: LevelFilter) {
+    env_logger::Builder::new()
+        .filter_level(level.min(LevelFilter::Warn))
+        .filter_module("changen", level)
+        .filter_module("changelog", level)
+        .parse_default_env()
+        .target(env_logger::Target::Stderr)
+        .format(|buf, record| match record.level() {
+            Level::Error => writeln!(buf, "error: {}", record.args()),
+            Level::Warn => writeln!(buf, "warning: {}", record.args()),
+            Level::Info => writeln!(buf, "{}", record.args()),
+            Level::Debug | Level::Trace => writeln!(
+                buf,
+                "[{} {}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ),
+        })
+        .init();
+}
+
+/// Path meaning the standard input or output.
+const STDIO: &str = "-";
+
+/// Where the document is written: the `--output` override, or `path`.
+/// `None` means the standard output.
+fn destination<'a>(path: &'a Path, stdout: bool, output: Option<&'a Path>) -> Option<&'a Path> {
+    let path = output.unwrap_or(path);
+
     // !io::stdout().is_terminal()
     // won't work on Github action because stdout is piped somehow.
-    if stdout {
-        print!("{output}")
+    if stdout || path == Path::new(STDIO) {
+        None
     } else {
-        let mut file = File::options().truncate(true).write(true).open(path)?;
-        file.write_all(output.as_bytes())?;
+        Some(path)
+    }
+}
+
+fn write_output(
+    changelog: &ChangeLog,
+    path: &Path,
+    stdout: bool,
+    output: Option<&Path>,
+    verify_roundtrip: bool,
+) -> anyhow::Result<()> {
+    if verify_roundtrip {
+        let rendered = output::render_to_string(changelog);
+        output::verify_roundtrip(changelog, &rendered, &HashMap::new())?;
+        return write_output_str(&rendered, path, stdout, output);
+    }
+
+    match destination(path, stdout, output) {
+        Some(path) => output::apply_to_file(path, changelog)?,
+        None => output::render_to_stdout(changelog)?,
+    }
+
+    Ok(())
+}
+
+/// Like [`write_output`], with the release options `release_option`.
+fn write_output_with(
+    changelog: &ChangeLog,
+    release_option: &OptionsRelease,
+    path: &Path,
+    stdout: bool,
+    output: Option<&Path>,
+    verify_roundtrip: bool,
+) -> anyhow::Result<()> {
+    let rendered = output::render_with(changelog, release_option);
+
+    if verify_roundtrip {
+        output::verify_roundtrip(changelog, &rendered, &release_option.section_headings)?;
+    }
+
+    write_output_str(&rendered, path, stdout, output)
+}
+
+fn write_output_str(
+    contents: &str,
+    path: &Path,
+    stdout: bool,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    match destination(path, stdout, output) {
+        Some(path) => output::write_changelog_file(path, contents)?,
+        None => print!("{contents}"),
     }
 
     Ok(())
@@ -87,16 +218,71 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
     debug!("is terminal: {}", io::stdin().is_terminal());
     debug!("is terminal stdout: {}", io::stdout().is_terminal());
 
+    let out = cli.output.as_deref();
+    let verify = cli.verify_roundtrip;
+
     match cli.command {
         Commands::Generate(mut options) => {
             let path = get_changelog_path(options.file.clone());
+
+            if options.output_format == OutputFormat::Json
+                && !options.dry_run
+                && destination(&path, options.stdout, out).is_none()
+            {
+                bail!("The JSON report can't share stdout with the changelog: write the changelog to a file, or use --dry-run");
+            }
+
             let input = read_file(&path)?;
-            let changelog = parse_changelog(&input)?;
+            let release_option = OptionsRelease {
+                section_headings: options.section_headings.iter().cloned().collect(),
+                sort_notes_by_date: options.sort_notes_by_date,
+                ..Default::default()
+            };
+            let changelog = parse_changelog_with_options(
+                &input,
+                &changelog::de::Options {
+                    section_headings: release_option.section_headings.clone(),
+                    ..Default::default()
+                },
+            )?;
             options.repo = try_get_repo(options.repo);
 
-            let output = generate(r, changelog, &options)?;
+            let before = changelog.clone();
+            let changelog = generate(r, changelog, &options)?;
+
+            if !options.dry_run {
+                write_output_with(
+                    &changelog,
+                    &release_option,
+                    &path,
+                    options.stdout,
+                    out,
+                    verify,
+                )?;
+            }
 
-            write_output(&output, &path, options.stdout)?;
+            let report = GenerationReport::new(&before, &changelog);
+
+            match options.output_format {
+                OutputFormat::Json => print!("{}", report::to_json(&report)?),
+                OutputFormat::Text if options.dry_run => write!(
+                    anstream::stdout(),
+                    "{}",
+                    GeneratedNote::preview(&report.notes)
+                )?,
+                OutputFormat::Text if options.specific.is_some() => {
+                    for added in &report.notes {
+                        info!("{}", added.added_message());
+                    }
+                }
+                OutputFormat::Text => {}
+            }
+
+            if options.step_summary {
+                if let Some(unreleased) = &changelog.unreleased {
+                    output::append_step_summary(unreleased)?;
+                }
+            }
         }
 
         Commands::Release(mut options) => {
@@ -105,11 +291,11 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
             let changelog = parse_changelog(&input)?;
             options.repo = try_get_repo(options.repo);
 
-            let (version, output) = release::release(r, changelog, &options)?;
+            let (version, changelog) = release::release(r, changelog, &options)?;
 
-            write_output(&output, &path, options.stdout)?;
+            write_output(&changelog, &path, options.stdout, out, verify)?;
 
-            eprintln!("New release {} successfully created.", version);
+            info!("New release {} successfully created.", version);
         }
 
         Commands::Validate(options) => {
@@ -118,132 +304,560 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
                 format,
                 map,
                 ast,
+                link_lifecycle,
+                footer_links,
+                fix,
+                keep_orphans,
+                check_urls,
+                provider,
+                repo,
+                tag_prefix,
                 stdout,
+                output_format,
             } = options;
 
             let path = get_changelog_path(file);
             let input = read_file(&path)?;
-            let mut changelog = parse_changelog(&input)?;
+            let (mut changelog, mut warnings) =
+                parse_changelog_with_warnings(&input, &changelog::de::Options::default())?;
 
             debug!("changelog: {:?}", changelog);
 
+            if footer_links || fix {
+                warnings.extend(links::check_footer_links(&changelog, &input));
+            }
+
+            if check_urls {
+                warnings.extend(links::check_footer_urls(&changelog, &input)?);
+            }
+
+            if fix {
+                let repo = try_get_repo(repo);
+                links::fix_footer_links(
+                    &mut changelog,
+                    &provider,
+                    repo.as_deref(),
+                    &tag_prefix,
+                    keep_orphans,
+                )?;
+            }
+
+            match output_format {
+                OutputFormat::Text => {
+                    for warning in &warnings {
+                        warn!("{warning}");
+                    }
+                }
+                OutputFormat::Json => print!(
+                    "{}",
+                    report::to_json(&ValidateReport {
+                        schema_version: report::SCHEMA_VERSION,
+                        diagnostics: warnings,
+                    })?
+                ),
+            }
+
             if ast {
-                dbg!(&changelog);
+                info!("{changelog:#?}");
+            }
+
+            if link_lifecycle {
+                for link in changelog.inject_lifecycle_links() {
+                    info!(
+                        "{}: \"{}\" was deprecated in {}",
+                        link.removed_in, link.note.message, link.deprecated_in
+                    );
+                }
             }
 
             if format {
                 let map = MapMessageToSection::try_new(map)?;
                 changelog.sanitize(&map.to_fmt_options());
-                let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
+            }
 
-                write_output(&output, &path, stdout)?;
+            if format || link_lifecycle || fix {
+                write_output(&changelog, &path, stdout, out, verify)?;
             }
 
-            eprintln!("Changelog parsed with success!");
+            info!("Changelog parsed with success!");
         }
 
         Commands::Show(options) => {
-            let Show { file, n, version } = options;
-
-            let path = get_changelog_path(file);
+            let path = get_changelog_path(options.file.clone());
             let input = read_file(&path)?;
             let changelog = parse_changelog(&input)?;
 
             debug!("changelog: {:?}", changelog);
 
-            let releases = if let Some(regex) = &version {
-                let mut res = Vec::new();
+            let output = show::show(&changelog, &options)?;
 
-                for release in changelog.releases() {
-                    if regex.is_match(release.version()) {
-                        res.push(Cow::Borrowed(release))
-                    }
+            print!("{output}");
+        }
+
+        Commands::New(mut options) => {
+            let path = get_changelog_path(options.path.clone());
+
+            if destination(&path, false, out).is_some_and(Path::exists) && !options.force {
+                bail!("Path already exist. Delete it or use the --force option");
+            }
+
+            if options.tags || options.backfill {
+                options.repo = try_get_repo(options.repo);
+            }
+
+            let changelog = new::new_changelog(r, &options)?;
+
+            write_output_str(&changelog, &path, false, out)?;
+
+            info!("Changelog successfully created!");
+        }
+        Commands::Remove(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let mut changelog = parse_changelog(&input)?;
+
+            debug!("changelog: {:?}", changelog);
+
+            for removed in remove::remove(&mut changelog, &options)? {
+                info!("Removed {removed}");
+            }
+
+            changelog.sanitize(&changelog::fmt::Options::default());
+
+            write_output(&changelog, &path, options.stdout, out, verify)?;
+        }
+        Commands::Merge(options) => {
+            let Merge { first, second } = options;
+
+            let first = parse_changelog(&fs::read_to_string(first)?)?;
+            let second = parse_changelog(&fs::read_to_string(second)?)?;
+
+            let mut changelog = merge::merge(first, second);
+
+            changelog.sanitize(&changelog::fmt::Options::default());
+
+            // without --output, print the merged changelog
+            write_output(&changelog, Path::new(STDIO), false, out, verify)?;
+        }
+        Commands::Backfill(mut options) => {
+            let path = get_changelog_path(options.file.clone());
+            let dest = destination(&path, options.stdout, out);
+
+            if dest.is_some_and(Path::exists) && !options.force {
+                bail!("Path already exist. Delete it or use the --force option");
+            }
+
+            options.repo = try_get_repo(options.repo);
+
+            let output = backfill::backfill_changelog(r, &options)?;
+
+            write_output_str(&output, &path, options.stdout, out)?;
+
+            if dest.is_some() {
+                info!("Changelog successfully created!");
+            }
+        }
+        Commands::Bump(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            let version = bump::bump(&changelog, &options, bump::today())?;
+
+            println!("{version}");
+
+            if options.apply {
+                let release_options = config::Release {
+                    file: Some(path.clone()),
+                    version: Some(version),
+                    repo: try_get_repo(None),
+                    ..Default::default()
+                };
+
+                let (version, changelog) = release::release(r, changelog, &release_options)?;
+
+                write_output(&changelog, &path, false, out, verify)?;
+
+                info!("New release {} successfully created.", version);
+            }
+        }
+        Commands::Fmt(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+
+            let map = MapMessageToSection::try_new(options.map.as_ref())?;
+            let section_headings = options.section_headings.iter().cloned().collect();
+            let output = fmt::format(&input, map, options.reorder_releases, &section_headings)?;
+
+            if options.check {
+                if input != output {
+                    eprint!("{}", fmt::diff(&input, &output));
+                    bail!("{} is not formatted", path.display());
                 }
-                res
+
+                info!("Changelog already formatted.");
             } else {
-                changelog
-                    .nth_release(n)
-                    .map(|e| e.release())
-                    .into_iter()
-                    .collect()
+                write_output_str(&output, &path, options.stdout, out)?;
+            }
+        }
+        Commands::Check(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            check::check(r, &changelog, &options)?;
+
+            info!("Changelog check passed.");
+        }
+        Commands::Yank(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+
+            let output = yank::yank(&input, &options, true)?;
+
+            write_output_str(&output, &path, options.stdout, out)?;
+
+            info!("Release {} yanked.", options.version);
+        }
+        Commands::Unyank(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+
+            let output = yank::yank(&input, &options, false)?;
+
+            write_output_str(&output, &path, options.stdout, out)?;
+
+            info!("Release {} unyanked.", options.version);
+        }
+        Commands::NormalizeLinks(mut options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let mut changelog = parse_changelog(&input)?;
+            options.repo = try_get_repo(options.repo);
+
+            let Some(repo) = &options.repo else {
+                bail!("The repo is needed to normalize the footer links");
             };
 
-            if releases.is_empty() {
-                bail!("No release found");
+            backfill::normalize_footer_links(
+                &mut changelog,
+                &options.provider,
+                repo,
+                &options.tag_prefix,
+            )?;
+
+            write_output(&changelog, &path, options.stdout, out, verify)?;
+        }
+        Commands::Export(mut options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            if options.full_changelog {
+                options.repo = try_get_repo(options.repo);
             }
 
-            for (pos, release) in releases.iter().enumerate() {
-                debug!("show release: {:?}", release);
-                let mut output = String::new();
-                serialize_release(
-                    &mut output,
-                    release,
-                    &OptionsRelease {
-                        serialize_title: false,
-                    },
-                );
+            let notes = export::export(&changelog, &options)?;
 
-                print!("{}", output);
-                if pos != releases.len() - 1 {
-                    println!();
+            // without --output, print the notes
+            write_output_str(&notes, Path::new(STDIO), false, out)?;
+        }
+        Commands::ExportAppstream(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            let releases = appstream::render_releases(&changelog)?;
+
+            match &options.metainfo {
+                Some(metainfo) => {
+                    let xml = fs::read_to_string(metainfo)?;
+                    let xml = appstream::splice_releases(&xml, &releases)?;
+
+                    write_output_str(&xml, metainfo, options.stdout, out)?;
                 }
+                None => write_output_str(&releases, Path::new(STDIO), false, out)?,
             }
         }
+        Commands::ExportDebian(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
 
-        Commands::New(options) => {
-            let New { path, force } = options;
+            let output = debian::export_debian(&changelog, &options, utils::today())?;
 
-            let path = get_changelog_path(path);
+            write_output_str(&output, Path::new(STDIO), false, out)?;
+        }
+        Commands::ExportRpm(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
 
-            if path.exists() && !force {
-                bail!("Path already exist. Delete it or use the --force option");
-            }
+            let output = rpm::export_rpm(&changelog, &options)?;
 
-            let changelog = include_str!("../res/CHANGELOG_DEFAULT.md");
+            write_output_str(&output, Path::new(STDIO), false, out)?;
+        }
+        Commands::ExportFeed(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            let output = feed::render_feed(&changelog, &options, utils::today())?;
+
+            write_output_str(&output, Path::new(STDIO), false, out)?;
+        }
+        Commands::Publish(mut options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+            options.repo = try_get_repo(options.repo);
+
+            let Some(repo) = &options.repo else {
+                bail!("The repo is needed to publish a release");
+            };
+
+            let version = match &options.version {
+                Some(version) => version.clone(),
+                None => match changelog.last_version() {
+                    Some(version) => version,
+                    None => bail!("No release found"),
+                },
+            };
 
-            let mut file = OpenOptions::new()
-                .create(true)
-                .truncate(true)
-                .write(true)
-                .open(path)?;
+            let body = export::export(
+                &changelog,
+                &Export {
+                    file: None,
+                    version: Some(version.clone()),
+                    full_changelog: false,
+                    provider: options.provider.clone(),
+                    repo: None,
+                    header: None,
+                    footer: None,
+                    tag_prefix: options.tag_prefix.clone(),
+                },
+            )?;
+
+            let release = GitRelease {
+                tag: options
+                    .tag
+                    .clone()
+                    .unwrap_or_else(|| repository::tag_name(&options.tag_prefix, &version)),
+                name: version.to_string(),
+                body,
+                draft: options.draft,
+                prerelease: options.prerelease,
+                overwrite: !options.no_overwrite,
+            };
 
-            file.write_all(changelog.as_bytes())?;
+            let url = options.provider.create_or_update_release(repo, &release)?;
 
-            println!("Changelog successfully created!");
+            info!("Release {} published: {url}", release.tag);
         }
-        Commands::Remove(options) => {
-            let Remove {
-                file,
-                stdout,
-                remove_id,
-            } = options;
+        Commands::Amend(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let mut changelog = parse_changelog(&input)?;
 
-            let path = get_changelog_path(file);
+            let report = match &options.report {
+                Some(report) => Some(serde_json::from_str::<GenerationReport>(
+                    &fs::read_to_string(report)?,
+                )?),
+                None => None,
+            };
+
+            let target = match &report {
+                Some(report) => match report.notes.last() {
+                    Some(note) => Some(note),
+                    None => bail!("The report has no note"),
+                },
+                None => None,
+            };
+
+            let section = amend::amend(&mut changelog, &options, target)?;
+
+            changelog.sanitize(&changelog::fmt::Options::default());
+
+            write_output(&changelog, &path, options.stdout, out, verify)?;
+
+            info!("Note amended, in the {section} section.");
+        }
+        Commands::ImportFragments(mut options) => {
+            let path = get_changelog_path(options.file.clone());
             let input = read_file(&path)?;
             let mut changelog = parse_changelog(&input)?;
+            options.repo = try_get_repo(options.repo);
 
-            debug!("changelog: {:?}", changelog);
+            let map = MapMessageToSection::try_new(options.map.as_ref())?;
+
+            let fragments = fragments::import_news_fragments(
+                &options.dir,
+                &map,
+                &options.provider,
+                options.repo.as_deref(),
+            )?;
+            let paths = fragments
+                .iter()
+                .map(|fragment| fragment.path.clone())
+                .collect::<Vec<_>>();
+
+            changelog
+                .unreleased_or_default()
+                .insert_release_notes(fragments::into_sections(fragments));
+
+            changelog.sanitize(&map.to_fmt_options());
+
+            write_output(&changelog, &path, options.stdout, out, verify)?;
+
+            info!("{} fragments imported.", paths.len());
+
+            // printed fragments are still pending
+            if !options.keep && destination(&path, options.stdout, out).is_some() {
+                for path in paths {
+                    fs::remove_file(&path)
+                        .with_context(|| format!("can't delete {}", path.display()))?;
+                }
+            }
+        }
+        Commands::ImportReleases(mut options) => {
+            let path = get_changelog_path(options.file.clone());
+            options.repo = try_get_repo(options.repo);
+
+            let Some(repo) = &options.repo else {
+                bail!("The repo is needed to list its releases");
+            };
 
-            if let Some(regex) = &remove_id.version {
-                changelog
-                    .releases
-                    .retain(|_, v| !regex.is_match(v.version()));
+            let mut changelog = if path.exists() {
+                parse_changelog(&read_file(&path)?)?
             } else {
-                match changelog.nth_release(remove_id.n.unwrap())?.owned() {
-                    changelog::utils::NthRelease::Unreleased(_) => {
-                        changelog.unreleased.take();
-                    }
-                    changelog::utils::NthRelease::Released(key, _) => {
-                        changelog.releases.remove(&key);
-                    }
+                parse_changelog(include_str!("../res/CHANGELOG_DEFAULT.md"))?
+            };
+
+            let releases = options.provider.list_releases(repo)?;
+
+            let imported = import_releases::import_releases(&mut changelog, &releases, &options)?;
+
+            write_output(&changelog, &path, options.stdout, out, verify)?;
+
+            info!("{} releases imported.", imported.len());
+        }
+        Commands::Convert(options) => {
+            let input = read_file(&options.input)?;
+
+            let from = match options.from {
+                Some(from) => from,
+                None if options.input == Path::new(STDIO) => {
+                    bail!("The format of the standard input can't be inferred. Use --from")
                 }
+                None => DocumentFormat::from_path(&options.input)?,
+            };
+
+            let to = match (options.to, out) {
+                (Some(to), _) => to,
+                (None, Some(out)) if out != Path::new(STDIO) => DocumentFormat::from_path(out)?,
+                (None, _) => bail!("The format of the output can't be inferred. Use --to"),
+            };
+
+            let output = convert::convert(&input, from, to)?;
+
+            // without --output, print the converted changelog
+            write_output_str(&output, Path::new(STDIO), false, out)?;
+        }
+        Commands::Stats(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            let stats = stats::stats(&changelog);
+
+            match options.output_format {
+                OutputFormat::Text => print!("{}", stats.summary()),
+                OutputFormat::Json => print!(
+                    "{}",
+                    report::to_json(&StatsReport {
+                        schema_version: report::SCHEMA_VERSION,
+                        stats,
+                    })?
+                ),
             }
+        }
+        Commands::Search(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
 
-            changelog.sanitize(&changelog::fmt::Options::default());
+            let matches = search::search(&changelog, &options);
+
+            match options.output_format {
+                OutputFormat::Text => print!("{}", search::summary(&matches)),
+                OutputFormat::Json => print!(
+                    "{}",
+                    report::to_json(&SearchReport {
+                        schema_version: report::SCHEMA_VERSION,
+                        matches,
+                    })?
+                ),
+            }
+        }
+        Commands::Summarize(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            let Some(release) = changelog.get(&options.release) else {
+                bail!("Release {} not found", options.release);
+            };
+
+            for bullet in summarize::summarize_release(release, options.max_bullets) {
+                println!("- {bullet}");
+            }
+        }
+        Commands::TagMessage(options) => {
+            let path = get_changelog_path(options.file.clone());
+            let input = read_file(&path)?;
+            let changelog = parse_changelog(&input)?;
+
+            let message = tag_message::tag_message(&changelog, &options.version)?;
+
+            if options.create_tag {
+                if !r.is_clean()? {
+                    bail!("The working tree is not clean. Commit or stash the changes first");
+                }
+
+                let tag = repository::tag_name(&options.tag_prefix, &options.version);
 
-            let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
+                r.create_tag(&tag, &message)?;
 
-            write_output(&output, &path, stdout)?;
+                info!("Tag {tag} created.");
+            } else {
+                print!("{message}");
+            }
+        }
+        Commands::Diff(options) => {
+            let diff = match (options.file.as_slice(), options.versions.as_slice()) {
+                ([old, new], []) => {
+                    let old = parse_changelog(&fs::read_to_string(old)?)?;
+                    let new = parse_changelog(&fs::read_to_string(new)?)?;
+                    diff::diff_changelogs(&old, &new)
+                }
+                ([] | [_], [old, new]) => {
+                    let path = get_changelog_path(options.file.first().cloned());
+                    let changelog = parse_changelog(&read_file(&path)?)?;
+                    diff::diff_versions(&changelog, old, new)?
+                }
+                _ => bail!("Give two versions to compare, or two files with --file"),
+            };
+
+            match options.output_format {
+                OutputFormat::Text => print!("{}", diff.summary()),
+                OutputFormat::Json => print!(
+                    "{}",
+                    report::to_json(&DiffReport {
+                        schema_version: report::SCHEMA_VERSION,
+                        diff,
+                    })?
+                ),
+            }
         }
     }
 
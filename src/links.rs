@@ -0,0 +1,326 @@
+use std::{str::FromStr, time::Duration};
+
+use anyhow::bail;
+use changelog::{de::ParseWarning, utils::UNRELEASED, ChangeLog, FooterLink, Version};
+use reqwest::{blocking::Client, header::USER_AGENT};
+
+use crate::git_provider::{DiffTags, GitProvider};
+
+/// A release without footer link.
+pub const RULE_MISSING_FOOTER_LINK: &str = "missing-footer-link";
+/// A footer link named after a version which is not a release of the changelog.
+pub const RULE_ORPHAN_FOOTER_LINK: &str = "orphan-footer-link";
+/// A footer link which can't be reached.
+pub const RULE_DEAD_FOOTER_LINK: &str = "dead-footer-link";
+
+/// Time given to each footer link to answer.
+const URL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Cross-check the footer links with the releases. `input` is the parsed text, to locate the problems.
+/// Links which are not named after a version, like `[docs]`, are references used in the notes,
+/// and are not checked.
+pub fn check_footer_links(changelog: &ChangeLog, input: &str) -> Vec<ParseWarning> {
+    let mut warnings = Vec::new();
+
+    for version in expected_links(changelog) {
+        if link_of(changelog, &version).is_none() {
+            warnings.push(ParseWarning {
+                rule: RULE_MISSING_FOOTER_LINK.into(),
+                line: line_of(input, &format!("## [{version}]")),
+                message: format!("The release {version} has no footer link"),
+            });
+        }
+    }
+
+    for link in orphan_links(changelog) {
+        warnings.push(ParseWarning {
+            rule: RULE_ORPHAN_FOOTER_LINK.into(),
+            line: line_of(input, &format!("[{}]:", link.text)),
+            message: format!(
+                "The footer link [{}] doesn't correspond to a release",
+                link.text
+            ),
+        });
+    }
+
+    warnings
+}
+
+/// Add the missing footer links with the compare links of `provider`, in the order of the releases.
+/// The orphaned links are removed, unless `keep_orphans` is set.
+pub fn fix_footer_links(
+    changelog: &mut ChangeLog,
+    provider: &GitProvider,
+    repo: Option<&str>,
+    tag_prefix: &str,
+    keep_orphans: bool,
+) -> anyhow::Result<()> {
+    let versions = changelog.releases_keys().cloned().collect::<Vec<_>>();
+    let orphans = orphan_links(changelog)
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut links = Vec::new();
+
+    for text in expected_links(changelog) {
+        if let Some(link) = link_of(changelog, &text) {
+            links.push(link.clone());
+            continue;
+        }
+
+        let Some(repo) = repo else {
+            bail!("The repo is needed to add the missing footer links");
+        };
+
+        let link = if text == UNRELEASED {
+            provider.unreleased_link(repo, &versions[0], tag_prefix)?
+        } else {
+            let pos = versions
+                .iter()
+                .position(|v| v.to_string() == text)
+                .expect("the text of an expected link is a release");
+
+            let diff_tags = DiffTags::new(versions[pos].clone(), versions.get(pos + 1).cloned())?
+                .with_tag_prefix(tag_prefix);
+
+            provider.diff_link(repo, &diff_tags)?
+        };
+
+        info!("Footer link [{text}] added");
+        links.push(FooterLink { text, link });
+    }
+
+    for link in &changelog.footer_links.links {
+        if links.iter().any(|l| l.text == link.text) {
+            continue;
+        }
+
+        if orphans.contains(link) && !keep_orphans {
+            info!("Orphaned footer link [{}] removed", link.text);
+            continue;
+        }
+
+        links.push(link.clone());
+    }
+
+    changelog.footer_links.links = links;
+
+    Ok(())
+}
+
+/// Send a HEAD request to each footer link, and report the ones which don't answer with a success.
+/// Relative links are skipped.
+pub fn check_footer_urls(changelog: &ChangeLog, input: &str) -> anyhow::Result<Vec<ParseWarning>> {
+    let client = Client::builder().timeout(URL_TIMEOUT).build()?;
+
+    let mut warnings = Vec::new();
+
+    for link in &changelog.footer_links.links {
+        if !link.link.starts_with("http://") && !link.link.starts_with("https://") {
+            debug!("skip the relative link {}", link.link);
+            continue;
+        }
+
+        let error = match client
+            .head(&link.link)
+            .header(USER_AGENT, "my-github-client")
+            .send()
+        {
+            Ok(response) if response.status().is_success() => continue,
+            Ok(response) => format!("returned status {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        warnings.push(ParseWarning {
+            rule: RULE_DEAD_FOOTER_LINK.into(),
+            line: line_of(input, &format!("[{}]:", link.text)),
+            message: format!("The footer link [{}] {error}", link.text),
+        });
+    }
+
+    Ok(warnings)
+}
+
+/// Text of the footer links the releases should have: Unreleased, when there is a release to compare
+/// with, and each release, the newest first.
+fn expected_links(changelog: &ChangeLog) -> Vec<String> {
+    let mut texts = Vec::new();
+
+    if changelog.unreleased.is_some() && !changelog.releases.is_empty() {
+        texts.push(UNRELEASED.to_owned());
+    }
+
+    texts.extend(changelog.releases_keys().map(Version::to_string));
+
+    texts
+}
+
+fn link_of<'a>(changelog: &'a ChangeLog, text: &str) -> Option<&'a FooterLink> {
+    changelog
+        .footer_links
+        .links
+        .iter()
+        .find(|link| version_text(&link.text).eq_ignore_ascii_case(text))
+}
+
+fn orphan_links(changelog: &ChangeLog) -> Vec<&FooterLink> {
+    changelog
+        .footer_links
+        .links
+        .iter()
+        .filter(|link| match Version::from_str(version_text(&link.text)) {
+            Ok(version) => !changelog.releases.contains_key(&version),
+            Err(_) => false,
+        })
+        .collect()
+}
+
+/// Text of a footer link without the "v" of a tag, like "1.0.0" for "v1.0.0".
+fn version_text(text: &str) -> &str {
+    text.strip_prefix('v').unwrap_or(text)
+}
+
+/// Line of the first line starting with `prefix`. Starts at 1.
+fn line_of(input: &str, prefix: &str) -> usize {
+    input
+        .lines()
+        .position(|line| line.trim_start().starts_with(prefix))
+        .map_or(1, |pos| pos + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INPUT: &str = "## [Unreleased]
+
+## [1.1.0]
+
+## [1.0.0]
+
+See the [docs].
+
+[Unreleased]: https://github.com/a/b/compare/1.1.0...HEAD
+[1.0.0]: https://github.com/a/b/commits/1.0.0
+[0.9.0]: https://github.com/a/b/compare/0.8.0...0.9.0
+[docs]: https://docs.rs
+";
+
+    #[test]
+    fn missing_and_orphaned() {
+        let changelog = parse_changelog(INPUT).unwrap();
+
+        let warnings = check_footer_links(&changelog, INPUT);
+
+        assert_eq!(
+            warnings,
+            vec![
+                ParseWarning {
+                    rule: RULE_MISSING_FOOTER_LINK.into(),
+                    line: 3,
+                    message: "The release 1.1.0 has no footer link".into(),
+                },
+                ParseWarning {
+                    rule: RULE_ORPHAN_FOOTER_LINK.into(),
+                    line: 11,
+                    message: "The footer link [0.9.0] doesn't correspond to a release".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tag_links() {
+        let input = "## [1.0.0]\n\n[v1.0.0]: https://github.com/a/b/tree/v1.0.0\n";
+        let mut changelog = parse_changelog(input).unwrap();
+
+        assert!(check_footer_links(&changelog, input).is_empty());
+
+        fix_footer_links(&mut changelog, &GitProvider::Github, None, "v", false).unwrap();
+        assert_eq!(
+            changelog.footer_links.links,
+            vec![FooterLink {
+                text: "v1.0.0".into(),
+                link: "https://github.com/a/b/tree/v1.0.0".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn fix() {
+        let mut changelog = parse_changelog(INPUT).unwrap();
+
+        fix_footer_links(&mut changelog, &GitProvider::Github, Some("a/b"), "", false).unwrap();
+
+        assert!(check_footer_links(&changelog, INPUT).is_empty());
+        assert_eq!(
+            changelog.footer_links.links,
+            vec![
+                FooterLink {
+                    text: "Unreleased".into(),
+                    link: "https://github.com/a/b/compare/1.1.0...HEAD".into()
+                },
+                FooterLink {
+                    text: "1.1.0".into(),
+                    link: "https://github.com/a/b/compare/1.0.0...1.1.0".into()
+                },
+                FooterLink {
+                    text: "1.0.0".into(),
+                    link: "https://github.com/a/b/commits/1.0.0".into()
+                },
+                FooterLink {
+                    text: "docs".into(),
+                    link: "https://docs.rs".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn first_release() {
+        let mut changelog = parse_changelog("## [0.2.0]\n\n## [0.1.0]\n").unwrap();
+
+        fix_footer_links(
+            &mut changelog,
+            &GitProvider::Github,
+            Some("a/b"),
+            "v",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            changelog.footer_links.links,
+            vec![
+                FooterLink {
+                    text: "0.2.0".into(),
+                    link: "https://github.com/a/b/compare/v0.1.0...v0.2.0".into()
+                },
+                FooterLink {
+                    text: "0.1.0".into(),
+                    link: "https://github.com/a/b/tree/v0.1.0".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fix_keep_orphans() {
+        let mut changelog = parse_changelog(INPUT).unwrap();
+
+        fix_footer_links(&mut changelog, &GitProvider::Github, Some("a/b"), "", true).unwrap();
+
+        assert!(changelog
+            .footer_links
+            .links
+            .iter()
+            .any(|link| link.text == "0.9.0"));
+
+        let mut changelog = parse_changelog(INPUT).unwrap();
+        fix_footer_links(&mut changelog, &GitProvider::Github, None, "", false).unwrap_err();
+    }
+}
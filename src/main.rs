@@ -1,11 +1,11 @@
-use changen::{config::Cli, run};
+use changen::{config::Cli, init_logger, run};
 use clap::Parser;
 
 fn main() -> anyhow::Result<()> {
-    env_logger::init();
-
     let cli = Cli::parse();
 
+    init_logger(cli.log_level());
+
     run(cli)
 }
 
@@ -0,0 +1,195 @@
+use changelog::{utils::parse_note_attribution, ChangeLog, Release, ReleaseSectionNote};
+
+/// Merge `second` into `first`. Releases with the same version get their notes merged.
+/// On conflict (header, release date, footer link), `first` is preferred.
+pub fn merge(mut first: ChangeLog, second: ChangeLog) -> ChangeLog {
+    if first.header.is_none() {
+        first.header = second.header;
+    }
+
+    if let Some(unreleased) = second.unreleased {
+        match &mut first.unreleased {
+            Some(first_unreleased) => merge_release(first_unreleased, unreleased),
+            None => first.unreleased = Some(unreleased),
+        }
+    }
+
+    for (version, release) in second.releases {
+        match first.releases.get_mut(&version) {
+            Some(first_release) => merge_release(first_release, release),
+            None => {
+                first.releases.insert(version, release);
+            }
+        }
+    }
+
+    for link in second.footer_links.links {
+        if first
+            .footer_links
+            .links
+            .iter()
+            .all(|first_link| first_link.text != link.text)
+        {
+            first.footer_links.links.push(link);
+        }
+    }
+
+    first
+}
+
+fn merge_release(first: &mut Release, second: Release) {
+    if let (Some(first_date), Some(second_date)) = (&first.title.title, &second.title.title) {
+        if first_date != second_date {
+            warn!(
+                "release {}: conflicting dates \"{}\" and \"{}\", keeping \"{}\"",
+                first.version(),
+                first_date,
+                second_date,
+                first_date
+            );
+        }
+    }
+
+    if first.title.title.is_none() {
+        first.title.title = second.title.title;
+    }
+    if first.title.release_link.is_none() {
+        first.title.release_link = second.title.release_link;
+    }
+    if first.header.is_none() {
+        first.header = second.header;
+    }
+    if first.footer.is_none() {
+        first.footer = second.footer;
+    }
+
+    for (title, section) in second.note_sections {
+        match first.note_sections.get_mut(&title) {
+            Some(first_section) => {
+                if first_section.description.is_none() {
+                    first_section.description = section.description;
+                }
+
+                for note in section.notes {
+                    let key = note_key(&note);
+
+                    if first_section.notes.iter().all(|n| note_key(n) != key) {
+                        first_section.notes.push(note);
+                    }
+                }
+            }
+            None => {
+                first.note_sections.insert(title, section);
+            }
+        }
+    }
+}
+
+/// Identify a note regardless of its PR link and acknowledgement suffix.
+pub fn note_key(note: &ReleaseSectionNote) -> (Option<&str>, String) {
+    (
+        note.scope.as_deref(),
+        parse_note_attribution(&note.message).message,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::{
+        de::parse_changelog,
+        ser::{serialize_changelog, Options},
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn overlapping() {
+        let first = parse_changelog(
+            "## [Unreleased]
+
+### Fixed
+
+- crash in [#2](https://github.com/a/b/pull/2)
+
+## [1.0.0] - 2024-01-02
+
+### Added
+
+- ui: new button
+
+[1.0.0]: https://github.com/a/b/commits/1.0.0
+",
+        )
+        .unwrap();
+
+        let second = parse_changelog(
+            "## [Unreleased]
+
+### Fixed
+
+- crash in [#3](https://github.com/b/b/pull/3)
+- leak
+
+## [1.0.0] - 2024-01-01
+
+### Added
+
+- ui: new button
+- new command
+
+## [0.1.0]
+
+### Added
+
+- init
+
+[1.0.0]: https://github.com/b/b/commits/1.0.0
+[0.1.0]: https://github.com/b/b/commits/0.1.0
+",
+        )
+        .unwrap();
+
+        let merged = merge(first, second);
+
+        assert_eq!(
+            serialize_changelog(&merged, &Options::default()),
+            "## [Unreleased]
+
+### Fixed
+
+- crash in [#2](https://github.com/a/b/pull/2)
+- leak
+
+## [1.0.0] - 2024-01-02
+
+### Added
+
+- ui: new button
+- new command
+
+## [0.1.0]
+
+### Added
+
+- init
+
+[1.0.0]: https://github.com/a/b/commits/1.0.0
+[0.1.0]: https://github.com/b/b/commits/0.1.0
+"
+        );
+    }
+
+    #[test]
+    fn disjoint() {
+        let first = parse_changelog("## [2.0.0]\n\n### Added\n\n- two\n").unwrap();
+        let second = parse_changelog("# Changelog\n\n## [1.0.0]\n\n### Added\n\n- one\n").unwrap();
+
+        let merged = merge(first, second);
+
+        assert_eq!(
+            serialize_changelog(&merged, &Options::default()),
+            "# Changelog\n\n## [2.0.0]\n\n### Added\n\n- two\n\n## [1.0.0]\n\n### Added\n\n- one\n"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use changelog::{de::parse_changelog, ser::serialize_changelog};
+
+use crate::{
+    backfill::{backfill, footer_links, TagRange},
+    config::{Generate, MapMessageToSection, New},
+    repository::Repository,
+};
+
+pub fn new_changelog<R: Repository>(r: &R, options: &New) -> Result<String> {
+    let mut changelog = parse_changelog(include_str!("../res/CHANGELOG_DEFAULT.md"))?;
+
+    changelog.unreleased_or_default();
+
+    if options.tags || options.backfill {
+        let map = MapMessageToSection::default();
+
+        let generate = Generate {
+            provider: options.provider.clone(),
+            repo: options.repo.clone(),
+            ..Default::default()
+        };
+
+        backfill(
+            r,
+            &mut changelog,
+            &map,
+            &generate,
+            &TagRange {
+                filter: options.tag_filter.clone(),
+                tag_prefix: options.tag_prefix.clone(),
+                ..Default::default()
+            },
+            options.backfill,
+        )?;
+
+        changelog.sanitize(&map.to_fmt_options_all_releases());
+
+        if let Some(repo) = &options.repo {
+            changelog.footer_links.links =
+                footer_links(&changelog, &options.provider, repo, &options.tag_prefix)?;
+        }
+    }
+
+    let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
+
+    Ok(output)
+}
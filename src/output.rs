@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+use changelog::{
+    de::parse_changelog_with_options,
+    ser::{serialize_changelog, serialize_release, write_changelog, OptionsRelease},
+    ChangeLog, Release,
+};
+
+/// Serialize the changelog with the default options.
+pub fn render_to_string(changelog: &ChangeLog) -> String {
+    serialize_changelog(changelog, &changelog::ser::Options::default())
+}
+
+/// Serialize the changelog with the release options `release_option`.
+pub fn render_with(changelog: &ChangeLog, release_option: &OptionsRelease) -> String {
+    let options = changelog::ser::Options {
+        release_option: release_option.clone(),
+    };
+
+    serialize_changelog(changelog, &options)
+}
+
+/// Stream the changelog to stdout, with the default options.
+pub fn render_to_stdout(changelog: &ChangeLog) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    write_changelog(&mut stdout, changelog, &changelog::ser::Options::default())?;
+    stdout.flush()
+}
+
+/// Write the changelog to `path`.
+pub fn apply_to_file(path: &Path, changelog: &ChangeLog) -> anyhow::Result<()> {
+    write_changelog_file(path, &render_to_string(changelog))
+}
+
+/// Parse `rendered` back, and check that it has the releases, sections and notes of `changelog`.
+/// Guard against a serialization bug corrupting the file. Empty sections are not compared.
+/// `section_headings` are the ones it was rendered with.
+pub fn verify_roundtrip(
+    changelog: &ChangeLog,
+    rendered: &str,
+    section_headings: &HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let options = changelog::de::Options {
+        section_headings: section_headings.clone(),
+        ..Default::default()
+    };
+
+    let parsed = parse_changelog_with_options(rendered, &options)
+        .context("The rendered changelog can't be parsed back. It was not written")?;
+
+    if changelog.unreleased.is_some() && parsed.unreleased.is_none() {
+        bail!("The Unreleased section was dropped from the rendered changelog. It was not written");
+    }
+
+    let releases = changelog
+        .unreleased
+        .iter()
+        .chain(changelog.releases())
+        .collect::<Vec<_>>();
+    let parsed_releases = parsed
+        .unreleased
+        .iter()
+        .chain(parsed.releases())
+        .collect::<Vec<_>>();
+
+    let versions = releases.iter().map(|r| r.version()).collect::<Vec<_>>();
+    let parsed_versions = parsed_releases
+        .iter()
+        .map(|r| r.version())
+        .collect::<Vec<_>>();
+
+    if versions != parsed_versions {
+        bail!(
+            "The releases of the rendered changelog ({}) are not the ones of the model ({}). It was not written",
+            parsed_versions.join(", "),
+            versions.join(", ")
+        );
+    }
+
+    for (release, parsed_release) in releases.iter().zip(&parsed_releases) {
+        let sections = release
+            .note_sections
+            .values()
+            .filter(|s| !s.notes.is_empty());
+        let parsed_sections = parsed_release
+            .note_sections
+            .values()
+            .filter(|s| !s.notes.is_empty());
+
+        if !sections.eq(parsed_sections) {
+            bail!(
+                "The sections of the release {} changed in the rendered changelog. It was not written",
+                release.version()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Write `contents` to `path`.
+/// The contents are first written next to `path`, then renamed over it,
+/// so an error can't leave a truncated changelog behind.
+/// The permissions of an existing file are preserved.
+pub fn write_changelog_file(path: &Path, contents: &str) -> anyhow::Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("invalid changelog path: {}", path.display()))?;
+
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let res = fs::write(&tmp_path, contents)
+        .and_then(|_| match fs::metadata(path) {
+            Ok(metadata) => fs::set_permissions(&tmp_path, metadata.permissions()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+
+    if let Err(e) = res {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e).with_context(|| format!("can't write {}", path.display()));
+    }
+
+    Ok(())
+}
+
+/// Append the release notes to the file of the `GITHUB_STEP_SUMMARY` variable,
+/// shown on the summary page of a Github Actions run.
+pub fn append_step_summary(release: &Release) -> anyhow::Result<()> {
+    match env::var_os("GITHUB_STEP_SUMMARY") {
+        Some(path) => append_step_summary_to(Path::new(&path), release),
+        None => {
+            warn!("GITHUB_STEP_SUMMARY is not set, the step summary is not written.");
+            Ok(())
+        }
+    }
+}
+
+fn append_step_summary_to(path: &Path, release: &Release) -> anyhow::Result<()> {
+    let mut summary = format!("## {}\n\n", release.version());
+
+    serialize_release(
+        &mut summary,
+        release,
+        &OptionsRelease {
+            serialize_title: false,
+            ..Default::default()
+        },
+    );
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("can't open {}", path.display()))?;
+
+    file.write_all(summary.as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let dir = std::env::temp_dir().join("changen_output_apply");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("CHANGELOG.md");
+        fs::write(&path, "old content, much longer than the new one").unwrap();
+
+        let changelog = parse_changelog("## [Unreleased]\n\n### Added\n\n- a\n").unwrap();
+
+        apply_to_file(&path, &changelog).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            render_to_string(&changelog)
+        );
+        assert!(!dir.join(".CHANGELOG.md.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut changelog = parse_changelog(
+            "## [Unreleased]\n\n### Added\n\n- a\n\n## [1.0.0]\n\n### Fixed\n\n- b\n",
+        )
+        .unwrap();
+
+        verify_roundtrip(&changelog, &render_to_string(&changelog), &HashMap::new()).unwrap();
+
+        // a message breaking the document
+        changelog.unreleased.as_mut().unwrap().note_sections["Added"].notes[0].message =
+            "a\n\n## [2.0.0]".into();
+
+        let err = verify_roundtrip(&changelog, &render_to_string(&changelog), &HashMap::new())
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .starts_with("The releases of the rendered changelog"),
+            "{err}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keep_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join("changen_output_permissions");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("CHANGELOG.md");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_changelog_file(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o640
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn step_summary() {
+        let dir = std::env::temp_dir().join("changen_output_step_summary");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("summary.md");
+        fs::write(&path, "previous step\n").unwrap();
+
+        let changelog = parse_changelog("## [Unreleased]\n\n### Added\n\n- a\n").unwrap();
+
+        append_step_summary_to(&path, changelog.unreleased.as_ref().unwrap()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "previous step\n## Unreleased\n\n### Added\n\n- a\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
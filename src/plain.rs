@@ -0,0 +1,197 @@
+use changelog::{
+    utils::{expand_links, strip_links},
+    Release, ReleaseSectionNote,
+};
+
+use crate::{
+    config::HeadingStyle,
+    utils::{display_width, wrap},
+};
+
+/// How a release is rendered as plain text.
+#[derive(Debug, Clone)]
+pub struct PlainTextOptions {
+    /// Column at which the lines are wrapped. Words longer than it, like URLs, are not cut.
+    pub width: usize,
+    pub heading_style: HeadingStyle,
+    /// Write the links as "text (url)", rather than only their text.
+    pub link_urls: bool,
+    /// Start with the version and the date of the release.
+    pub title: bool,
+}
+
+impl Default for PlainTextOptions {
+    fn default() -> Self {
+        Self {
+            width: 72,
+            heading_style: HeadingStyle::Underline,
+            link_urls: false,
+            title: true,
+        }
+    }
+}
+
+/// The notes of a release without markdown syntax, for emails and announcements.
+///
+/// The title and the sections are headings, underlined or uppercase. The notes are `* ` items,
+/// with their context indented under them. The paragraphs are separated by a blank line.
+pub fn to_plain_text(release: &Release, options: &PlainTextOptions) -> String {
+    let mut paragraphs = Vec::new();
+
+    if options.title {
+        let title = match &release.title.title {
+            Some(title) => format!("{} - {title}", release.version()),
+            None => release.version().to_owned(),
+        };
+        paragraphs.push(heading(&title, '=', options));
+    }
+
+    if let Some(header) = &release.header {
+        paragraphs.push(wrap_text(header, options));
+    }
+
+    for section in release.note_sections.values() {
+        if section.notes.is_empty() && section.description.is_none() {
+            continue;
+        }
+
+        // the notes without section
+        let mut paragraph = if section.title.is_empty() {
+            String::new()
+        } else {
+            heading(&section.title, '-', options)
+        };
+
+        if let Some(description) = &section.description {
+            if !paragraph.is_empty() {
+                paragraph.push('\n');
+            }
+            paragraph.push_str(&wrap_text(description, options));
+        }
+
+        if !section.notes.is_empty() && !paragraph.is_empty() {
+            paragraph.push('\n');
+        }
+
+        for note in &section.notes {
+            paragraph.push_str(&note_text(note, options));
+        }
+
+        paragraphs.push(paragraph);
+    }
+
+    if let Some(footer) = &release.footer {
+        paragraphs.push(wrap_text(footer, options));
+    }
+
+    paragraphs.join("\n")
+}
+
+fn note_text(note: &ReleaseSectionNote, options: &PlainTextOptions) -> String {
+    let message = inline_text(&note.message, options);
+
+    let line = match &note.scope {
+        Some(scope) => format!("* {scope}: {message}"),
+        None => format!("* {message}"),
+    };
+
+    let mut text = String::new();
+
+    for line in wrap(&line, options.width, "", "  ") {
+        text.push_str(&line);
+        text.push('\n');
+    }
+
+    for context in &note.context {
+        for line in wrap(&inline_text(context, options), options.width, "  ", "  ") {
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+fn heading(title: &str, underline: char, options: &PlainTextOptions) -> String {
+    let title = inline_text(title, options);
+
+    match options.heading_style {
+        HeadingStyle::Underline => {
+            let line = underline.to_string().repeat(display_width(&title));
+            format!("{title}\n{line}\n")
+        }
+        HeadingStyle::Uppercase => format!("{}\n", title.to_uppercase()),
+    }
+}
+
+fn inline_text(markdown: &str, options: &PlainTextOptions) -> String {
+    if options.link_urls {
+        expand_links(markdown)
+    } else {
+        strip_links(markdown)
+    }
+}
+
+/// Wrap each line of a free text, keeping its blank lines.
+fn wrap_text(text: &str, options: &PlainTextOptions) -> String {
+    let mut res = String::new();
+
+    for line in inline_text(text, options).lines() {
+        if line.trim().is_empty() {
+            res.push('\n');
+            continue;
+        }
+
+        for line in wrap(line, options.width, "", "") {
+            res.push_str(&line);
+            res.push('\n');
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn release() -> Release {
+        parse_changelog(include_str!("../tests/plain/CHANGELOG.md"))
+            .unwrap()
+            .releases()
+            .next()
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn underline() {
+        let options = PlainTextOptions {
+            width: 60,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_plain_text(&release(), &options),
+            include_str!("../tests/plain/underline.txt")
+        );
+    }
+
+    #[test]
+    fn uppercase_with_urls() {
+        let options = PlainTextOptions {
+            width: 60,
+            heading_style: HeadingStyle::Uppercase,
+            link_urls: true,
+            title: false,
+        };
+
+        assert_eq!(
+            to_plain_text(&release(), &options),
+            include_str!("../tests/plain/uppercase.txt")
+        );
+    }
+}
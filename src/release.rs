@@ -1,17 +1,20 @@
 use anyhow::bail;
-use changelog::{ser::serialize_changelog, utils::DEFAULT_UNRELEASED, ChangeLog};
+use changelog::{
+    utils::{InsertPolicy, DEFAULT_UNRELEASED},
+    ChangeLog,
+};
 
 use crate::{
     config::MergeDevVersions,
     git_provider::DiffTags,
-    repository::{try_detect_new_version, Repository},
+    repository::{tag_name, try_detect_new_version, Repository},
 };
 
 pub fn release<R: Repository>(
     r: &R,
     mut changelog: ChangeLog,
     options: &crate::config::Release,
-) -> anyhow::Result<(String, String)> {
+) -> anyhow::Result<(String, ChangeLog)> {
     let crate::config::Release {
         file: _,
         version,
@@ -23,14 +26,16 @@ pub fn release<R: Repository>(
         omit_diff,
         stdout: _,
         force,
+        tag_filter,
+        tag_prefix,
     } = options;
 
-    let new_version = try_detect_new_version(r, version.clone())?;
+    let new_version = try_detect_new_version(r, version.clone(), tag_filter.as_ref(), tag_prefix)?;
 
     if changelog.releases.contains_key(&new_version) {
         if *force {
             changelog.releases.remove(&new_version);
-            eprintln!("The release {} will be overwritten", new_version)
+            warn!("The release {} will be overwritten", new_version)
         } else {
             bail!(
                 "Version {} already exist. Create a new tag or use the --version option. You can also use the --force option to override the existing release.",
@@ -56,12 +61,12 @@ pub fn release<R: Repository>(
     }
 
     if let Some(repo) = &repo {
-        match provider.release_link(repo, &new_version.to_string()) {
+        match provider.release_link(repo, &tag_name(tag_prefix, &new_version)) {
             Ok(link) => {
                 prev_unreleased.title.release_link = Some(link);
             }
             Err(e) => {
-                eprintln!("{e}");
+                warn!("{e}");
             }
         }
     }
@@ -73,7 +78,7 @@ pub fn release<R: Repository>(
         {
             let dev_releases = changelog
                 .releases
-                .extract_if(|k, _| {
+                .extract_if(.., |k, _| {
                     k.version_opt()
                         .map(|k| {
                             k.major == new_version_semver.major
@@ -96,14 +101,14 @@ pub fn release<R: Repository>(
         .clone()
         .or_else(|| changelog.last_version());
 
-    let diff_tags = DiffTags::new(new_version, previous_version)?;
+    let diff_tags = DiffTags::new(new_version, previous_version)?.with_tag_prefix(tag_prefix);
 
     if !omit_diff {
         let link = if let Some(repo) = &repo {
             match provider.diff_link(repo, &diff_tags) {
                 Ok(link) => Some(link),
                 Err(e) => {
-                    eprintln!("{e}");
+                    warn!("{e}");
                     None
                 }
             }
@@ -126,15 +131,11 @@ pub fn release<R: Repository>(
         }
     }
 
-    changelog
-        .releases
-        .insert(diff_tags.new.clone(), prev_unreleased);
+    changelog.insert_release(prev_unreleased, InsertPolicy::default())?;
 
     debug!("release: serialize changelog: {:?}", changelog);
 
     changelog.sanitize(&changelog::fmt::Options::default());
 
-    let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
-
-    Ok((diff_tags.new.to_string(), output))
+    Ok((diff_tags.new.to_string(), changelog))
 }
@@ -0,0 +1,99 @@
+use anyhow::{bail, Context};
+use changelog::{de::parse_changelog, ChangeLog};
+use reqwest::{
+    blocking::Client,
+    header::{CONTENT_TYPE, USER_AGENT},
+};
+
+/// Content types under which a changelog can be served. Raw files of Github are `text/plain`.
+const MARKDOWN_CONTENT_TYPES: [&str; 4] = [
+    "text/markdown",
+    "text/x-markdown",
+    "text/plain",
+    "application/octet-stream",
+];
+
+/// Fetch the raw markdown of a changelog, and parse it.
+/// Example: <https://raw.githubusercontent.com/wiiznokes/changen/master/CHANGELOG.md>
+pub fn parse_change_log_from_url(url: &str) -> anyhow::Result<ChangeLog> {
+    let response = Client::new()
+        .get(url)
+        .header(USER_AGENT, "my-github-client")
+        .send()
+        .with_context(|| format!("Can't fetch the changelog at {url}"))?;
+
+    if !response.status().is_success() {
+        bail!(
+            "Fetching the changelog at {url} returned status {}",
+            response.status()
+        );
+    }
+
+    if let Some(content_type) = response.headers().get(CONTENT_TYPE) {
+        let content_type = content_type.to_str().unwrap_or_default();
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+
+        if !MARKDOWN_CONTENT_TYPES
+            .iter()
+            .any(|markdown| mime.eq_ignore_ascii_case(markdown))
+        {
+            bail!("The content at {url} is not markdown: {content_type}. Use the link of the raw file.");
+        }
+    }
+
+    let input = response.text()?;
+
+    debug!("changelog fetched from {url}");
+
+    Ok(parse_changelog(&input)?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::integration_test::mock_server_with;
+
+    use super::*;
+
+    /// Answer one request, and return the url of the changelog.
+    fn serve(status: u16, content_type: &'static str, body: &'static str) -> String {
+        let (url, _) = mock_server_with(vec![(status, content_type, body)]);
+        format!("{url}/CHANGELOG.md")
+    }
+
+    #[test]
+    fn from_url() {
+        let url = serve(
+            200,
+            "text/plain; charset=utf-8",
+            "## [Unreleased]\n\n## [1.0.0]\n\n### Added\n\n- init\n",
+        );
+
+        let changelog = parse_change_log_from_url(&url).unwrap();
+
+        assert_eq!(
+            changelog.last_version().map(|v| v.to_string()),
+            Some("1.0.0".into())
+        );
+    }
+
+    #[test]
+    fn not_found() {
+        let url = serve(404, "text/plain", "404: Not Found");
+
+        let err = parse_change_log_from_url(&url).unwrap_err();
+
+        assert!(err.to_string().contains("returned status 404"), "{err}");
+    }
+
+    #[test]
+    fn not_markdown() {
+        let url = serve(200, "text/html", "<html></html>");
+
+        let err = parse_change_log_from_url(&url).unwrap_err();
+
+        assert!(
+            err.to_string().contains("is not markdown: text/html"),
+            "{err}"
+        );
+    }
+}
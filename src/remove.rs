@@ -0,0 +1,222 @@
+use anyhow::bail;
+use changelog::{
+    utils::{NthRelease, UNRELEASED},
+    ChangeLog, Release, Version,
+};
+use regex::Regex;
+
+use crate::config::{Remove, RemoveSelection};
+
+/// Remove the selected releases, or only their notes matching `options.matches`.
+/// Return a description of each removed item. Fail if nothing was removed.
+pub fn remove(changelog: &mut ChangeLog, options: &Remove) -> anyhow::Result<Vec<String>> {
+    let (unreleased, versions) = select_releases(changelog, &options.remove_id)?;
+
+    let mut removed = Vec::new();
+
+    match &options.matches {
+        Some(regex) => {
+            if unreleased {
+                if let Some(release) = &mut changelog.unreleased {
+                    remove_notes(release, regex, options.section.as_deref(), &mut removed);
+                }
+            }
+
+            for version in &versions {
+                if let Some(release) = changelog.releases.get_mut(version) {
+                    remove_notes(release, regex, options.section.as_deref(), &mut removed);
+                }
+            }
+        }
+        None => {
+            if unreleased && changelog.unreleased.take().is_some() {
+                remove_footer_link(changelog, UNRELEASED);
+                removed.push(format!("release {UNRELEASED}"));
+            }
+
+            for version in &versions {
+                if changelog.releases.remove(version).is_some() {
+                    remove_footer_link(changelog, &version.to_string());
+                    removed.push(format!("release {version}"));
+                }
+            }
+        }
+    }
+
+    if removed.is_empty() {
+        bail!("Nothing matched, nothing was removed");
+    }
+
+    Ok(removed)
+}
+
+/// Return whether Unreleased is selected, and the selected versions.
+fn select_releases(
+    changelog: &ChangeLog,
+    selection: &RemoveSelection,
+) -> anyhow::Result<(bool, Vec<Version>)> {
+    if let Some(regex) = &selection.version {
+        let unreleased = regex.as_str().eq_ignore_ascii_case(UNRELEASED);
+
+        let versions = changelog
+            .releases_full()
+            .filter(|(_, release)| regex.is_match(release.version()))
+            .map(|(version, _)| version.clone())
+            .collect();
+
+        return Ok((unreleased, versions));
+    }
+
+    match changelog.nth_release(selection.n.unwrap())? {
+        NthRelease::Unreleased(_) => Ok((true, Vec::new())),
+        NthRelease::Released(version, _) => Ok((false, vec![version.into_owned()])),
+    }
+}
+
+fn remove_notes(
+    release: &mut Release,
+    regex: &Regex,
+    section: Option<&str>,
+    removed: &mut Vec<String>,
+) {
+    let version = release.version().to_owned();
+
+    for (title, release_section) in &mut release.note_sections {
+        if section.is_some_and(|section| !section.eq_ignore_ascii_case(title)) {
+            continue;
+        }
+
+        release_section.notes.retain(|note| {
+            if regex.is_match(&note.message) {
+                removed.push(format!("{version} {title}: {}", note.message));
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+fn remove_footer_link(changelog: &mut ChangeLog, text: &str) {
+    changelog
+        .footer_links
+        .links
+        .retain(|link| link.text != text);
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::{
+        de::parse_changelog,
+        ser::{serialize_changelog, Options},
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CHANGELOG: &str = "## [Unreleased]
+
+### Added
+
+- new button
+
+### Fixed
+
+- bump deps
+- crash
+
+## [1.0.1]
+
+### Fixed
+
+- bump deps
+
+## [1.0.0]
+
+### Added
+
+- init
+
+[Unreleased]: https://github.com/a/b/compare/1.0.1...HEAD
+[1.0.1]: https://github.com/a/b/compare/1.0.0...1.0.1
+[1.0.0]: https://github.com/a/b/commits/1.0.0
+";
+
+    fn options(version: &str) -> Remove {
+        Remove {
+            file: None,
+            stdout: true,
+            remove_id: RemoveSelection {
+                n: None,
+                version: Some(Regex::new(version).unwrap()),
+            },
+            matches: None,
+            section: None,
+        }
+    }
+
+    fn apply(options: &Remove) -> anyhow::Result<(Vec<String>, String)> {
+        let mut changelog = parse_changelog(CHANGELOG).unwrap();
+        let removed = remove(&mut changelog, options)?;
+        changelog.sanitize(&changelog::fmt::Options::default());
+
+        Ok((
+            removed,
+            serialize_changelog(&changelog, &Options::default()),
+        ))
+    }
+
+    #[test]
+    fn notes() {
+        let mut options = options("unreleased");
+        options.matches = Some(Regex::new("bump deps").unwrap());
+
+        let (removed, output) = apply(&options).unwrap();
+
+        assert_eq!(removed, vec!["Unreleased Fixed: bump deps".to_owned()]);
+        assert!(output.starts_with(
+            "## [Unreleased]
+
+### Added
+
+- new button
+
+### Fixed
+
+- crash
+
+## [1.0.1]
+
+### Fixed
+
+- bump deps
+"
+        ));
+
+        options.matches = Some(Regex::new("^(new|crash)").unwrap());
+        options.section = Some("fixed".into());
+
+        let (removed, _) = apply(&options).unwrap();
+
+        assert_eq!(removed, vec!["Unreleased Fixed: crash".to_owned()]);
+    }
+
+    #[test]
+    fn whole_release() {
+        let (removed, output) = apply(&options("1.0.1")).unwrap();
+
+        assert_eq!(removed, vec!["release 1.0.1".to_owned()]);
+        assert!(!output.contains("## [1.0.1]"));
+        assert!(!output.contains("[1.0.1]:"));
+        assert!(output.contains("[1.0.0]: https://github.com/a/b/commits/1.0.0"));
+    }
+
+    #[test]
+    fn no_match() {
+        apply(&options("2.0.0")).unwrap_err();
+
+        let mut options = options("1.0.0");
+        options.matches = Some(Regex::new("bump deps").unwrap());
+        apply(&options).unwrap_err();
+    }
+}
@@ -0,0 +1,174 @@
+//! Machine readable outputs, printed with `--output-format json`.
+//!
+//! Every report has a `schema_version` field, incremented on each breaking change of its schema.
+//! Adding a field is not a breaking change.
+
+use anstyle::{AnsiColor, Style};
+use changelog::{
+    de::ParseWarning,
+    ser::{serialize_release, serialize_release_section_note, OptionsRelease},
+    utils::DEFAULT_UNRELEASED,
+    ChangeLog, Release, ReleaseSection, ReleaseSectionNote,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{diff::ChangelogDiff, search::SearchMatch, stats::Stats};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Output of `show`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShowReport {
+    pub schema_version: u32,
+    pub releases: Vec<Release>,
+}
+
+/// Output of `validate`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidateReport {
+    pub schema_version: u32,
+    /// `{ "rule": "duplicate-section", "line": 12, "message": "..." }`
+    pub diagnostics: Vec<ParseWarning>,
+}
+
+/// Output of `diff`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffReport {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub diff: ChangelogDiff,
+}
+
+/// Output of `stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+/// Output of `search`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchReport {
+    pub schema_version: u32,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Output of `generate`: the notes added to the Unreleased section, in their order in the changelog.
+/// `amend --report` edits the last one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationReport {
+    pub schema_version: u32,
+    pub notes: Vec<GeneratedNote>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedNote {
+    pub section: String,
+    #[serde(flatten)]
+    pub note: ReleaseSectionNote,
+}
+
+impl GenerationReport {
+    /// Notes of the Unreleased section of `after` missing in `before`.
+    pub fn new(before: &ChangeLog, after: &ChangeLog) -> Self {
+        let mut notes = Vec::new();
+
+        if let Some(unreleased) = &after.unreleased {
+            for (title, section) in &unreleased.note_sections {
+                let before_section = before
+                    .unreleased
+                    .as_ref()
+                    .and_then(|unreleased| unreleased.note_sections.get(title));
+
+                for note in &section.notes {
+                    if before_section.is_none_or(|section| !section.notes.contains(note)) {
+                        notes.push(GeneratedNote {
+                            section: title.clone(),
+                            note: note.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            notes,
+        }
+    }
+}
+
+impl GeneratedNote {
+    /// Notes grouped by section, in markdown.
+    pub fn summary(notes: &[GeneratedNote]) -> String {
+        let mut release = DEFAULT_UNRELEASED.clone();
+
+        for note in notes {
+            release
+                .note_sections
+                .entry(note.section.clone())
+                .or_insert_with(|| ReleaseSection {
+                    title: note.section.clone(),
+                    description: None,
+                    notes: Vec::new(),
+                })
+                .notes
+                .push(note.note.clone());
+        }
+
+        let mut output = String::new();
+
+        serialize_release(
+            &mut output,
+            &release,
+            &OptionsRelease {
+                serialize_title: false,
+                ..Default::default()
+            },
+        );
+
+        output
+    }
+
+    /// Like [`Self::summary`], with the section titles in bold and the notes in green.
+    /// The colors are escape codes, to print with `anstream`, which removes them when unsupported.
+    pub fn preview(notes: &[GeneratedNote]) -> String {
+        let title = Style::new().bold();
+        let note = Style::new().fg_color(Some(AnsiColor::Green.into()));
+
+        let mut output = String::new();
+
+        for line in Self::summary(notes).lines() {
+            let style = if line.starts_with("### ") {
+                title
+            } else if line.is_empty() {
+                Style::new()
+            } else {
+                note
+            };
+
+            output.push_str(&format!("{style}{line}{style:#}\n"));
+        }
+
+        output
+    }
+
+    /// Confirmation shown when a single commit was added.
+    pub fn added_message(&self) -> String {
+        let mut note = String::new();
+        serialize_release_section_note(&mut note, &self.note, &OptionsRelease::default());
+
+        format!(
+            "Release note:\n{note}successfully added in the {} section.",
+            self.section
+        )
+    }
+}
+
+/// Pretty JSON, ending with a new line.
+pub fn to_json<T: Serialize>(report: &T) -> anyhow::Result<String> {
+    let mut output = serde_json::to_string_pretty(report)?;
+    output.push('\n');
+    Ok(output)
+}
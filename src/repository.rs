@@ -1,7 +1,13 @@
-use std::{collections::VecDeque, process::Command, str::FromStr};
+use std::{
+    collections::VecDeque,
+    io::Write,
+    process::{Command, Stdio},
+    str::FromStr,
+};
 
 use anyhow::bail;
 use changelog::Version;
+use regex::Regex;
 
 use crate::git_provider::DiffTags;
 
@@ -12,6 +18,10 @@ pub struct RawCommit {
     pub body: String,
     pub sha: String,
     pub list_files: Vec<String>,
+    /// The commit has more than one parent.
+    pub is_merge: bool,
+    /// Commit date, in ISO 8601 in UTC, like "2024-01-02T09:00:00+00:00".
+    pub date: Option<String>,
 }
 
 impl RawCommit {
@@ -26,12 +36,29 @@ impl RawCommit {
             title: r.commit_title(sha),
             body: r.commit_body(sha),
             list_files: r.commit_files(sha),
+            is_merge: r.commit_is_merge(sha),
+            date: r.commit_date(sha),
             sha: sha.into(),
         }
     }
 
+    /// Replace the commit message by a git note. The first line of the note
+    /// replaces the title, the rest replaces the body when not empty.
+    pub fn apply_note(&mut self, note: &str) {
+        let note = note.trim();
+
+        let (title, body) = note.split_once('\n').unwrap_or((note, ""));
+
+        self.title = title.trim().into();
+
+        let body = body.trim();
+        if !body.is_empty() {
+            self.body = body.into();
+        }
+    }
+
     pub fn short_commit(&self) -> &str {
-        &self.sha[0..7]
+        &self.sha[..self.sha.len().min(7)]
     }
 }
 
@@ -52,12 +79,38 @@ pub trait Repository {
 
     fn commit_body(&self, sha: &str) -> String;
 
+    /// Files changed by the commit. For a merge, the files it brings to its first parent.
     fn commit_files(&self, sha: &str) -> Vec<String>;
 
+    fn commit_is_merge(&self, sha: &str) -> bool;
+
+    /// Commit date, in ISO 8601 in UTC, like "2024-01-02T09:00:00+00:00".
+    fn commit_date(&self, sha: &str) -> Option<String>;
+
+    /// Content of the git note attached to the commit, if any.
+    fn commit_note(&self, sha: &str) -> Option<String>;
+
     fn commits_between_tags(&self, tags: &Period) -> Vec<String>;
 
-    /// Most recent at the end
-    fn tags_list(&self) -> anyhow::Result<VecDeque<Version>>;
+    /// Most recent at the end. Only the tags matching `filter` are listed, when set.
+    /// `tag_prefix` is stripped from the tag names to get the versions.
+    fn tags_list(
+        &self,
+        filter: Option<&Regex>,
+        tag_prefix: &str,
+    ) -> anyhow::Result<VecDeque<Version>>;
+
+    /// Creation date of the tag, formatted as YYYY-MM-DD.
+    fn tag_date(&self, tag: &str) -> Option<String>;
+
+    /// Branch pointed by origin/HEAD, if known locally.
+    fn default_branch(&self) -> Option<String>;
+
+    /// No staged, unstaged or untracked changes.
+    fn is_clean(&self) -> anyhow::Result<bool>;
+
+    /// Create the annotated tag `tag` on HEAD.
+    fn create_tag(&self, tag: &str, message: &str) -> anyhow::Result<()>;
 }
 
 /// Represent the real implementation of the Repository trait
@@ -120,8 +173,17 @@ impl Repository for Fs {
     }
 
     fn commit_files(&self, sha: &str) -> Vec<String> {
+        // diff-tree lists no file for a merge, unless given the tree it is compared to
+        let first_parent = format!("{sha}^1");
+
+        let mut args = vec!["diff-tree", "--no-commit-id", "--name-only", "-r"];
+        if self.commit_is_merge(sha) {
+            args.push(&first_parent);
+        }
+        args.push(sha);
+
         let output = Command::new("git")
-            .args(["diff-tree", "--no-commit-id", "--name-only", "-r", sha])
+            .args(args)
             .output()
             .expect("Failed to execute git command");
 
@@ -137,6 +199,47 @@ impl Repository for Fs {
             .collect()
     }
 
+    fn commit_is_merge(&self, sha: &str) -> bool {
+        let output = Command::new("git")
+            .args(["rev-list", "--parents", "-n", "1", sha])
+            .output()
+            .expect("Failed to execute git command");
+
+        if !output.status.success() {
+            panic!("{}", String::from_utf8_lossy(&output.stderr))
+        }
+
+        // the sha followed by its parents
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .split_whitespace()
+            .count()
+            > 2
+    }
+
+    fn commit_note(&self, sha: &str) -> Option<String> {
+        let output = Command::new("git")
+            .args(["notes", "show", sha])
+            .output()
+            .expect("Failed to execute git command");
+
+        if !output.status.success() {
+            debug!(
+                "commit_note error: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return None;
+        }
+
+        let note = String::from_utf8(output.stdout).ok()?.trim().to_owned();
+
+        if note.is_empty() {
+            None
+        } else {
+            Some(note)
+        }
+    }
+
     fn commits_between_tags(&self, tags: &Period) -> Vec<String> {
         let until = tags.until.as_deref().unwrap_or("HEAD");
 
@@ -166,7 +269,11 @@ impl Repository for Fs {
             .collect()
     }
 
-    fn tags_list(&self) -> anyhow::Result<VecDeque<Version>> {
+    fn tags_list(
+        &self,
+        filter: Option<&Regex>,
+        tag_prefix: &str,
+    ) -> anyhow::Result<VecDeque<Version>> {
         let output = Command::new("git")
             .arg("tag")
             .output()
@@ -179,11 +286,13 @@ impl Repository for Fs {
         let mut tags = Vec::new();
 
         for tag in String::from_utf8(output.stdout)?.trim().lines() {
-            match Version::from_str(tag) {
-                Ok(v) => tags.push(v),
-                Err(e) => {
-                    eprintln!("incorrect semver tag {tag}: {e}");
-                }
+            if filter.is_some_and(|filter| !filter.is_match(tag)) {
+                debug!("tag {tag} filtered out");
+                continue;
+            }
+
+            if let Some(v) = tag_version(tag_prefix, tag) {
+                tags.push(v);
             }
         }
 
@@ -195,15 +304,122 @@ impl Repository for Fs {
 
         Ok(tags)
     }
+
+    fn commit_date(&self, sha: &str) -> Option<String> {
+        let output = Command::new("git")
+            .args(["show", "-s", "--date=iso-strict-local", "--pretty=%cd", sha])
+            .env("TZ", "UTC")
+            .output()
+            .expect("Failed to execute git command");
+
+        if !output.status.success() {
+            debug!(
+                "commit_date error: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return None;
+        }
+
+        let date = String::from_utf8(output.stdout).ok()?.trim().to_owned();
+
+        if date.is_empty() {
+            None
+        } else {
+            Some(date)
+        }
+    }
+
+    fn tag_date(&self, tag: &str) -> Option<String> {
+        let output = Command::new("git")
+            .args([
+                "for-each-ref",
+                "--format=%(creatordate:short)",
+                &format!("refs/tags/{tag}"),
+            ])
+            .output()
+            .expect("Failed to execute git command");
+
+        if !output.status.success() {
+            debug!(
+                "tag_date error: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return None;
+        }
+
+        let date = String::from_utf8(output.stdout).ok()?.trim().to_owned();
+
+        if date.is_empty() {
+            None
+        } else {
+            Some(date)
+        }
+    }
+
+    fn default_branch(&self) -> Option<String> {
+        let output = Command::new("git")
+            .args(["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+            .output()
+            .expect("Failed to execute git command");
+
+        if !output.status.success() {
+            debug!(
+                "default_branch error: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return None;
+        }
+
+        let branch = String::from_utf8(output.stdout).ok()?;
+        let branch = branch.trim();
+
+        Some(branch.strip_prefix("origin/").unwrap_or(branch).to_owned())
+    }
+
+    fn is_clean(&self) -> anyhow::Result<bool> {
+        let output = Command::new("git")
+            .args(["status", "--porcelain"])
+            .output()?;
+
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr))
+        }
+
+        Ok(output.stdout.iter().all(u8::is_ascii_whitespace))
+    }
+
+    fn create_tag(&self, tag: &str, message: &str) -> anyhow::Result<()> {
+        let mut child = Command::new("git")
+            .args(["tag", "-a", tag, "-F", "-"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(message.as_bytes())?;
+
+        let output = child.wait_with_output()?;
+
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr))
+        }
+
+        Ok(())
+    }
 }
 
 pub fn try_detect_new_version<R: Repository>(
     r: &R,
     new: Option<Version>,
+    tag_filter: Option<&Regex>,
+    tag_prefix: &str,
 ) -> anyhow::Result<Version> {
     match new {
         Some(new) => Ok(new),
-        None => match r.tags_list()?.pop_back() {
+        None => match r.tags_list(tag_filter, tag_prefix)?.pop_back() {
             Some(v) => Ok(v),
             None => {
                 bail!("No version provided. Can't fall back to last tag because there is none.")
@@ -227,7 +443,47 @@ impl DiffTags {
             None
         };
 
-        Ok(DiffTags { prev, new })
+        Ok(DiffTags {
+            prev,
+            new,
+            tag_prefix: String::new(),
+        })
+    }
+
+    pub fn with_tag_prefix(mut self, tag_prefix: &str) -> Self {
+        self.tag_prefix = tag_prefix.to_owned();
+        self
+    }
+
+    pub fn prev_tag(&self) -> Option<String> {
+        self.prev
+            .as_ref()
+            .map(|prev| tag_name(&self.tag_prefix, prev))
+    }
+
+    pub fn new_tag(&self) -> String {
+        tag_name(&self.tag_prefix, &self.new)
+    }
+}
+
+/// Name of the tag of `version`. Example: "v1.2.3" with the prefix "v".
+pub fn tag_name(tag_prefix: &str, version: &Version) -> String {
+    format!("{tag_prefix}{version}")
+}
+
+/// Version of a tag, or `None` if the tag doesn't start with `tag_prefix` or is not a version.
+pub fn tag_version(tag_prefix: &str, tag: &str) -> Option<Version> {
+    let Some(version) = tag.strip_prefix(tag_prefix) else {
+        debug!("tag {tag} doesn't start with the prefix \"{tag_prefix}\"");
+        return None;
+    };
+
+    match Version::from_str(version) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            warn!("incorrect semver tag {tag}: {e}");
+            None
+        }
     }
 }
 
@@ -244,7 +500,7 @@ mod test {
 
         dbg!(&raw);
 
-        let res = r.tags_list();
+        let res = r.tags_list(None, "");
 
         dbg!(&res);
 
@@ -0,0 +1,143 @@
+use std::fmt::Write;
+
+use anyhow::bail;
+use changelog::{utils::strip_links, ChangeLog, Release};
+
+use crate::{
+    config::ExportRpm,
+    utils::{civil_from_days, parse_iso_date, weekday, MONTHS},
+};
+
+/// The releases of `changelog` as the `%changelog` section of a RPM spec file, the newest first.
+/// Unreleased is not exported.
+///
+/// Each release starts with a `* Tue Jan 02 2024 Name <email> - 1.2.0-1` line, followed by one
+/// `- message` line per note: the scope is kept, the links are replaced by their text,
+/// and the context is joined to the message. Each release must have a `YYYY-MM-DD` date.
+/// With `since`, only the releases newer than this version are exported.
+pub fn export_rpm(changelog: &ChangeLog, options: &ExportRpm) -> anyhow::Result<String> {
+    let maintainer = options.maintainer.trim();
+
+    if !(maintainer.contains('<') && maintainer.ends_with('>')) {
+        bail!("The maintainer must be like \"Name <email>\", not \"{maintainer}\"");
+    }
+
+    let mut entries = Vec::new();
+
+    for (version, release) in changelog.releases_full() {
+        if options.since.as_ref().is_some_and(|since| version <= since) {
+            break;
+        }
+
+        entries.push(entry(release, maintainer, &options.release)?);
+    }
+
+    Ok(entries.join("\n"))
+}
+
+fn entry(release: &Release, maintainer: &str, rpm_release: &str) -> anyhow::Result<String> {
+    let version = release.version();
+
+    let Some(days) = release.title.title.as_deref().and_then(parse_iso_date) else {
+        bail!("The release {version} has no YYYY-MM-DD date");
+    };
+
+    let (year, month, day) = civil_from_days(days);
+
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        "* {} {} {day:02} {year} {maintainer} - {version}-{rpm_release}",
+        weekday(days),
+        MONTHS[month as usize - 1]
+    )
+    .unwrap();
+
+    let notes = release
+        .note_sections
+        .values()
+        .flat_map(|section| &section.notes)
+        .collect::<Vec<_>>();
+
+    if notes.is_empty() {
+        writeln!(output, "- New upstream release").unwrap();
+    }
+
+    for note in notes {
+        let mut text = match &note.scope {
+            Some(scope) => format!("{scope}: {}", note.plain_message()),
+            None => note.plain_message(),
+        };
+
+        for line in &note.context {
+            text.push(' ');
+            text.push_str(&strip_links(line.trim()));
+        }
+
+        // a '%' would start a macro
+        writeln!(output, "- {}", text.replace('%', "%%")).unwrap();
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::{de::parse_changelog, Version};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn options() -> ExportRpm {
+        ExportRpm {
+            file: None,
+            maintainer: "Alice Doe <alice@example.com>".into(),
+            release: "1".into(),
+            since: None,
+        }
+    }
+
+    #[test]
+    fn golden() {
+        let changelog = parse_changelog(include_str!("../tests/rpm/CHANGELOG.md")).unwrap();
+
+        assert_eq!(
+            export_rpm(&changelog, &options()).unwrap(),
+            include_str!("../tests/rpm/changelog")
+        );
+    }
+
+    #[test]
+    fn since() {
+        let changelog = parse_changelog(include_str!("../tests/rpm/CHANGELOG.md")).unwrap();
+
+        let mut options = options();
+        options.since = Some(Version::new(0, 1, 0));
+
+        assert_eq!(
+            export_rpm(&changelog, &options).unwrap(),
+            include_str!("../tests/rpm/changelog")
+                .split("\n\n")
+                .next()
+                .unwrap()
+                .to_owned()
+                + "\n"
+        );
+
+        options.since = Some(Version::new(0, 2, 0));
+        assert_eq!(export_rpm(&changelog, &options).unwrap(), "");
+    }
+
+    #[test]
+    fn invalid() {
+        let changelog = parse_changelog("## [1.0.0]\n\n### Added\n\n- a\n").unwrap();
+
+        let err = export_rpm(&changelog, &options()).unwrap_err();
+        assert_eq!(err.to_string(), "The release 1.0.0 has no YYYY-MM-DD date");
+
+        let mut bad_maintainer = options();
+        bad_maintainer.maintainer = "alice".into();
+        export_rpm(&changelog, &bad_maintainer).unwrap_err();
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt::Write;
+
+use changelog::{
+    query::NoteFilter,
+    ser::{serialize_release_section_note, OptionsRelease},
+    utils::UNRELEASED,
+    ChangeLog, ReleaseSectionNote,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Search;
+
+/// A note matched by `search`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub version: String,
+    pub section: String,
+    pub note: ReleaseSectionNote,
+}
+
+/// The notes matching the pattern of `options`, in document order.
+pub fn search(changelog: &ChangeLog, options: &Search) -> Vec<SearchMatch> {
+    let notes: Box<dyn Iterator<Item = _>> = Box::new(changelog.notes().matching(&options.pattern));
+
+    let notes = match &options.since {
+        Some(since) => Box::new(notes.since_version(since)),
+        None => notes,
+    };
+
+    let notes = match &options.section {
+        Some(section) => Box::new(notes.in_section(section)),
+        None => notes,
+    };
+
+    notes
+        .map(|n| SearchMatch {
+            version: n.version.map_or(UNRELEASED.to_owned(), ToString::to_string),
+            section: n.section.to_owned(),
+            note: n.note.clone(),
+        })
+        .collect()
+}
+
+/// Human readable list of the matches, grouped by release and section.
+pub fn summary(matches: &[SearchMatch]) -> String {
+    let mut output = String::new();
+
+    let mut last = None;
+
+    for m in matches {
+        if last != Some((&m.version, &m.section)) {
+            writeln!(output, "{} - {}", m.version, m.section).unwrap();
+            last = Some((&m.version, &m.section));
+        }
+
+        serialize_release_section_note(&mut output, &m.note, &OptionsRelease::default());
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+    use regex::Regex;
+
+    use crate::config::OutputFormat;
+
+    use super::*;
+
+    const INPUT: &str = "## [Unreleased]
+
+### Fixed
+
+- panic when the config is empty
+
+## [1.3.0] - 2024-03-01
+
+### Fixed
+
+- crash on resume
+  The worker could panic while resuming.
+
+## [1.2.0] - 2024-02-01
+
+### Fixed
+
+- panic on start
+";
+
+    fn options(pattern: &str) -> Search {
+        Search {
+            file: None,
+            pattern: Regex::new(pattern).unwrap(),
+            since: None,
+            section: None,
+            output_format: OutputFormat::Text,
+        }
+    }
+
+    #[test]
+    fn matches() {
+        let changelog = parse_changelog(INPUT).unwrap();
+
+        let mut options = options("panic");
+        assert_eq!(search(&changelog, &options).len(), 3);
+
+        options.since = Some("1.2.0".parse().unwrap());
+        options.section = Some("fixed".into());
+
+        assert_eq!(
+            summary(&search(&changelog, &options)),
+            "Unreleased - Fixed
+- panic when the config is empty
+1.3.0 - Fixed
+- crash on resume
+  The worker could panic while resuming.
+"
+        );
+    }
+}
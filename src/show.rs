@@ -0,0 +1,179 @@
+use std::borrow::Cow;
+
+use anyhow::bail;
+use changelog::{
+    ser::{serialize_release, OptionsRelease},
+    ChangeLog, Release,
+};
+
+use crate::{
+    config::{Show, ShowFormat},
+    plain::{to_plain_text, PlainTextOptions},
+    report::{to_json, ShowReport, SCHEMA_VERSION},
+};
+
+pub fn show(changelog: &ChangeLog, options: &Show) -> anyhow::Result<String> {
+    let releases = select_releases(changelog, options);
+
+    if releases.is_empty() {
+        bail!("No release found");
+    }
+
+    let output = match options.output_format {
+        ShowFormat::Markdown => serialize_releases(&releases),
+        ShowFormat::Plain => {
+            let plain_options = PlainTextOptions {
+                width: options.width,
+                heading_style: options.heading_style,
+                link_urls: options.link_urls,
+                title: false,
+            };
+
+            releases
+                .iter()
+                .map(|release| to_plain_text(release, &plain_options))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        ShowFormat::Json => to_json(&ShowReport {
+            schema_version: SCHEMA_VERSION,
+            releases: releases.into_iter().map(Cow::into_owned).collect(),
+        })?,
+    };
+
+    Ok(output)
+}
+
+fn select_releases<'a>(changelog: &'a ChangeLog, options: &Show) -> Vec<Cow<'a, Release>> {
+    if let Some(version) = &options.release {
+        return changelog
+            .get(version)
+            .map(Cow::Borrowed)
+            .into_iter()
+            .collect();
+    }
+
+    if options.latest {
+        return changelog
+            .releases()
+            .next()
+            .map(Cow::Borrowed)
+            .into_iter()
+            .collect();
+    }
+
+    if let Some(regex) = &options.version {
+        return changelog
+            .releases()
+            .filter(|release| regex.is_match(release.version()))
+            .map(Cow::Borrowed)
+            .collect();
+    }
+
+    changelog
+        .nth_release(options.n)
+        .map(|e| e.release())
+        .into_iter()
+        .collect()
+}
+
+fn serialize_releases(releases: &[Cow<'_, Release>]) -> String {
+    let mut output = String::new();
+
+    for (pos, release) in releases.iter().enumerate() {
+        debug!("show release: {:?}", release);
+
+        if pos != 0 {
+            output.push('\n');
+        }
+
+        serialize_release(
+            &mut output,
+            release,
+            &OptionsRelease {
+                serialize_title: false,
+                ..Default::default()
+            },
+        );
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use crate::config::HeadingStyle;
+
+    use super::*;
+
+    fn default_options() -> Show {
+        Show {
+            file: None,
+            release: None,
+            latest: false,
+            n: 0,
+            version: None,
+            output_format: ShowFormat::Markdown,
+            width: 72,
+            heading_style: HeadingStyle::Underline,
+            link_urls: false,
+        }
+    }
+
+    fn changelog() -> ChangeLog {
+        parse_changelog(include_str!("../tests/changelogs/CHANGELOG1.md")).unwrap()
+    }
+
+    #[test]
+    fn selectors() {
+        let changelog = changelog();
+
+        let mut options = default_options();
+        options.release = Some("2024.7".into());
+        let output = show(&changelog, &options).unwrap();
+        assert!(output.starts_with("### Added\n\n- add toasts\n"));
+
+        options.release = Some("v2024.7".into());
+        assert_eq!(show(&changelog, &options).unwrap(), output);
+
+        let mut options = default_options();
+        options.release = Some("unreleased".into());
+        let output = show(&changelog, &options).unwrap();
+        assert!(output.contains("- v1.1 German Translation\n"));
+
+        let mut options = default_options();
+        options.latest = true;
+        let output = show(&changelog, &options).unwrap();
+        assert!(output.contains("- fix french translation\n"));
+
+        let mut options = default_options();
+        options.release = Some("9.9.9".into());
+        show(&changelog, &options).unwrap_err();
+    }
+
+    #[test]
+    fn formats() {
+        let changelog = parse_changelog(
+            "## [1.0.0]\n\n### Fixed\n\n- crash in [#1](https://github.com/a/b/pull/1) by [@a](https://github.com/a)\n",
+        )
+        .unwrap();
+
+        let mut options = default_options();
+        options.output_format = ShowFormat::Plain;
+        let output = show(&changelog, &options).unwrap();
+        assert_eq!(output, "Fixed\n-----\n\n* crash in #1 by @a\n");
+
+        let mut options = default_options();
+        options.output_format = ShowFormat::Json;
+        let output = show(&changelog, &options).unwrap();
+        let report: ShowReport = serde_json::from_str(&output).unwrap();
+        assert_eq!(report.schema_version, SCHEMA_VERSION);
+        assert_eq!(
+            report.releases,
+            vec![changelog.releases().next().unwrap().clone()]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use changelog::{utils::parse_note_attribution, ChangeLog, Release};
+use serde::{Deserialize, Serialize};
+
+/// Number of contributors listed by `stats`.
+const TOP_CONTRIBUTORS: usize = 10;
+
+/// Counts of the notes of a changelog.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Unreleased first, then the releases, the newest first.
+    pub releases: Vec<ReleaseStats>,
+    /// Number of releases per year of their date. Releases without date are not counted.
+    pub releases_per_year: BTreeMap<String, usize>,
+    /// Authors of the most notes, from the ` by [@login](link)` suffixes.
+    pub top_contributors: Vec<ContributorStats>,
+    /// Unreleased is not counted in the totals, nor in the average.
+    pub total_releases: usize,
+    pub total_notes: usize,
+    pub average_notes_per_release: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseStats {
+    pub version: String,
+    pub date: Option<String>,
+    pub notes: usize,
+    pub sections: Vec<SectionStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionStats {
+    pub title: String,
+    pub notes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributorStats {
+    pub name: String,
+    pub notes: usize,
+}
+
+pub fn stats(changelog: &ChangeLog) -> Stats {
+    let mut releases = Vec::new();
+    let mut releases_per_year = BTreeMap::new();
+    let mut contributors: Vec<ContributorStats> = Vec::new();
+
+    for release in changelog.unreleased.iter().chain(changelog.releases()) {
+        let stats = release_stats(release);
+
+        if let Some(year) = stats.date.as_deref().and_then(year) {
+            *releases_per_year.entry(year.to_owned()).or_default() += 1;
+        }
+
+        releases.push(stats);
+    }
+
+    for note in changelog.notes() {
+        let Some(author) = parse_note_attribution(&note.note.message).author else {
+            continue;
+        };
+
+        match contributors.iter_mut().find(|c| c.name == author.name) {
+            Some(contributor) => contributor.notes += 1,
+            None => contributors.push(ContributorStats {
+                name: author.name,
+                notes: 1,
+            }),
+        }
+    }
+
+    // stable, so the first mentioned comes first on equality
+    contributors.sort_by_key(|c| std::cmp::Reverse(c.notes));
+    contributors.truncate(TOP_CONTRIBUTORS);
+
+    let total_releases = changelog.releases.len();
+    let total_notes = releases
+        .iter()
+        .skip(changelog.unreleased.iter().count())
+        .map(|r| r.notes)
+        .sum::<usize>();
+
+    let average_notes_per_release = if total_releases == 0 {
+        0.0
+    } else {
+        total_notes as f64 / total_releases as f64
+    };
+
+    Stats {
+        releases,
+        releases_per_year,
+        top_contributors: contributors,
+        total_releases,
+        total_notes,
+        average_notes_per_release,
+    }
+}
+
+fn release_stats(release: &Release) -> ReleaseStats {
+    let sections = release
+        .note_sections
+        .values()
+        .filter(|section| !section.notes.is_empty())
+        .map(|section| SectionStats {
+            title: section.title.clone(),
+            notes: section.notes.len(),
+        })
+        .collect::<Vec<_>>();
+
+    ReleaseStats {
+        version: release.version().to_owned(),
+        date: release.title.title.clone(),
+        notes: sections.iter().map(|s| s.notes).sum(),
+        sections,
+    }
+}
+
+/// Year of a `YYYY-MM-DD` date.
+fn year(date: &str) -> Option<&str> {
+    let year = date.get(..4)?;
+
+    (year.chars().all(|c| c.is_ascii_digit()) && date[4..].starts_with('-')).then_some(year)
+}
+
+impl Stats {
+    /// Human readable summary.
+    pub fn summary(&self) -> String {
+        let mut output = String::new();
+
+        for release in &self.releases {
+            match &release.date {
+                Some(date) => write!(output, "{} ({date})", release.version).unwrap(),
+                None => write!(output, "{}", release.version).unwrap(),
+            }
+            writeln!(output, ": {} notes", release.notes).unwrap();
+
+            for section in &release.sections {
+                writeln!(output, "  {}: {}", section.title, section.notes).unwrap();
+            }
+        }
+
+        if !self.releases_per_year.is_empty() {
+            writeln!(output, "\nReleases per year:").unwrap();
+
+            for (year, count) in &self.releases_per_year {
+                writeln!(output, "  {year}: {count}").unwrap();
+            }
+        }
+
+        if !self.top_contributors.is_empty() {
+            writeln!(output, "\nTop contributors:").unwrap();
+
+            for contributor in &self.top_contributors {
+                writeln!(output, "  @{}: {}", contributor.name, contributor.notes).unwrap();
+            }
+        }
+
+        writeln!(
+            output,
+            "\nTotal: {} releases, {} notes, {:.1} notes per release",
+            self.total_releases, self.total_notes, self.average_notes_per_release
+        )
+        .unwrap();
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn counts() {
+        let changelog = parse_changelog(include_str!("../tests/stats/CHANGELOG.md")).unwrap();
+
+        let stats = stats(&changelog);
+
+        assert_eq!(stats.total_releases, 3);
+        assert_eq!(stats.total_notes, 6);
+        assert_eq!(stats.average_notes_per_release, 2.0);
+        assert_eq!(
+            stats.releases_per_year,
+            BTreeMap::from([("2023".into(), 2), ("2024".into(), 1)])
+        );
+
+        assert_eq!(
+            stats.summary(),
+            "Unreleased: 1 notes
+  Added: 1
+1.1.0 (2024-02-10): 3 notes
+  Added: 2
+  Fixed: 1
+1.0.0 (2023-12-24): 2 notes
+  Fixed: 2
+0.1.0 (2023-06-01): 1 notes
+  Added: 1
+
+Releases per year:
+  2023: 2
+  2024: 1
+
+Top contributors:
+  @alice: 3
+  @bob: 1
+  @carol: 1
+
+Total: 3 releases, 6 notes, 2.0 notes per release
+"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use changelog::{
+    utils::{parse_note_attribution, strip_links},
+    Release, ReleaseSection, ReleaseSectionNote,
+};
+
+/// Sections of the new features, before the fixes.
+const FEATURE_SECTIONS: [&str; 2] = ["Added", "Changed"];
+/// Sections of the fixes, the most important first.
+const FIX_SECTIONS: [&str; 2] = ["Security", "Fixed"];
+
+/// A short "what's new" of a release, in at most `max_bullets` lines, without the PR links and authors.
+///
+/// The breaking changes come first: the notes of a section titled like "Breaking Changes",
+/// and the notes starting with "BREAKING". Then the top feature, from the feature section
+/// with the most notes, and the top fix, a security fix first. The other features and fixes
+/// fill the remaining bullets, alternately. In a section, the notes with the most context come first.
+pub fn summarize_release(release: &Release, max_bullets: usize) -> Vec<String> {
+    let is_breaking_section =
+        |section: &ReleaseSection| section.title.to_lowercase().contains("breaking");
+
+    let mut breaking = Vec::new();
+
+    for section in release.note_sections.values() {
+        for note in &section.notes {
+            if is_breaking_section(section) || note.message.starts_with("BREAKING") {
+                breaking.push(note);
+            }
+        }
+    }
+
+    let candidates = |titles: &[&str]| {
+        let mut sections = release
+            .note_sections
+            .values()
+            .filter(|section| {
+                !is_breaking_section(section)
+                    && titles
+                        .iter()
+                        .any(|t| t.eq_ignore_ascii_case(&section.title))
+            })
+            .collect::<Vec<_>>();
+
+        sections.sort_by_key(|section| {
+            let rank = titles
+                .iter()
+                .position(|t| t.eq_ignore_ascii_case(&section.title));
+            (std::cmp::Reverse(section.notes.len()), rank)
+        });
+
+        sections
+            .into_iter()
+            .flat_map(|section| by_impact(&section.notes))
+            .filter(|note| !breaking.contains(note))
+            .collect::<Vec<_>>()
+    };
+
+    let features = candidates(&FEATURE_SECTIONS);
+
+    // a security fix is the most impactful, whatever the size of its section
+    let mut fixes = Vec::new();
+    for title in FIX_SECTIONS {
+        fixes.extend(candidates(&[title]));
+    }
+
+    let mut features = features.into_iter();
+    let mut fixes = fixes.into_iter();
+    let mut notes = breaking;
+
+    loop {
+        let (feature, fix) = (features.next(), fixes.next());
+
+        if feature.is_none() && fix.is_none() {
+            break;
+        }
+
+        notes.extend(feature);
+        notes.extend(fix);
+    }
+
+    notes.into_iter().take(max_bullets).map(bullet).collect()
+}
+
+/// The notes with the most context first, keeping the order of the section on equality.
+fn by_impact(notes: &[ReleaseSectionNote]) -> Vec<&ReleaseSectionNote> {
+    let mut notes = notes.iter().collect::<Vec<_>>();
+    notes.sort_by_key(|note| std::cmp::Reverse(note.context.len()));
+    notes
+}
+
+fn bullet(note: &ReleaseSectionNote) -> String {
+    let message = strip_links(&parse_note_attribution(&note.message).message);
+
+    match &note.scope {
+        Some(scope) => format!("{scope}: {message}"),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INPUT: &str = "## [Unreleased]
+
+### Breaking Changes
+
+- drop the v1 config
+
+### Added
+
+- ui: dark theme in [#3](https://github.com/a/b/pull/3) by [@alice](https://github.com/alice)
+- export to [csv](https://en.wikipedia.org/wiki/Comma-separated_values)
+  With a header line.
+
+### Changed
+
+- faster startup
+
+### Fixed
+
+- typo in the help
+- crash on resume
+  The worker could panic.
+
+### Security
+
+- escape the input
+";
+
+    fn unreleased() -> Release {
+        parse_changelog(INPUT).unwrap().unreleased.unwrap()
+    }
+
+    #[test]
+    fn summary() {
+        assert_eq!(
+            summarize_release(&unreleased(), 3),
+            vec!["drop the v1 config", "export to csv", "escape the input"]
+        );
+
+        assert_eq!(
+            summarize_release(&unreleased(), 10),
+            vec![
+                "drop the v1 config",
+                "export to csv",
+                "escape the input",
+                "ui: dark theme",
+                "crash on resume",
+                "faster startup",
+                "typo in the help",
+            ]
+        );
+
+        assert!(summarize_release(&unreleased(), 0).is_empty());
+    }
+
+    #[test]
+    fn fixes_only() {
+        let release =
+            parse_changelog("## [1.0.1]\n\n### Fixed\n\n- BREAKING: remove the flag\n- leak\n")
+                .unwrap()
+                .releases()
+                .next()
+                .unwrap()
+                .clone();
+
+        assert_eq!(
+            summarize_release(&release, 3),
+            vec!["BREAKING: remove the flag", "leak"]
+        );
+    }
+}
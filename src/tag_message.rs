@@ -0,0 +1,51 @@
+use anyhow::bail;
+use changelog::{ChangeLog, Version};
+
+use crate::plain::{to_plain_text, PlainTextOptions};
+
+/// Width of the lines of a tag message.
+const WIDTH: usize = 72;
+
+/// Notes of a release as plain text, to use as the message of an annotated tag.
+/// Links are replaced by their text, headings are underlined, and lines are wrapped.
+pub fn tag_message(changelog: &ChangeLog, version: &Version) -> anyhow::Result<String> {
+    let Some(release) = changelog.releases.get(version) else {
+        bail!("Release {version} not found");
+    };
+
+    Ok(to_plain_text(
+        release,
+        &PlainTextOptions {
+            width: WIDTH,
+            ..Default::default()
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use changelog::de::parse_changelog;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn golden() {
+        let changelog = parse_changelog(include_str!("../tests/tag_message/CHANGELOG.md")).unwrap();
+
+        let output = tag_message(&changelog, &Version::from_str("1.4.0").unwrap()).unwrap();
+
+        assert_eq!(output, include_str!("../tests/tag_message/1.4.0.txt"));
+    }
+
+    #[test]
+    fn not_found() {
+        let changelog = parse_changelog(include_str!("../tests/tag_message/CHANGELOG.md")).unwrap();
+
+        let err = tag_message(&changelog, &Version::from_str("9.0.0").unwrap()).unwrap_err();
+
+        assert_eq!(err.to_string(), "Release 9.0.0 not found");
+    }
+}
@@ -6,7 +6,7 @@ use changelog::{
     ser::{serialize_changelog, Options},
 };
 
-use crate::config::MapMessageToSection;
+use crate::config::{MapMessageToSection, KEEP_A_CHANGELOG_MAP};
 
 #[test]
 fn validate_default_changelog() {
@@ -25,6 +25,21 @@ fn validate_default_map() {
     serde_json::de::from_str::<MapMessageToSection>(map).unwrap();
 }
 
+#[test]
+fn keep_a_changelog_map() {
+    let map = MapMessageToSection::try_new(Some(KEEP_A_CHANGELOG_MAP)).unwrap();
+
+    assert_eq!(map.map_section("feat"), Some("Added".into()));
+    assert_eq!(map.map_section("fix"), Some("Fixed".into()));
+    assert_eq!(map.map_section("SEC"), Some("Security".into()));
+    assert_eq!(map.map_section("doc"), None);
+
+    assert_eq!(
+        map.to_fmt_options().sort_options.section_order,
+        changelog::fmt::SortOptions::keep_a_changelog().section_order
+    );
+}
+
 #[test]
 fn format_default_changelog() {
     let input = include_str!("../res/CHANGELOG_DEFAULT.md");
@@ -1,4 +1,18 @@
+use std::{
+    fs,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 use anyhow::bail;
+use regex::Regex;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Repo {
@@ -30,7 +44,7 @@ pub fn try_get_repo(repo: Option<String>) -> Option<String> {
     };
 
     if repo.is_none() {
-        eprintln!("couldn't get the repo name. Example: \"wiiznokes/changen\".");
+        warn!("couldn't get the repo name. Example: \"wiiznokes/changen\".");
     }
 
     repo
@@ -64,9 +78,234 @@ impl TextInterpolate {
     }
 }
 
+/// Whether `file` is `path`, or is inside the directory `path`.
+pub fn is_under_path(file: &str, path: &str) -> bool {
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+
+    path.is_empty()
+        || file
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Regex matching the paths matched by `glob`. "*" and "?" don't match "/", "**" does.
+/// A glob without "/" is matched against the file name.
+pub fn glob_regex(glob: &str) -> Regex {
+    let mut regex = String::from(if glob.contains('/') { "^" } else { "(^|/)" });
+
+    let mut chars = glob.trim_start_matches('/').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+
+                // "a/**/b" matches "a/b"
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+
+    Regex::new(&regex).expect("the glob is escaped")
+}
+
+/// The paths under `root` matched by `glob`, sorted. Each component of the glob is matched
+/// against one directory level, so "**" is not supported. Unreadable directories are skipped.
+pub fn expand_glob(root: &Path, glob: &str) -> Vec<PathBuf> {
+    let mut paths = vec![root.to_path_buf()];
+
+    for component in glob.split('/').filter(|c| !c.is_empty()) {
+        let regex = glob_regex(component);
+
+        let mut matched = paths
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| regex.is_match(name))
+            })
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+
+        matched.sort();
+        paths = matched;
+    }
+
+    paths
+}
+
+/// Number of days since 1970-01-01, in UTC.
+pub fn today() -> i64 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    (secs / 86400) as i64
+}
+
+/// (year, month, day) of a number of days since 1970-01-01.
+/// https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn civil_from_days(days: i64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year as u64, month as u64, day as u64)
+}
+
+/// Number of days since 1970-01-01. The inverse of [`civil_from_days`].
+/// https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+pub fn days_from_civil(year: u64, month: u64, day: u64) -> i64 {
+    let (year, month, day) = (year as i64, month as i64, day as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+/// Abbreviated weekdays, starting with Thursday, the weekday of 1970-01-01.
+pub const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+pub const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Abbreviated weekday of a number of days since 1970-01-01, like "Mon".
+pub fn weekday(days: i64) -> &'static str {
+    WEEKDAYS[days.rem_euclid(7) as usize]
+}
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date.
+pub fn parse_iso_date(date: &str) -> Option<i64> {
+    let mut parts = date.trim().splitn(3, '-');
+
+    let year = parts.next().filter(|y| y.len() == 4)?.parse().ok()?;
+    let month = parts.next().filter(|m| m.len() == 2)?.parse().ok()?;
+    let day = parts.next().filter(|d| d.len() == 2)?.parse().ok()?;
+
+    ((1..=12).contains(&month) && (1..=31).contains(&day))
+        .then(|| days_from_civil(year, month, day))
+}
+
+/// Midnight UTC of a day, like "Fri, 01 Mar 2024 00:00:00 +0000".
+pub fn rfc2822(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{}, {day:02} {} {year} 00:00:00 +0000",
+        weekday(days),
+        MONTHS[month as usize - 1]
+    )
+}
+
+/// Escape the text of an XML element or attribute.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Number of columns of `text` in a terminal: the emojis and the CJK characters take two columns,
+/// the combining marks and the zero width characters none.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Wrap `text` at `width` columns, on word boundaries. Words longer than the width are not cut.
+/// The first line starts with `first_indent`, the others with `indent`.
+/// The columns are counted with [`display_width`].
+pub fn wrap(text: &str, width: usize, first_indent: &str, indent: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first_indent.to_owned();
+    let mut is_empty = true;
+
+    for word in text.split_whitespace() {
+        if !is_empty && display_width(&line) + 1 + display_width(word) > width {
+            lines.push(std::mem::replace(&mut line, indent.to_owned()));
+            is_empty = true;
+        }
+
+        if !is_empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        is_empty = false;
+    }
+
+    lines.push(line);
+
+    lines
+}
+
+/// Call `f` on every item, on at most `concurrency` threads at a time.
+/// The results are in the order of the items, whatever the order in which the calls finish.
+/// Once a call breaks, the items not started yet are skipped: their result is `None`.
+pub fn map_concurrent<T, R, F>(items: &[T], concurrency: usize, f: F) -> Vec<Option<R>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> ControlFlow<R, R> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let results = Mutex::new(items.iter().map(|_| None).collect::<Vec<_>>());
+
+    thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let i = next.fetch_add(1, Ordering::Relaxed);
+
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+
+                let result = match f(item) {
+                    ControlFlow::Continue(result) => result,
+                    ControlFlow::Break(result) => {
+                        stop.store(true, Ordering::Relaxed);
+                        result
+                    }
+                };
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
 #[cfg(test)]
 mod test {
-    use super::Repo;
+    use super::*;
 
     #[test]
     fn test() {
@@ -80,4 +319,72 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn glob() {
+        assert!(glob_regex("*.md").is_match("README.md"));
+        assert!(glob_regex("*.md").is_match("pkg/docs/README.md"));
+        assert!(!glob_regex("*.md").is_match("pkg/src/lib.rs"));
+
+        assert!(glob_regex("pkg/docs/**").is_match("pkg/docs/a/b.png"));
+        assert!(glob_regex("pkg/**/*.md").is_match("pkg/README.md"));
+        assert!(!glob_regex("pkg/*.md").is_match("pkg/docs/README.md"));
+
+        assert!(is_under_path("pkg/src/lib.rs", "./pkg/"));
+        assert!(!is_under_path("pkg2/src/lib.rs", "pkg"));
+    }
+
+    #[test]
+    fn width() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("🚀 fast"), 7);
+        assert_eq!(display_width("✨"), 2);
+        assert_eq!(display_width("❤\u{FE0F}"), 2);
+        assert_eq!(display_width("e\u{301}té"), 3);
+        assert_eq!(display_width("日本語"), 6);
+
+        assert_eq!(wrap("🚀🚀 🚀🚀 a", 9, "", ""), vec!["🚀🚀 🚀🚀", "a"]);
+    }
+
+    #[test]
+    fn concurrent() {
+        let items = (0..20u64).collect::<Vec<_>>();
+
+        // the first items finish last
+        let results = map_concurrent(&items, 4, |i| {
+            thread::sleep(std::time::Duration::from_millis(20 - i));
+            ControlFlow::Continue(i * 2)
+        });
+
+        assert_eq!(
+            results,
+            items.iter().map(|i| Some(i * 2)).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            map_concurrent(&items[..3], 0, |i| ControlFlow::Continue(*i)),
+            vec![Some(0), Some(1), Some(2)]
+        );
+        assert!(map_concurrent(&[] as &[u64], 4, |i| ControlFlow::Continue(*i)).is_empty());
+
+        // no item is started after a break
+        let results = map_concurrent(&items, 1, |i| match i {
+            2 => ControlFlow::Break(*i),
+            _ => ControlFlow::Continue(*i),
+        });
+        assert_eq!(results[..4], [Some(0), Some(1), Some(2), None]);
+        assert!(results[3..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn wrap_words() {
+        assert_eq!(
+            wrap("- a fairly long note about nothing", 12, "", "  "),
+            vec!["- a fairly", "  long note", "  about", "  nothing"]
+        );
+        assert_eq!(
+            wrap("https://a.very.long/url", 10, "  ", "  "),
+            vec!["  https://a.very.long/url"]
+        );
+    }
 }
@@ -0,0 +1,285 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    config::{Generate, MapMessageToSection},
+    git_provider::GitProvider,
+};
+
+/// An invalid entry of the configuration. `field` is the option, or the section of the map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub field: String,
+    pub message: String,
+}
+
+impl Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Check the options of the generation and the map, and return all the problems found,
+/// rather than stopping at the first one.
+pub fn validate_config(options: &Generate, map: &MapMessageToSection) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+
+    let mut problem = |field: &str, message: String| {
+        problems.push(ConfigProblem {
+            field: field.to_owned(),
+            message,
+        })
+    };
+
+    // keyword -> first section using it
+    let mut keywords = HashMap::new();
+
+    for (section, needles) in &map.0 {
+        let field = format!("map.{section}");
+
+        if section.trim().is_empty() {
+            problem("map", "A section has an empty title".into());
+        }
+
+        if needles.is_empty() {
+            problem(
+                &field,
+                "The section has no keyword, so nothing maps to it".into(),
+            );
+        }
+
+        let mut needles = needles.iter().collect::<Vec<_>>();
+        needles.sort();
+
+        for needle in needles {
+            if needle.trim().is_empty() {
+                problem(&field, "A keyword is empty".into());
+                continue;
+            }
+
+            match keywords.get(&needle.to_lowercase()) {
+                Some(first) if first != section => problem(
+                    &field,
+                    format!("The keyword \"{needle}\" is already mapped to {first}"),
+                ),
+                _ => {
+                    keywords.insert(needle.to_lowercase(), section.clone());
+                }
+            }
+        }
+    }
+
+    for (field, key) in [
+        ("section-trailer", &options.section_trailer),
+        ("skip-trailer", &options.skip_trailer),
+        ("message-trailer", &options.message_trailer),
+    ]
+    .into_iter()
+    .chain(
+        options
+            .context_footers
+            .iter()
+            .map(|key| ("context-footer", key)),
+    ) {
+        if let Some(message) = trailer_key_problem(key) {
+            problem(field, message);
+        }
+    }
+
+    for glob in &options.exclude_globs {
+        if glob.trim().is_empty() {
+            problem("exclude-glob", "The glob is empty".into());
+        }
+    }
+
+    if !options.omit_pr_link {
+        for placeholder in unknown_placeholders(&options.pr_link_format, &["id", "url"]) {
+            problem(
+                "pr-link-format",
+                format!("Unknown placeholder {{{placeholder}}}. Use {{id}} or {{url}}"),
+            );
+        }
+    }
+
+    if let Some(template) = &options.author_link_template {
+        if !template.contains("{author}") {
+            problem(
+                "author-link-template",
+                "The template doesn't contain {author}".into(),
+            );
+        }
+
+        for placeholder in unknown_placeholders(template, &["author"]) {
+            problem(
+                "author-link-template",
+                format!("Unknown placeholder {{{placeholder}}}. Use {{author}}"),
+            );
+        }
+    }
+
+    if let Some(repo) = &options.repo {
+        if !is_repo_name(repo) {
+            problem(
+                "repo",
+                format!("\"{repo}\" is not a repo name. Example: \"wiiznokes/changen\""),
+            );
+        }
+    }
+
+    for repo in &options.milestone_repos {
+        if !is_repo_name(repo) {
+            problem(
+                "milestone-repo",
+                format!("\"{repo}\" is not a repo name. Example: \"wiiznokes/changen\""),
+            );
+        }
+    }
+
+    if options.milestone.is_some() {
+        if options.repo.is_none() {
+            problem("milestone", "The repo is needed to list the PRs".into());
+        }
+
+        if options.provider != GitProvider::Github {
+            problem(
+                "provider",
+                format!(
+                    "Milestones are only supported with github, not {}",
+                    options.provider.to_string().trim()
+                ),
+            );
+        }
+    }
+
+    problems
+}
+
+fn trailer_key_problem(key: &str) -> Option<String> {
+    if key.trim().is_empty() {
+        Some("The trailer key is empty".into())
+    } else if key.contains(char::is_whitespace) || key.contains(':') {
+        Some(format!(
+            "\"{key}\" can't be a trailer key, it contains whitespace or ':'"
+        ))
+    } else {
+        None
+    }
+}
+
+/// Placeholders like `{name}` of `template` not in `known`.
+fn unknown_placeholders<'a>(template: &'a str, known: &[&str]) -> Vec<&'a str> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let name = &rest[..end];
+
+        if !known.contains(&name) {
+            unknown.push(name);
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    unknown
+}
+
+/// "owner/name", or "group/subgroup/name" for Gitlab.
+fn is_repo_name(repo: &str) -> bool {
+    repo.contains('/')
+        && repo
+            .split('/')
+            .all(|part| !part.is_empty() && !part.contains(char::is_whitespace))
+}
+
+#[cfg(test)]
+mod test {
+    use indexmap::IndexMap;
+    use pretty_assertions::assert_eq;
+
+    use crate::integration_test::DEFAULT_GENERATE;
+
+    use super::*;
+
+    fn fields(problems: &[ConfigProblem]) -> Vec<&str> {
+        problems.iter().map(|p| p.field.as_str()).collect()
+    }
+
+    #[test]
+    fn default_is_valid() {
+        assert_eq!(
+            validate_config(&DEFAULT_GENERATE, &MapMessageToSection::default()),
+            vec![]
+        );
+        assert_eq!(
+            validate_config(
+                &DEFAULT_GENERATE,
+                &MapMessageToSection::keep_a_changelog_default()
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn map() {
+        let map = MapMessageToSection(IndexMap::from([
+            ("Added".into(), ["feat".into(), "new".into()].into()),
+            ("Fixed".into(), ["fix".into(), "New".into()].into()),
+            ("Empty".into(), [].into()),
+        ]));
+
+        let problems = validate_config(&DEFAULT_GENERATE, &map);
+
+        assert_eq!(
+            problems,
+            vec![
+                ConfigProblem {
+                    field: "map.Fixed".into(),
+                    message: "The keyword \"New\" is already mapped to Added".into(),
+                },
+                ConfigProblem {
+                    field: "map.Empty".into(),
+                    message: "The section has no keyword, so nothing maps to it".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn options() {
+        let mut options = DEFAULT_GENERATE.clone();
+        options.skip_trailer = "Change log".into();
+        options.context_footers = vec!["Refs:".into()];
+        options.exclude_globs = vec!["".into()];
+        options.pr_link_format = "in [{number}]({url})".into();
+        options.author_link_template = Some("https://example.com/{user}".into());
+        options.repo = Some("changen".into());
+        options.milestone = Some("1.0".into());
+        options.provider = GitProvider::Gitlab;
+
+        let problems = validate_config(&options, &MapMessageToSection::default());
+
+        assert_eq!(
+            fields(&problems),
+            vec![
+                "skip-trailer",
+                "context-footer",
+                "exclude-glob",
+                "pr-link-format",
+                "author-link-template",
+                "author-link-template",
+                "repo",
+                "provider",
+            ]
+        );
+        assert_eq!(
+            problems[3].to_string(),
+            "pr-link-format: Unknown placeholder {number}. Use {id} or {url}"
+        );
+    }
+}
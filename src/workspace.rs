@@ -0,0 +1,94 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use changelog::{de::parse_changelog, ChangeLog};
+
+use crate::utils::expand_glob;
+
+/// Changelogs of the crates of a cargo workspace, relative to its root.
+pub const WORKSPACE_CHANGELOG_GLOB: &str = "crates/*/CHANGELOG.md";
+
+/// Parse the changelog of each crate of the workspace at `root`, keyed by the name of the crate directory.
+/// A changelog which can't be read or parsed is skipped with a warning, without failing the others.
+pub fn parse_workspace_changelogs(root: &Path) -> HashMap<String, ChangeLog> {
+    parse_changelogs_matching(root, WORKSPACE_CHANGELOG_GLOB)
+}
+
+/// Like [`parse_workspace_changelogs`], with the changelogs matched by `glob`, like "packages/*/CHANGES.md".
+/// The key is the name of the directory of the changelog.
+pub fn parse_changelogs_matching(root: &Path, glob: &str) -> HashMap<String, ChangeLog> {
+    let mut changelogs = HashMap::new();
+
+    for path in expand_glob(root, glob) {
+        let Some(name) = path
+            .parent()
+            .and_then(Path::file_name)
+            .and_then(|name| name.to_str())
+        else {
+            continue;
+        };
+
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(e) => {
+                warn!("Can't read {}, skipped: {e}", path.display());
+                continue;
+            }
+        };
+
+        match parse_changelog(&input) {
+            Ok(changelog) => {
+                debug!("{} parsed", path.display());
+                changelogs.insert(name.to_owned(), changelog);
+            }
+            Err(e) => warn!("Can't parse {}, skipped: {e}", path.display()),
+        }
+    }
+
+    changelogs
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn workspace() {
+        let root = std::env::temp_dir().join("changen_workspace");
+        let _ = fs::remove_dir_all(&root);
+
+        for name in ["core", "cli", "broken", "no_changelog"] {
+            fs::create_dir_all(root.join("crates").join(name)).unwrap();
+        }
+
+        fs::write(
+            root.join("crates/core/CHANGELOG.md"),
+            "## [1.0.0]\n\n### Added\n\n- init\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("crates/cli/CHANGELOG.md"),
+            "## [Unreleased]\n\n### Fixed\n\n- crash\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("crates/broken/CHANGELOG.md"),
+            "## [1.0.0]\n\n## [1.0.0]\n",
+        )
+        .unwrap();
+        fs::write(root.join("CHANGELOG.md"), "## [2.0.0]\n").unwrap();
+
+        let changelogs = parse_workspace_changelogs(&root);
+
+        let mut names = changelogs.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["cli", "core"]);
+
+        assert_eq!(
+            changelogs["core"].last_version().map(|v| v.to_string()),
+            Some("1.0.0".into())
+        );
+        assert!(changelogs["cli"].unreleased.is_some());
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{bail, Result};
+use changelog::{de::parse_changelog, utils::YANKED};
+
+use crate::config::Yank;
+
+/// Add (or remove) the "[YANKED]" marker of a release.
+/// Only the release title, and the optional warning line, are modified.
+pub fn yank(input: &str, options: &Yank, yanked: bool) -> Result<String> {
+    let changelog = parse_changelog(input)?;
+
+    let Some(release) = changelog.get(&options.version.to_string()) else {
+        bail!("Release {} not found", options.version);
+    };
+
+    match (release.title.yanked, yanked) {
+        (true, true) => bail!("Release {} is already yanked", options.version),
+        (false, false) => bail!("Release {} is not yanked", options.version),
+        _ => {}
+    }
+
+    // the version as written, like "v1.0.1"
+    let heading = format!("## [{}]", release.title.version);
+
+    let mut start = 0;
+
+    for line in input.split_inclusive('\n') {
+        let end = start + line.len();
+
+        if line.starts_with(&heading) {
+            let title = line.trim_end();
+            let newline = &line[title.len()..];
+            let rest = &input[end..];
+
+            let mut output = String::from(&input[..start]);
+
+            if yanked {
+                output.push_str(&format!("{title} {YANKED}{newline}"));
+
+                if let Some(warning) = &options.warning {
+                    if newline.is_empty() {
+                        output.push('\n');
+                    }
+                    output.push_str(&format!("\n{warning}\n"));
+                }
+
+                output.push_str(rest);
+            } else {
+                let title = title.strip_suffix(YANKED).unwrap_or(title).trim_end();
+
+                output.push_str(&format!("{title}{newline}"));
+
+                // the warning line added when yanking
+                let rest = options
+                    .warning
+                    .as_ref()
+                    .and_then(|warning| rest.strip_prefix(&format!("\n{warning}\n")))
+                    .unwrap_or(rest);
+
+                output.push_str(rest);
+            }
+
+            return Ok(output);
+        }
+
+        start = end;
+    }
+
+    bail!("Title of the release {} not found", options.version);
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use changelog::Version;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog
+
+## [Unreleased]
+
+## [1.0.1] - 2024-02-01
+
+Some  prose.
+
+### Fixed
+
+- a
+
+## [1.0.0] - 2024-01-01
+
+- b
+";
+
+    fn options(version: &str, warning: Option<&str>) -> Yank {
+        Yank {
+            file: None,
+            version: Version::from_str(version).unwrap(),
+            warning: warning.map(Into::into),
+            stdout: false,
+        }
+    }
+
+    #[test]
+    fn yank_unyank() {
+        let options = options("1.0.0", Some("**Do not use this release.**"));
+
+        let yanked = yank(CHANGELOG, &options, true).unwrap();
+
+        assert_eq!(
+            yanked,
+            CHANGELOG.replace(
+                "## [1.0.0] - 2024-01-01\n",
+                "## [1.0.0] - 2024-01-01 [YANKED]\n\n**Do not use this release.**\n"
+            )
+        );
+
+        assert_eq!(yank(&yanked, &options, false).unwrap(), CHANGELOG);
+    }
+
+    #[test]
+    fn keep_header_prose() {
+        let input = CHANGELOG.replace("## [1.0.1]", "## [1.0.1](https://a.b/1.0.1)");
+
+        let yanked = yank(&input, &options("1.0.1", Some("Broken.")), true).unwrap();
+
+        assert_eq!(
+            yanked,
+            input.replace(
+                "## [1.0.1](https://a.b/1.0.1) - 2024-02-01\n\nSome  prose.",
+                "## [1.0.1](https://a.b/1.0.1) - 2024-02-01 [YANKED]\n\nBroken.\n\nSome  prose."
+            )
+        );
+
+        let unyanked = yank(&yanked, &options("1.0.1", None), false).unwrap();
+
+        assert_eq!(
+            unyanked,
+            input.replace("\n\nSome  prose.", "\n\nBroken.\n\nSome  prose.")
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let e = yank(CHANGELOG, &options("2.0.0", None), true).unwrap_err();
+        assert_eq!(e.to_string(), "Release 2.0.0 not found");
+
+        let e = yank(CHANGELOG, &options("1.0.0", None), false).unwrap_err();
+        assert_eq!(e.to_string(), "Release 1.0.0 is not yanked");
+
+        let yanked = yank(CHANGELOG, &options("1.0.0", None), true).unwrap();
+        let e = yank(&yanked, &options("1.0.0", None), true).unwrap_err();
+        assert_eq!(e.to_string(), "Release 1.0.0 is already yanked");
+    }
+}
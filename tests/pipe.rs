@@ -0,0 +1,382 @@
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+fn changen(args: &[&str], stdin: &str) -> Output {
+    changen_in(Path::new(env!("CARGO_MANIFEST_DIR")), args, stdin)
+}
+
+fn changen_in(dir: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_changen"))
+        .args(args)
+        .current_dir(dir)
+        // .cargo/config.toml sets it, and it would take precedence over -q/-v
+        .env_remove("RUST_LOG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+const MESSY: &str = "## [Unreleased]\n### Fixed\n* crash\n\n\n## [1.0.0]\n### Added\n- init\n";
+
+const FORMATTED: &str =
+    "## [Unreleased]\n\n### Fixed\n\n- crash\n\n## [1.0.0]\n\n### Added\n\n- init\n";
+
+#[test]
+fn fmt_stdin_to_stdout() {
+    let output = changen(&["fmt", "--file", "-"], MESSY);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), FORMATTED);
+}
+
+#[test]
+fn generate_to_stdout() {
+    let output = changen(
+        &[
+            "generate",
+            "--file",
+            "-",
+            "--provider",
+            "none",
+            "--since",
+            "HEAD",
+            "--until",
+            "HEAD",
+        ],
+        MESSY,
+    );
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), FORMATTED);
+}
+
+#[test]
+fn generate_json_report_needs_its_own_stdout() {
+    let output = changen(&["generate", "--stdout", "--output-format", "json"], MESSY);
+    assert!(!output.status.success());
+
+    let output = changen(
+        &["generate", "--file", "-", "--output-format", "json"],
+        MESSY,
+    );
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("The JSON report can't share stdout"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn generate_output_to_stdout() {
+    let dir = std::env::temp_dir().join("changen_generate_output");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    fs::write(dir.join("CHANGELOG.md"), MESSY).unwrap();
+    git(&dir, &["init", "-q", "-b", "main"]);
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "chore: init"]);
+
+    let output = changen_in(
+        &dir,
+        &[
+            "generate",
+            "--output",
+            "-",
+            "--provider",
+            "none",
+            "--since",
+            "HEAD",
+            "--until",
+            "HEAD",
+        ],
+        "",
+    );
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), FORMATTED);
+    assert_eq!(fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(), MESSY);
+}
+
+const DUPLICATE_SECTION: &str = "## [Unreleased]\n\n### Fixed\n\n- crash\n\n### Fixed\n\n- leak\n";
+
+#[test]
+fn validate_diagnostics_on_stderr() {
+    let output = changen(&["validate", "--file", "-"], DUPLICATE_SECTION);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "");
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("warning: line 7"), "{stderr}");
+    assert!(
+        stderr.contains("Changelog parsed with success!"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn validate_json_on_stdout() {
+    let output = changen(
+        &["validate", "--file", "-", "--output-format", "json"],
+        DUPLICATE_SECTION,
+    );
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with('{'), "{stdout}");
+    assert!(stdout.contains("\"duplicate-section\""), "{stdout}");
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("duplicate"), "{stderr}");
+}
+
+#[test]
+fn format_alias_of_output_format() {
+    let output = changen(&["stats", "--file", "-", "--output-format", "json"], MESSY);
+    let alias = changen(&["stats", "--file", "-", "--format", "json"], MESSY);
+
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(b"{"));
+    assert_eq!(output.stdout, alias.stdout);
+}
+
+#[test]
+fn quiet_and_verbose() {
+    let output = changen(&["-q", "validate", "--file", "-"], DUPLICATE_SECTION);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+
+    let output = changen(&["-v", "fmt", "--file", "-"], MESSY);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), FORMATTED);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("[DEBUG changen"));
+}
+
+#[test]
+fn diff_files() {
+    let output = changen(
+        &[
+            "diff",
+            "--file",
+            "tests/diff/old.md",
+            "--file",
+            "tests/diff/new.md",
+        ],
+        "",
+    );
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "- 0.1.0\n~ 1.0.0\n  Added\n    + new command\n"
+    );
+}
+
+#[test]
+fn convert_stdin_to_stdout() {
+    let output = changen(
+        &["convert", "-", "--from", "markdown", "--to", "json"],
+        MESSY,
+    );
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json = String::from_utf8(output.stdout).unwrap();
+
+    let output = changen(
+        &["convert", "-", "--from", "json", "--to", "markdown"],
+        &json,
+    );
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), FORMATTED);
+}
+
+#[test]
+fn yank_prefixed_version() {
+    let output = changen(&["yank", "v1.0.0", "--file", "-", "--stdout"], MESSY);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("## [1.0.0] [YANKED]"));
+}
+
+#[test]
+fn summarize_prefixed_version() {
+    let output = changen(&["summarize", "v1.0.0", "--file", "-"], MESSY);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "- init\n");
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    git_dated(dir, "2024-01-01T00:00:00Z", args);
+}
+
+/// Run git, with `date` as the date of the commits.
+fn git_dated(dir: &Path, date: &str, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_COMMITTER_DATE", date)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_AUTHOR_NAME", "alice")
+        .env("GIT_AUTHOR_EMAIL", "alice@example.com")
+        .env("GIT_COMMITTER_NAME", "alice")
+        .env("GIT_COMMITTER_EMAIL", "alice@example.com")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn path_filter_keeps_merges() {
+    let dir = std::env::temp_dir().join("changen_path_filter_merges");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let commit = |file: &str, title: &str| {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, title).unwrap();
+        git(&dir, &["add", "-A"]);
+        git(&dir, &["commit", "-q", "-m", title]);
+    };
+
+    git(&dir, &["init", "-q", "-b", "main"]);
+    commit("README.md", "chore: init");
+    git(&dir, &["checkout", "-q", "-b", "feature"]);
+    commit("pkg/lib.rs", "wip");
+    git(&dir, &["checkout", "-q", "main"]);
+    commit("other/lib.rs", "fix: other package");
+    git(
+        &dir,
+        &[
+            "merge",
+            "-q",
+            "--no-ff",
+            "feature",
+            "-m",
+            "feat: merged in pkg",
+        ],
+    );
+
+    let output = changen_in(
+        &dir,
+        &[
+            "generate",
+            "--file",
+            "-",
+            "--provider",
+            "none",
+            "--merges-only",
+            "--path",
+            "pkg",
+        ],
+        "",
+    );
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "## [Unreleased]\n\n### Added\n\n- merged in pkg\n"
+    );
+}
+
+#[test]
+fn generate_sort_notes_by_date() {
+    let dir = std::env::temp_dir().join("changen_sort_notes_by_date");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    git(&dir, &["init", "-q", "-b", "main"]);
+
+    // rebased commits keep dates out of order
+    for (title, date) in [
+        ("feat: b", "2024-02-02T00:00:00Z"),
+        ("feat: c", "2024-02-03T00:00:00Z"),
+        ("feat: a", "2024-02-01T00:00:00Z"),
+    ] {
+        git_dated(&dir, date, &["commit", "-q", "--allow-empty", "-m", title]);
+    }
+
+    let output = changen_in(
+        &dir,
+        &[
+            "generate",
+            "--file",
+            "-",
+            "--provider",
+            "none",
+            "--sort-notes-by-date",
+        ],
+        "## [Unreleased]\n\n### Added\n\n- older\n",
+    );
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "## [Unreleased]\n\n### Added\n\n- c\n- b\n- a\n- older\n"
+    );
+}